@@ -0,0 +1,21 @@
+import { AudioContext } from '../index.mjs';
+
+// Render in real time without any sound card and pipe raw PCM to stdout, e.g.:
+// node examples/stream-sink.mjs | ffplay -f s16le -ar 48000 -ch_layout stereo -
+const audioContext = new AudioContext({
+  sampleRate: 48000,
+  sinkId: {
+    type: 'stream',
+    stream: process.stdout,
+    format: 's16',
+  },
+});
+
+const osc = audioContext.createOscillator();
+osc.frequency.value = 220;
+osc.connect(audioContext.destination);
+osc.start();
+
+setTimeout(async () => {
+  await audioContext.close();
+}, 5000);
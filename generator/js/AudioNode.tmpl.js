@@ -4,15 +4,79 @@ const {
   throwSanitizedError,
 } = require('./lib/errors.js');
 const {
+  isFunction,
   kEnumerableProperty,
   kHiddenProperty,
 } = require('./lib/utils.js');
 const {
-  kNapiObj
+  kNapiObj,
+  kCreateTap,
+  kProfilerProbes,
+  kParamOwner,
+  kConnections,
+  kBatchedNodes,
+  kDisableBatching,
 } = require('./lib/symbols.js');
 
 const AudioParam = require('./AudioParam.js');
 
+// the node that renders the input of the destination of a connection
+function destinationNode(destination) {
+  return destination instanceof AudioParam ? destination[kParamOwner] : destination;
+}
+
+// the nodes that can be reached from the outputs of the node
+function reachableNodes(node) {
+  const reachable = new Set();
+  const stack = [node];
+
+  while (stack.length > 0) {
+    for (let { destination } of stack.pop()[kConnections]) {
+      const next = destinationNode(destination);
+
+      // the params of the AudioListener do not belong to a node
+      if (next !== undefined && !reachable.has(next)) {
+        reachable.add(next);
+        stack.push(next);
+      }
+    }
+  }
+
+  return reachable;
+}
+
+// the outputs of a batch of render quanta are delayed, which cannot be done
+// in a cycle, cf. AudioWorkletNode batchQuanta
+function disableBatchingInCycles(context) {
+  if (context[kBatchedNodes] === undefined) {
+    return;
+  }
+
+  for (let node of context[kBatchedNodes]) {
+    if (reachableNodes(node).has(node)) {
+      node[kDisableBatching]();
+      context[kBatchedNodes].delete(node);
+    }
+  }
+}
+
+// forget the connections removed by disconnect, undefined matches any value
+function forgetConnections(node, destination, output, input) {
+  const connections = node[kConnections];
+
+  for (let i = connections.length - 1; i >= 0; i--) {
+    const connection = connections[i];
+
+    if (
+      (destination === undefined || connection.destination === destination)
+      && (output === undefined || connection.output === output)
+      && (input === undefined || connection.input === input)
+    ) {
+      connections.splice(i, 1);
+    }
+  }
+}
+
 class AudioNode extends EventTarget {
   #context = null;
 
@@ -33,6 +97,12 @@ class AudioNode extends EventTarget {
       value: options[kNapiObj],
       ...kHiddenProperty,
     });
+
+    // the connections of the outputs of the node, cf. connect
+    Object.defineProperty(this, kConnections, {
+      value: [],
+      ...kHiddenProperty,
+    });
   }
 
   get context() {
@@ -174,6 +244,24 @@ ${d.attributes(d.node).filter(attr => d.name(attr) !== 'context').map(attr => {
       throwSanitizedError(err);
     }
 
+    this[kConnections].push({ destination: args[0], output, input });
+    disableBatchingInCycles(this.#context);
+
+    // the upstream graph renders the last connected node right after this
+    // one, reconnect the probes of the profilers so that the time of the new
+    // destination is not attributed to this node, cf. AudioNodeProfiler
+    if (this[kProfilerProbes] !== undefined) {
+      for (let probe of this[kProfilerProbes]) {
+        try {
+          this[kNapiObj].disconnect(probe);
+        } catch (err) {
+          // the probe has been disconnected by `disconnect()`
+        }
+
+        this[kNapiObj].connect(probe, 0, 0);
+      }
+    }
+
     // return given destination
     return args[0];
   }
@@ -196,10 +284,13 @@ ${d.attributes(d.node).filter(attr => d.name(attr) !== 'context').map(attr => {
         });
 
         try {
-          return this[kNapiObj].disconnect(destination, output, input);
+          this[kNapiObj].disconnect(destination, output, input);
         } catch (err) {
           throwSanitizedError(err);
         }
+
+        forgetConnections(this, args[0], output, input);
+        return;
       } else {
         throw new TypeError("Failed to execute 'disconnect' on 'AudioNode': : Overload resolution failed");
       }
@@ -212,10 +303,13 @@ ${d.attributes(d.node).filter(attr => d.name(attr) !== 'context').map(attr => {
         });
 
         try {
-          return this[kNapiObj].disconnect(destination, output);
+          this[kNapiObj].disconnect(destination, output);
         } catch (err) {
           throwSanitizedError(err);
         }
+
+        forgetConnections(this, args[0], output);
+        return;
       } else {
         throw new TypeError("Failed to execute 'disconnect' on 'AudioNode': : Overload resolution failed");
       }
@@ -224,10 +318,13 @@ ${d.attributes(d.node).filter(attr => d.name(attr) !== 'context').map(attr => {
         const destination = args[0][kNapiObj];
 
         try {
-          return this[kNapiObj].disconnect(destination);
+          this[kNapiObj].disconnect(destination);
         } catch (err) {
           throwSanitizedError(err);
         }
+
+        forgetConnections(this, args[0]);
+        return;
       } else if (Number.isFinite(args[0])) {
         const output = conversions['unsigned long'](args[0], {
           enforceRange: true,
@@ -235,10 +332,13 @@ ${d.attributes(d.node).filter(attr => d.name(attr) !== 'context').map(attr => {
         });
 
         try {
-          return this[kNapiObj].disconnect(output);
+          this[kNapiObj].disconnect(output);
         } catch (err) {
           throwSanitizedError(err);
         }
+
+        forgetConnections(this, undefined, output);
+        return;
       }
 
       // Note that we don't have the "overload resolution failed" branch here
@@ -252,6 +352,75 @@ ${d.attributes(d.node).filter(attr => d.name(attr) !== 'context').map(attr => {
     } catch (err) {
       throwSanitizedError(err);
     }
+
+    forgetConnections(this);
+  }
+
+  // non spec compliant, copy the output of the node to a callback off the
+  // render thread, without altering the graph
+  tap(options, callback) {
+    if (!(this instanceof AudioNode)) {
+      throw new TypeError("Invalid Invocation: Value of 'this' must be of type 'AudioNode'");
+    }
+
+    if (arguments.length < 2) {
+      throw new TypeError(\`Failed to execute 'tap' on 'AudioNode': 2 arguments required, but only \${arguments.length} present\`);
+    }
+
+    if (options === undefined || options === null) {
+      options = {};
+    }
+
+    if (typeof options !== 'object') {
+      throw new TypeError("Failed to execute 'tap' on 'AudioNode': parameter 1 is not of type 'AudioNodeTapOptions'");
+    }
+
+    if (!isFunction(callback)) {
+      throw new TypeError("Failed to execute 'tap' on 'AudioNode': parameter 2 is not of type 'Function'");
+    }
+
+    const parsedOptions = {};
+
+    if (options.channels !== undefined) {
+      parsedOptions.channels = conversions['unsigned long'](options.channels, {
+        enforceRange: true,
+        context: \`Failed to execute 'tap' on 'AudioNode': Failed to read the 'channels' property from AudioNodeTapOptions: The provided value (\${options.channels})\`,
+      });
+
+      if (parsedOptions.channels < 1 || parsedOptions.channels > 32) {
+        throw new DOMException(\`Failed to execute 'tap' on 'AudioNode': The number of channels provided (\${parsedOptions.channels}) is outside the range [1, 32]\`, 'NotSupportedError');
+      }
+    } else {
+      parsedOptions.channels = 2;
+    }
+
+    if (options.bufferSize !== undefined) {
+      parsedOptions.bufferSize = conversions['unsigned long'](options.bufferSize, {
+        enforceRange: true,
+        context: \`Failed to execute 'tap' on 'AudioNode': Failed to read the 'bufferSize' property from AudioNodeTapOptions: The provided value (\${options.bufferSize})\`,
+      });
+
+      if (parsedOptions.bufferSize === 0) {
+        throw new DOMException("Failed to execute 'tap' on 'AudioNode': The buffer size must be greater than 0", 'IndexSizeError');
+      }
+    } else {
+      parsedOptions.bufferSize = 1024;
+    }
+
+    if (options.output !== undefined) {
+      parsedOptions.output = conversions['unsigned long'](options.output, {
+        enforceRange: true,
+        context: \`Failed to execute 'tap' on 'AudioNode': Failed to read the 'output' property from AudioNodeTapOptions: The provided value (\${options.output})\`,
+      });
+    } else {
+      parsedOptions.output = 0;
+    }
+
+    if (parsedOptions.output >= this.numberOfOutputs) {
+      throw new DOMException(\`Failed to execute 'tap' on 'AudioNode': The output index provided (\${parsedOptions.output}) is outside the range [0, \${this.numberOfOutputs})\`, 'IndexSizeError');
+    }
+
+    return this.context[kCreateTap](this, parsedOptions, callback);
   }
 }
 
@@ -279,6 +448,7 @@ Object.defineProperties(AudioNode.prototype, {
   }).join('')}
   connect: kEnumerableProperty,
   disconnect: kEnumerableProperty,
+  tap: kEnumerableProperty,
 });
 
 module.exports = AudioNode;
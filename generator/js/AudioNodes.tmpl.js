@@ -14,6 +14,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -315,6 +316,7 @@ module.exports = (jsExport, nativeBinding) => {
         return `
       this.#${d.name(param)} = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].${d.name(param)},
+        [kParamOwner]: this,
       });`;
       }).join('')}
     }
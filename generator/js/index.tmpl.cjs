@@ -1,4 +1,5 @@
 const nativeBinding = require('./load-native.cjs');
+const { parseJackOptions } = require('./js/lib/sinks.js');
 const jsExport = {};
 
 // --------------------------------------------------------------------------
@@ -27,9 +28,13 @@ jsExport.AudioListener = require('./js/AudioListener.js');
 jsExport.AudioWorklet = require('./js/AudioWorklet.js');
 jsExport.AudioParamMap = require('./js/AudioParamMap.js');
 jsExport.AudioRenderCapacity = require('./js/AudioRenderCapacity.js');
+jsExport.AudioPlaybackStats = require('./js/AudioPlaybackStats.js');
+jsExport.AudioNodeProfiler = require('./js/AudioNodeProfiler.js');
+jsExport.AudioNodeTap = require('./js/AudioNodeTap.js');
 
 jsExport.PeriodicWave = require('./js/PeriodicWave.js')(jsExport, nativeBinding);
 jsExport.AudioBuffer = require('./js/AudioBuffer.js')(jsExport, nativeBinding);
+jsExport.AudioRingBuffer = require('./js/AudioRingBuffer.js')(jsExport, nativeBinding);
 
 // --------------------------------------------------------------------------
 // Promisify MediaDevices API
@@ -42,12 +47,26 @@ jsExport.mediaDevices.enumerateDevices = async function enumerateDevices() {
   return Promise.resolve(list);
 };
 
+// non standard, list audio backends that can be used in AudioContextOptions
+const enumerateBackendsSync = nativeBinding.mediaDevices.enumerateBackends;
+jsExport.mediaDevices.enumerateBackends = async function enumerateBackends() {
+  const list = enumerateBackendsSync();
+  return Promise.resolve(list);
+};
+
 const getUserMediaSync = nativeBinding.mediaDevices.getUserMedia;
 jsExport.mediaDevices.getUserMedia = async function getUserMedia(options) {
   if (options === undefined) {
     throw new TypeError('Failed to execute "getUserMedia" on "MediaDevices": audio must be requested');
   }
 
+  // non standard, expose the input as the ports of a JACK client
+  if (options.audio && options.audio.backend === 'jack') {
+    const context = `Failed to execute 'getUserMedia' on 'MediaDevices'`;
+    const jack = parseJackOptions(options.audio.jack, true, context);
+    options = { ...options, audio: { ...options.audio, jack } };
+  }
+
   const stream = getUserMediaSync(options);
   return Promise.resolve(stream);
 };
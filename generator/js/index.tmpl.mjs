@@ -24,9 +24,13 @@ export const {
   AudioWorklet,
   AudioParamMap,
   AudioRenderCapacity,
+  AudioPlaybackStats,
+  AudioNodeProfiler,
+  AudioNodeTap,
 
   PeriodicWave,
   AudioBuffer,
+  AudioRingBuffer,
   // generated nodes
 ${d.nodes.map(n => `  ${d.name(n)},`).join('\n')}
 
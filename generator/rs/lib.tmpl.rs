@@ -12,6 +12,10 @@ mod audio_node;
 
 // halpers
 mod utils;
+// outputs of AudioContext not handled by the upstream crate
+mod sinks;
+// realtime scheduling of the audio threads
+mod thread_scheduling;
 // Web Audio API
 mod audio_context;
 use crate::audio_context::NapiAudioContext;
@@ -23,6 +27,16 @@ mod audio_listener;
 use crate::audio_listener::NapiAudioListener;
 mod audio_render_capacity;
 use crate::audio_render_capacity::NapiAudioRenderCapacity;
+mod audio_playback_stats;
+use crate::audio_playback_stats::NapiAudioPlaybackStats;
+mod audio_clock;
+mod offline_render_capacity;
+mod audio_node_profiler;
+use crate::audio_node_profiler::NapiAudioNodeProfiler;
+mod audio_node_tap;
+use crate::audio_node_tap::NapiAudioNodeTap;
+mod audio_ring_buffer;
+use crate::audio_ring_buffer::NapiAudioRingBuffer;
 mod audio_buffer;
 use crate::audio_buffer::NapiAudioBuffer;
 mod periodic_wave;
@@ -37,13 +51,20 @@ use crate::${d.slug(n)}::${d.napiName(n)};`}).join('')}
 // AudioWorklet internals
 use crate::audio_worklet_node::{
     exit_audio_worklet_global_scope,
+    replace_audio_worklet_processor,
     run_audio_worklet_global_scope,
 };
+mod audio_worklet_shared_buffers;
+use crate::audio_worklet_shared_buffers::{share_audio_buffer, take_shared_audio_buffer};
+mod audio_worklet_stats;
+mod audio_worklet_wasm;
+use crate::audio_worklet_wasm::NapiAudioWorkletWasmModule;
 
 // MediaDevices & MediaStream API
 mod media_streams;
 use crate::media_streams::NapiMediaStream;
 mod media_devices;
+use crate::media_devices::napi_enumerate_backends;
 use crate::media_devices::napi_enumerate_devices;
 use crate::media_devices::napi_get_user_media;
 
@@ -67,6 +88,18 @@ fn init(mut exports: JsObject, env: Env) -> Result<()> {
     let napi_class = NapiOfflineAudioContext::create_js_class(&env)?;
     exports.set_named_property("OfflineAudioContext", napi_class)?;
 
+    // non spec compliant, opt-in render time profiler of audio nodes
+    let napi_class = NapiAudioNodeProfiler::create_js_class(&env)?;
+    exports.set_named_property("AudioNodeProfiler", napi_class)?;
+
+    // non spec compliant, copy of the output of a node to a JS callback
+    let napi_class = NapiAudioNodeTap::create_js_class(&env)?;
+    exports.set_named_property("AudioNodeTap", napi_class)?;
+
+    // non spec compliant, queue of audio frames shared between threads
+    let napi_class = NapiAudioRingBuffer::create_js_class(&env)?;
+    exports.set_named_property("AudioRingBuffer", napi_class)?;
+
     let napi_class = NapiAudioBuffer::create_js_class(&env)?;
     exports.set_named_property("AudioBuffer", napi_class)?;
 
@@ -95,6 +128,15 @@ fn init(mut exports: JsObject, env: Env) -> Result<()> {
         "exit_audio_worklet_global_scope",
         exit_audio_worklet_global_scope,
     )?;
+    exports.create_named_method(
+        "replace_audio_worklet_processor",
+        replace_audio_worklet_processor,
+    )?;
+    exports.create_named_method("share_audio_buffer", share_audio_buffer)?;
+    exports.create_named_method("take_shared_audio_buffer", take_shared_audio_buffer)?;
+    // non spec compliant, processors compiled to WebAssembly
+    let napi_class = NapiAudioWorkletWasmModule::create_js_class(&env)?;
+    exports.set_named_property("AudioWorkletWasmModule", napi_class)?;
 
     // ----------------------------------------------------------------
     // MediaStream API & Media Devices API
@@ -106,6 +148,8 @@ fn init(mut exports: JsObject, env: Env) -> Result<()> {
 
     media_devices.create_named_method("enumerateDevices", napi_enumerate_devices)?;
     media_devices.create_named_method("getUserMedia", napi_get_user_media)?;
+    // non standard, list audio backends that can be given to AudioContext
+    media_devices.create_named_method("enumerateBackends", napi_enumerate_backends)?;
     // expose media devices
     exports.set_named_property("mediaDevices", media_devices)?;
 
@@ -123,6 +167,9 @@ fn init(mut exports: JsObject, env: Env) -> Result<()> {
     let napi_class = NapiAudioRenderCapacity::create_js_class(&env)?;
     store.set_named_property("AudioRenderCapacity", napi_class)?;
 
+    let napi_class = NapiAudioPlaybackStats::create_js_class(&env)?;
+    store.set_named_property("AudioPlaybackStats", napi_class)?;
+
     let napi_class = NapiAudioBuffer::create_js_class(&env)?;
     store.set_named_property("AudioBuffer", napi_class)?;
 
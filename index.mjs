@@ -45,9 +45,13 @@ export const {
   AudioWorklet,
   AudioParamMap,
   AudioRenderCapacity,
+  AudioPlaybackStats,
+  AudioNodeProfiler,
+  AudioNodeTap,
 
   PeriodicWave,
   AudioBuffer,
+  AudioRingBuffer,
   // generated nodes
   ScriptProcessorNode,
   AudioWorkletNode,
@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -141,9 +142,11 @@ module.exports = (jsExport, nativeBinding) => {
 
       this.#playbackRate = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].playbackRate,
+        [kParamOwner]: this,
       });
       this.#detune = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].detune,
+        [kParamOwner]: this,
       });
     }
 
@@ -11,20 +11,81 @@ const {
   kNapiObj,
   kOnStateChange,
   kOnSinkChange,
+  kOnSinkData,
+  kOnDeviceStatus,
+  kOnSchedulingWarning,
   kWorkletRelease,
 } = require('./lib/symbols.js');
 const {
   propagateEvent,
 } = require('./lib/events.js');
+const {
+  ErrorEvent,
+} = require('./Events.js');
+const {
+  parseSinkOptions,
+  createStreamSinkWriter,
+  createCustomSinkWriter,
+  parseJackOptions,
+} = require('./lib/sinks.js');
 
 let contextId = 0;
 
+// non standard, scheduling of the render thread or of the AudioWorklet thread,
+// i.e. `{ priority, cpuAffinity }`
+function parseThreadSchedulingOptions(options, key) {
+  const context = `Failed to construct 'AudioContext': Failed to read the '${key}' property from AudioContextOptions`;
+  const scheduling = {
+    priority: null,
+    cpuAffinity: null,
+  };
+
+  if (options === undefined) {
+    return scheduling;
+  }
+
+  if (typeof options !== 'object' || options === null) {
+    throw new TypeError(`${context}: The provided value is not of type 'AudioThreadSchedulingOptions'`);
+  }
+
+  if (options.priority !== undefined) {
+    // SCHED_FIFO priorities range from 1 to 99 on Linux
+    scheduling.priority = conversions['octet'](options.priority, {
+      enforceRange: true,
+      context: `${context}: Failed to read the 'priority' property from AudioThreadSchedulingOptions: The provided value (${options.priority})`,
+    });
+
+    if (scheduling.priority < 1 || scheduling.priority > 99) {
+      throw new TypeError(`${context}: Failed to read the 'priority' property from AudioThreadSchedulingOptions: The provided value (${options.priority}) is outside the range [1, 99]`);
+    }
+  }
+
+  if (options.cpuAffinity !== undefined) {
+    if (!Array.isArray(options.cpuAffinity) || options.cpuAffinity.length === 0) {
+      throw new TypeError(`${context}: Failed to read the 'cpuAffinity' property from AudioThreadSchedulingOptions: The provided value should be a non empty array of CPU indices`);
+    }
+
+    scheduling.cpuAffinity = options.cpuAffinity.map(cpu => {
+      return conversions['unsigned long'](cpu, {
+        enforceRange: true,
+        context: `${context}: Failed to read the 'cpuAffinity' property from AudioThreadSchedulingOptions: The provided value (${cpu})`,
+      });
+    });
+  }
+
+  return scheduling;
+}
+
 module.exports = function(jsExport, nativeBinding) {
 
   class AudioContext extends jsExport.BaseAudioContext {
     #sinkId = '';
+    #backend = 'default';
     #renderCapacity = null;
+    #playbackStats = null;
     #onsinkchange = null;
+    #onerror = null;
+    #interrupted = false;
 
     constructor(options = {}) {
       if (typeof options !== 'object') {
@@ -56,8 +117,12 @@ module.exports = function(jsExport, nativeBinding) {
       if (options.sinkId !== undefined) {
         if (typeof options.sinkId === 'object') {
           // https://webaudio.github.io/web-audio-api/#enumdef-audiosinktype
-          if (!('type' in options.sinkId) || options.sinkId.type !== 'none') {
-            throw TypeError(`Failed to construct 'AudioContext': Failed to read the 'sinkId' property from AudioNodeOptions: Failed to read the 'type' property from 'AudioSinkOptions': The provided value (${options.sinkId.type}) is not a valid enum value of type AudioSinkType.`);
+          // Besides 'none', we support non-standard sink types that render
+          // on the 'none' sink and hand the rendered frames back to JS.
+          const sink = parseSinkOptions(options.sinkId, `Failed to construct 'AudioContext': Failed to read the 'sinkId' property from AudioNodeOptions`);
+
+          if (sink !== null) {
+            targetOptions.sink = sink;
           }
 
           targetOptions.sinkId = 'none';
@@ -70,6 +135,59 @@ module.exports = function(jsExport, nativeBinding) {
         targetOptions.sinkId = '';
       }
 
+      // non standard, select the audio backend at runtime, e.g. 'alsa' or 'jack',
+      // cf. `mediaDevices.enumerateBackends()`
+      if (options.backend !== undefined) {
+        targetOptions.backend = conversions['DOMString'](options.backend, {
+          context: `Failed to construct 'AudioContext': Failed to read the 'backend' property from AudioContextOptions: The provided value (${options.backend})`,
+        });
+      } else {
+        targetOptions.backend = 'default';
+      }
+
+      if (targetOptions.backend === 'jack') {
+        targetOptions.jack = parseJackOptions(options.jack, false, `Failed to construct 'AudioContext'`);
+      }
+
+      // non standard, what to do when the audio device is lost, i.e. 'interrupt'
+      // the context until the device comes back, or also try to 'fallback'
+      // to the default output device
+      if (options.deviceLossPolicy !== undefined) {
+        if (!['interrupt', 'fallback'].includes(options.deviceLossPolicy)) {
+          throw new TypeError(`Failed to construct 'AudioContext': Failed to read the 'deviceLossPolicy' property from AudioContextOptions: The provided value (${options.deviceLossPolicy}) is not a valid enum value of type DeviceLossPolicy.`);
+        }
+
+        targetOptions.deviceLossPolicy = options.deviceLossPolicy;
+      } else {
+        targetOptions.deviceLossPolicy = 'interrupt';
+      }
+
+      // non standard, realtime scheduling and CPU affinity of the audio threads,
+      // failures are reported as process warnings
+      targetOptions.renderThread = parseThreadSchedulingOptions(options.renderThread, 'renderThread');
+      targetOptions.workletThread = parseThreadSchedulingOptions(options.workletThread, 'workletThread');
+
+      // non standard, number of Workers running the AudioWorkletProcessors,
+      // processors are run concurrently when greater than 1
+      if (options.workletThreads !== undefined) {
+        targetOptions.workletThreads = conversions['unsigned long'](options.workletThreads, {
+          enforceRange: true,
+          context: `Failed to construct 'AudioContext': Failed to read the 'workletThreads' property from AudioContextOptions: The provided value (${options.workletThreads})`,
+        });
+
+        if (targetOptions.workletThreads === 0) {
+          throw new DOMException(`Failed to construct 'AudioContext': Invalid 'workletThreads' property from AudioContextOptions: The provided value (${options.workletThreads}) should be strictly positive`, 'NotSupportedError');
+        }
+      } else {
+        targetOptions.workletThreads = 1;
+      }
+
+      if (options.lockMemory !== undefined) {
+        targetOptions.lockMemory = conversions['boolean'](options.lockMemory);
+      } else {
+        targetOptions.lockMemory = false;
+      }
+
       let napiObj;
 
       try {
@@ -84,10 +202,16 @@ module.exports = function(jsExport, nativeBinding) {
         this.#sinkId = options.sinkId;
       }
 
+      this.#backend = targetOptions.backend;
+
       this.#renderCapacity = new jsExport.AudioRenderCapacity({
         [kNapiObj]: this[kNapiObj].renderCapacity,
       });
 
+      this.#playbackStats = new jsExport.AudioPlaybackStats({
+        [kNapiObj]: this[kNapiObj].playbackStats,
+      });
+
       // Add function to Napi object to bridge from Rust events to JS EventTarget
       this[kNapiObj][kOnStateChange] = (function(err, rawEvent) {
         const event = new Event(rawEvent.type);
@@ -99,6 +223,51 @@ module.exports = function(jsExport, nativeBinding) {
         propagateEvent(this, event);
       }).bind(this);
 
+      this[kNapiObj][kOnDeviceStatus] = (function(err, rawEvent) {
+        // a lost device of a non default backend (or a JACK client) has been
+        // replaced by the default output device, cf. `deviceLossPolicy`
+        if (rawEvent.type === 'sinkchange') {
+          this.#sinkId = rawEvent.message;
+          propagateEvent(this, new Event('sinkchange'));
+          return;
+        }
+
+        // errors of the sink that don't interrupt the output
+        if (rawEvent.type === 'error') {
+          const error = new Error(rawEvent.message);
+          propagateEvent(this, new ErrorEvent('error', { message: rawEvent.message, error }));
+          return;
+        }
+
+        if (rawEvent.type === 'interrupted') {
+          this.#interrupted = true;
+
+          const error = new Error(rawEvent.message);
+          const errorEvent = new ErrorEvent('error', { message: rawEvent.message, error });
+          propagateEvent(this, errorEvent);
+        } else {
+          this.#interrupted = false;
+        }
+
+        const event = new Event('statechange');
+        propagateEvent(this, event);
+      }).bind(this);
+
+      this[kNapiObj][kOnSchedulingWarning] = (function(err, message) {
+        process.emitWarning(message, 'AudioSchedulingWarning');
+      }).bind(this);
+
+      if (targetOptions.sink !== undefined) {
+        if (targetOptions.sink.type === 'stream') {
+          this[kNapiObj][kOnSinkData] = createStreamSinkWriter(options.sinkId.stream);
+        } else if (targetOptions.sink.type === 'custom') {
+          this[kNapiObj][kOnSinkData] = createCustomSinkWriter(options.sinkId, err => {
+            const message = `Failed to execute 'onData' on 'AudioSinkOptions': ${err && err.message}`;
+            propagateEvent(this, new ErrorEvent('error', { message, error: err }));
+          });
+        }
+      }
+
       // Workaround to bind the `sinkchange` and `statechange` events to EventTarget.
       // This must be called from JS facade ctor as the JS handler are added to the Napi
       // object after its instantiation, and that we don't have any initial `resume` call.
@@ -132,6 +301,16 @@ module.exports = function(jsExport, nativeBinding) {
       }
     }
 
+    get state() {
+      if (!(this instanceof AudioContext)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
+      }
+
+      const state = super.state;
+      // the audio device has been lost
+      return this.#interrupted && state !== 'closed' ? 'interrupted' : state;
+    }
+
     get baseLatency() {
       if (!(this instanceof AudioContext)) {
         throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
@@ -164,6 +343,23 @@ module.exports = function(jsExport, nativeBinding) {
       return this.#renderCapacity;
     }
 
+    get playbackStats() {
+      if (!(this instanceof AudioContext)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
+      }
+
+      return this.#playbackStats;
+    }
+
+    // name of the attribute in earlier drafts of the specification
+    get playoutStats() {
+      if (!(this instanceof AudioContext)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
+      }
+
+      return this.#playbackStats;
+    }
+
     get onsinkchange() {
       if (!(this instanceof AudioContext)) {
         throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
@@ -182,6 +378,24 @@ module.exports = function(jsExport, nativeBinding) {
       }
     }
 
+    get onerror() {
+      if (!(this instanceof AudioContext)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
+      }
+
+      return this.#onerror;
+    }
+
+    set onerror(value) {
+      if (!(this instanceof AudioContext)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
+      }
+
+      if (isFunction(value) || value === null) {
+        this.#onerror = value;
+      }
+    }
+
     getOutputTimestamp() {
       if (!(this instanceof AudioContext)) {
         throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
@@ -190,6 +404,28 @@ module.exports = function(jsExport, nativeBinding) {
       throw new Error(`AudioContext::getOutputTimestamp is not yet implemented`);
     }
 
+    // non standard, map the audio clock to `process.hrtime.bigint()`, e.g. to
+    // synchronize `currentTime` with MIDI, OSC or network clocks
+    getClockInfo() {
+      if (!(this instanceof AudioContext)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
+      }
+
+      const info = this[kNapiObj].getClockInfo();
+      const now = process.hrtime.bigint();
+
+      return {
+        // frame and time at the start of the last rendered quantum
+        currentFrame: info.currentFrame,
+        currentTime: info.currentTime,
+        // system time at which the last quantum was rendered, `null` until
+        // the first quantum is rendered
+        hrtime: info.elapsed === null ? null : now - BigInt(info.elapsed),
+        // drift of the audio clock versus the system clock, in ppm
+        drift: info.drift,
+      };
+    }
+
     async resume() {
       if (!(this instanceof AudioContext)) {
         throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
@@ -214,7 +450,13 @@ module.exports = function(jsExport, nativeBinding) {
       // Close audioWorklet first so that `run_audio_worklet_global_scope` exit first
       // The other way around works too because of `recv_timeout` but cleaner this way
       await this.audioWorklet[kWorkletRelease]();
-      await this[kNapiObj].close();
+
+      try {
+        await this[kNapiObj].close();
+      } catch (err) {
+        // e.g. the file sink failed to finalize its file
+        throwSanitizedError(err);
+      }
     }
 
     async setSinkId(sinkId) {
@@ -228,6 +470,14 @@ module.exports = function(jsExport, nativeBinding) {
 
       let targetSinkId = '';
 
+      if (typeof this.#sinkId === 'object' && this.#sinkId.type !== 'none') {
+        throw new DOMException(`Failed to execute 'setSinkId' on 'AudioContext': Cannot change the sink of a context created with a '${this.#sinkId.type}' sink`, 'InvalidStateError');
+      }
+
+      if (this.#backend !== 'default') {
+        throw new DOMException(`Failed to execute 'setSinkId' on 'AudioContext': Cannot change the sink of a context using the '${this.#backend}' audio backend`, 'NotSupportedError');
+      }
+
       if (typeof sinkId === 'object') {
         if (!('type' in sinkId) || sinkId.type !== 'none') {
           throw new TypeError(`Failed to execute 'setSinkId' on 'AudioContext': Failed to read the 'type' property from 'AudioSinkOptions': The provided value '${sinkId.type}' is not a valid enum value of type AudioSinkType.`);
@@ -249,6 +499,79 @@ module.exports = function(jsExport, nativeBinding) {
       }
     }
 
+    // non standard, play the output of the context on another audio device,
+    // e.g. to output the same audio on a USB interface and on HDMI
+    async addSink(sinkId, options = {}) {
+      if (!(this instanceof AudioContext)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
+      }
+
+      if (arguments.length < 1) {
+        throw new TypeError(`Failed to execute 'addSink' on 'AudioContext': 1 argument required, but only ${arguments.length} present`);
+      }
+
+      const targetSinkId = conversions['DOMString'](sinkId, {
+        context: `Failed to execute 'addSink' on 'AudioContext': The provided value (${sinkId})`,
+      });
+
+      if (typeof options !== 'object' || options === null) {
+        throw new TypeError(`Failed to execute 'addSink' on 'AudioContext': The provided value is not of type 'AudioMirrorSinkOptions'`);
+      }
+
+      // by default the sinkId refers to a device of the backend of the context
+      let backend = this.#backend;
+
+      if (options.backend !== undefined) {
+        backend = conversions['DOMString'](options.backend, {
+          context: `Failed to execute 'addSink' on 'AudioContext': Failed to read the 'backend' property from AudioMirrorSinkOptions: The provided value (${options.backend})`,
+        });
+      }
+
+      // audio buffered between the render thread and the device, which
+      // absorbs the jitter of the two devices and the drift compensation
+      let latency = 0.04;
+
+      if (options.latency !== undefined) {
+        latency = conversions['double'](options.latency, {
+          context: `Failed to execute 'addSink' on 'AudioContext': Failed to read the 'latency' property from AudioMirrorSinkOptions: The provided value (${options.latency})`,
+        });
+
+        if (latency <= 0) {
+          throw new RangeError(`Failed to execute 'addSink' on 'AudioContext': Failed to read the 'latency' property from AudioMirrorSinkOptions: The provided value (${options.latency}) should be strictly positive`);
+        }
+      }
+
+      if (this.state === 'closed') {
+        throw new DOMException(`Failed to execute 'addSink' on 'AudioContext': Cannot add a sink to a closed context`, 'InvalidStateError');
+      }
+
+      try {
+        this[kNapiObj].addSink(targetSinkId, backend, latency);
+      } catch (err) {
+        throwSanitizedError(err);
+      }
+    }
+
+    async removeSink(sinkId) {
+      if (!(this instanceof AudioContext)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioContext\'');
+      }
+
+      if (arguments.length < 1) {
+        throw new TypeError(`Failed to execute 'removeSink' on 'AudioContext': 1 argument required, but only ${arguments.length} present`);
+      }
+
+      const targetSinkId = conversions['DOMString'](sinkId, {
+        context: `Failed to execute 'removeSink' on 'AudioContext': The provided value (${sinkId})`,
+      });
+
+      try {
+        this[kNapiObj].removeSink(targetSinkId);
+      } catch (err) {
+        throwSanitizedError(err);
+      }
+    }
+
     // online context only AudioNodes
     createMediaStreamSource(mediaStream) {
       if (!(this instanceof AudioContext)) {
@@ -310,16 +633,23 @@ module.exports = function(jsExport, nativeBinding) {
       value: 'AudioContext',
     },
 
+    state: kEnumerableProperty,
     baseLatency: kEnumerableProperty,
     outputLatency: kEnumerableProperty,
     sinkId: kEnumerableProperty,
     renderCapacity: kEnumerableProperty,
+    playbackStats: kEnumerableProperty,
+    playoutStats: kEnumerableProperty,
     onsinkchange: kEnumerableProperty,
+    onerror: kEnumerableProperty,
     getOutputTimestamp: kEnumerableProperty,
+    getClockInfo: kEnumerableProperty,
     resume: kEnumerableProperty,
     suspend: kEnumerableProperty,
     close: kEnumerableProperty,
     setSinkId: kEnumerableProperty,
+    addSink: kEnumerableProperty,
+    removeSink: kEnumerableProperty,
     createMediaStreamSource: kEnumerableProperty,
     createMediaElementSource: kEnumerableProperty,
     createMediaStreamTrackSource: kEnumerableProperty,
@@ -0,0 +1,192 @@
+const conversions = require('webidl-conversions');
+
+const {
+  throwSanitizedError,
+} = require('./lib/errors.js');
+const {
+  kNapiObj,
+  kProfilerProbes,
+} = require('./lib/symbols.js');
+const {
+  kEnumerableProperty,
+} = require('./lib/utils.js');
+
+const AudioNode = require('./AudioNode.js');
+
+const RENDER_QUANTUM_SIZE = 128;
+
+/**
+ * Non spec compliant, opt-in profiler attributing render time to audio nodes
+ *
+ * Each observed node is connected to a silent probe, the render time measured
+ * between two consecutive probes of the same render quantum is attributed to
+ * the observed node. The time of nodes that are not observed is therefore
+ * attributed to the next observed node in render order, and the first observed
+ * node of each render quantum is not measured.
+ *
+ * The upstream graph renders the destination of the last connection of a node
+ * right after it, the probe is therefore connected again each time the
+ * observed node is connected to another destination, cf. `AudioNode.connect`.
+ */
+class AudioNodeProfiler {
+  #context = null;
+  #observed = new Map();
+
+  constructor(options) {
+    // Make constructor "private"
+    if (
+      (typeof options !== 'object')
+      || !(kNapiObj in options)
+      || options[kNapiObj]['Symbol.toStringTag'] !== 'AudioNodeProfiler'
+    ) {
+      throw new TypeError('Illegal constructor');
+    }
+
+    this[kNapiObj] = options[kNapiObj];
+    this.#context = options.context;
+  }
+
+  observe(node, label = undefined) {
+    if (!(this instanceof AudioNodeProfiler)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioNodeProfiler\'');
+    }
+
+    if (!(node instanceof AudioNode)) {
+      throw new TypeError(`Failed to execute 'observe' on 'AudioNodeProfiler': parameter 1 is not of type 'AudioNode'`);
+    }
+
+    if (node.context !== this.#context) {
+      throw new DOMException(`Failed to execute 'observe' on 'AudioNodeProfiler': cannot observe a node belonging to a different audio context`, 'InvalidAccessError');
+    }
+
+    if (node.numberOfOutputs === 0) {
+      throw new DOMException(`Failed to execute 'observe' on 'AudioNodeProfiler': cannot observe a node without output`, 'NotSupportedError');
+    }
+
+    label = label !== undefined
+      ? conversions['DOMString'](label, {
+        context: `Failed to execute 'observe' on 'AudioNodeProfiler': The provided value (${label})`,
+      })
+      : node[Symbol.toStringTag];
+
+    if (this.#observed.has(node)) {
+      this.#observed.get(node).label = label;
+      return;
+    }
+
+    const probe = this[kNapiObj].createProbe();
+
+    try {
+      node[kNapiObj].connect(probe, 0, 0);
+    } catch (err) {
+      this[kNapiObj].releaseProbe(probe.probeId);
+      throwSanitizedError(err);
+    }
+
+    if (node[kProfilerProbes] === undefined) {
+      node[kProfilerProbes] = new Set();
+    }
+
+    node[kProfilerProbes].add(probe);
+    this.#observed.set(node, { label, probe });
+  }
+
+  unobserve(node) {
+    if (!(this instanceof AudioNodeProfiler)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioNodeProfiler\'');
+    }
+
+    const entry = this.#observed.get(node);
+
+    if (entry === undefined) {
+      return;
+    }
+
+    node[kProfilerProbes].delete(entry.probe);
+
+    try {
+      node[kNapiObj].disconnect(entry.probe);
+    } catch (err) {
+      // the probe has been disconnected by `disconnect()`
+    }
+
+    this[kNapiObj].releaseProbe(entry.probe.probeId);
+    this.#observed.delete(node);
+  }
+
+  disconnect() {
+    if (!(this instanceof AudioNodeProfiler)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioNodeProfiler\'');
+    }
+
+    for (let node of Array.from(this.#observed.keys())) {
+      this.unobserve(node);
+    }
+  }
+
+  /**
+   * Render time of the observed nodes, in seconds, most expensive first
+   */
+  getReport() {
+    if (!(this instanceof AudioNodeProfiler)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioNodeProfiler\'');
+    }
+
+    const quantumDuration = RENDER_QUANTUM_SIZE / this.#context.sampleRate;
+    const report = [];
+
+    for (let [node, { label, probe }] of this.#observed) {
+      const { quanta, totalTime, maxTime } = this[kNapiObj].getStats(probe.probeId);
+      const averageTime = quanta > 0 ? totalTime / quanta : 0;
+
+      report.push({
+        node,
+        label,
+        quanta,
+        totalTime,
+        averageTime,
+        maxTime,
+        // share of the duration of a render quantum
+        averageLoad: averageTime / quantumDuration,
+      });
+    }
+
+    return report.sort((a, b) => b.totalTime - a.totalTime);
+  }
+
+  reset() {
+    if (!(this instanceof AudioNodeProfiler)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioNodeProfiler\'');
+    }
+
+    this[kNapiObj].reset();
+  }
+}
+
+Object.defineProperties(AudioNodeProfiler, {
+  length: {
+    __proto__: null,
+    writable: false,
+    enumerable: false,
+    configurable: true,
+    value: 0,
+  },
+});
+
+Object.defineProperties(AudioNodeProfiler.prototype, {
+  [Symbol.toStringTag]: {
+    __proto__: null,
+    writable: false,
+    enumerable: false,
+    configurable: true,
+    value: 'AudioNodeProfiler',
+  },
+
+  observe: kEnumerableProperty,
+  unobserve: kEnumerableProperty,
+  disconnect: kEnumerableProperty,
+  getReport: kEnumerableProperty,
+  reset: kEnumerableProperty,
+});
+
+module.exports = AudioNodeProfiler;
@@ -0,0 +1,132 @@
+const {
+  throwSanitizedError,
+} = require('./lib/errors.js');
+const {
+  kNapiObj,
+  kOnTapData,
+} = require('./lib/symbols.js');
+const {
+  kEnumerableProperty,
+} = require('./lib/utils.js');
+
+/**
+ * Non spec compliant, handle of `AudioNode.tap`
+ *
+ * The tapped node is connected to a silent node that copies its output into a
+ * lock-free ring buffer, which is drained outside the render thread and handed
+ * over to the callback by chunks of `bufferSize` frames.
+ */
+class AudioNodeTap {
+  #node = null;
+  #output = 0;
+  #closed = null;
+  #resolveClosed = null;
+
+  constructor(options) {
+    // Make constructor "private"
+    if (
+      (typeof options !== 'object')
+      || !(kNapiObj in options)
+      || options[kNapiObj]['Symbol.toStringTag'] !== 'AudioNodeTap'
+    ) {
+      throw new TypeError('Illegal constructor');
+    }
+
+    this[kNapiObj] = options[kNapiObj];
+    this.#node = options.node;
+    this.#output = options.output;
+
+    const callback = options.callback;
+
+    this[kNapiObj][kOnTapData] = (_err, channels) => {
+      // all the blocks have been delivered after `close`
+      if (channels === null) {
+        this.#resolveClosed();
+        return;
+      }
+
+      try {
+        callback(channels);
+      } catch (err) {
+        console.error(err);
+      }
+    };
+
+    try {
+      this.#node[kNapiObj].connect(this[kNapiObj].node, this.#output, 0);
+    } catch (err) {
+      throwSanitizedError(err);
+    }
+
+    this[kNapiObj].listen();
+  }
+
+  get node() {
+    if (!(this instanceof AudioNodeTap)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioNodeTap\'');
+    }
+
+    return this.#node;
+  }
+
+  get droppedFrames() {
+    if (!(this instanceof AudioNodeTap)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioNodeTap\'');
+    }
+
+    return this[kNapiObj].droppedFrames;
+  }
+
+  /**
+   * Disconnect the tap, the returned promise resolves once the last frames
+   * have been handed over to the callback
+   */
+  close() {
+    if (!(this instanceof AudioNodeTap)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioNodeTap\'');
+    }
+
+    if (this.#closed !== null) {
+      return this.#closed;
+    }
+
+    this.#closed = new Promise(resolve => this.#resolveClosed = resolve);
+
+    try {
+      this.#node[kNapiObj].disconnect(this[kNapiObj].node, this.#output);
+    } catch (err) {
+      // the node may have been disconnected from everything meanwhile,
+      // e.g. by `node.disconnect()`
+    }
+
+    this[kNapiObj].close();
+
+    return this.#closed;
+  }
+}
+
+Object.defineProperties(AudioNodeTap, {
+  length: {
+    __proto__: null,
+    writable: false,
+    enumerable: false,
+    configurable: true,
+    value: 0,
+  },
+});
+
+Object.defineProperties(AudioNodeTap.prototype, {
+  [Symbol.toStringTag]: {
+    __proto__: null,
+    writable: false,
+    enumerable: false,
+    configurable: true,
+    value: 'AudioNodeTap',
+  },
+
+  node: kEnumerableProperty,
+  droppedFrames: kEnumerableProperty,
+  close: kEnumerableProperty,
+});
+
+module.exports = AudioNodeTap;
@@ -32,6 +32,7 @@ const {
 } = require('./lib/utils.js');
 const {
   kNapiObj,
+  kParamOwner,
 } = require('./lib/symbols.js');
 
 class AudioParam {
@@ -49,6 +50,12 @@ class AudioParam {
       value: options[kNapiObj],
       ...kHiddenProperty,
     });
+
+    // the node the param belongs to, cf. AudioNode.connect
+    Object.defineProperty(this, kParamOwner, {
+      value: options[kParamOwner],
+      ...kHiddenProperty,
+    });
   }
 
   get value() {
@@ -0,0 +1,144 @@
+const {
+  kNapiObj,
+} = require('./lib/symbols.js');
+const {
+  kEnumerableProperty,
+} = require('./lib/utils.js');
+
+class AudioPlaybackStats {
+  constructor(options) {
+    // Make constructor "private"
+    if (
+      (typeof options !== 'object')
+      || !(kNapiObj in options)
+      || options[kNapiObj]['Symbol.toStringTag'] !== 'AudioPlaybackStats'
+    ) {
+      throw new TypeError('Illegal constructor');
+    }
+
+    this[kNapiObj] = options[kNapiObj];
+  }
+
+  get underrunDuration() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    return this[kNapiObj].underrunDuration;
+  }
+
+  get underrunEvents() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    return this[kNapiObj].underrunEvents;
+  }
+
+  get totalDuration() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    return this[kNapiObj].totalDuration;
+  }
+
+  get averageLatency() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    return this[kNapiObj].averageLatency;
+  }
+
+  get minimumLatency() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    return this[kNapiObj].minimumLatency;
+  }
+
+  get maximumLatency() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    return this[kNapiObj].maximumLatency;
+  }
+
+  // non spec compliant, cumulative counters in sample-frames
+  get totalFrames() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    return this[kNapiObj].totalFrames;
+  }
+
+  get underrunFrames() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    return this[kNapiObj].underrunFrames;
+  }
+
+  resetLatency() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    this[kNapiObj].resetLatency();
+  }
+
+  toJSON() {
+    if (!(this instanceof AudioPlaybackStats)) {
+      throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioPlaybackStats\'');
+    }
+
+    return {
+      underrunDuration: this.underrunDuration,
+      underrunEvents: this.underrunEvents,
+      totalDuration: this.totalDuration,
+      averageLatency: this.averageLatency,
+      minimumLatency: this.minimumLatency,
+      maximumLatency: this.maximumLatency,
+      totalFrames: this.totalFrames,
+      underrunFrames: this.underrunFrames,
+    };
+  }
+}
+
+Object.defineProperties(AudioPlaybackStats, {
+  length: {
+    __proto__: null,
+    writable: false,
+    enumerable: false,
+    configurable: true,
+    value: 0,
+  },
+});
+
+Object.defineProperties(AudioPlaybackStats.prototype, {
+  [Symbol.toStringTag]: {
+    __proto__: null,
+    writable: false,
+    enumerable: false,
+    configurable: true,
+    value: 'AudioPlaybackStats',
+  },
+
+  underrunDuration: kEnumerableProperty,
+  underrunEvents: kEnumerableProperty,
+  totalDuration: kEnumerableProperty,
+  averageLatency: kEnumerableProperty,
+  minimumLatency: kEnumerableProperty,
+  maximumLatency: kEnumerableProperty,
+  totalFrames: kEnumerableProperty,
+  underrunFrames: kEnumerableProperty,
+  resetLatency: kEnumerableProperty,
+  toJSON: kEnumerableProperty,
+});
+
+module.exports = AudioPlaybackStats;
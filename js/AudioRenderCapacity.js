@@ -5,6 +5,7 @@ const {
   kOnUpdate,
 } = require('./lib/symbols.js');
 const {
+  isFunction,
   kEnumerableProperty,
 } = require('./lib/utils.js');
 const {
@@ -84,7 +85,7 @@ class AudioRenderCapacity extends EventTarget {
       throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRenderCapacity'`);
     }
 
-    return this[kNapiObj].start();
+    return this[kNapiObj].stop();
   }
 }
 
@@ -108,7 +109,7 @@ Object.defineProperties(AudioRenderCapacity.prototype, {
   },
 
   onupdate: kEnumerableProperty,
-  stop: kEnumerableProperty,
+  start: kEnumerableProperty,
   stop: kEnumerableProperty,
 });
 
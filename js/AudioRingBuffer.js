@@ -0,0 +1,249 @@
+const conversions = require('webidl-conversions');
+
+const {
+  throwSanitizedError,
+} = require('./lib/errors.js');
+const {
+  kEnumerableProperty,
+  kHiddenProperty,
+} = require('./lib/utils.js');
+const {
+  kNapiObj,
+} = require('./lib/symbols.js');
+
+/**
+ * Non spec compliant, wait-free single producer single consumer queue of
+ * audio frames mapped on a SharedArrayBuffer
+ *
+ * The ring buffer is created on one side with `{ numberOfChannels, length }`,
+ * its `buffer` is then given to the other side, e.g. through the
+ * `processorOptions` of an `AudioWorkletNode`, which creates its own instance
+ * from it. One side must only call `push` and the other only `pull`.
+ */
+module.exports = (_jsExport, nativeBinding) => {
+  class AudioRingBuffer {
+    constructor(options) {
+      if (arguments.length < 1) {
+        throw new TypeError(`Failed to construct 'AudioRingBuffer': 1 argument required, but only ${arguments.length} present`);
+      }
+
+      let napiObj;
+
+      if (options instanceof SharedArrayBuffer) {
+        // attach to a ring buffer created by another instance
+        try {
+          napiObj = new nativeBinding.AudioRingBuffer(new Uint8Array(options));
+        } catch (err) {
+          throwSanitizedError(err);
+        }
+      } else {
+        if (typeof options !== 'object' || options === null) {
+          throw new TypeError(`Failed to construct 'AudioRingBuffer': argument 1 is not of type 'AudioRingBufferOptions' or 'SharedArrayBuffer'`);
+        }
+
+        // dictionary AudioRingBufferOptions {
+        //     unsigned long numberOfChannels = 1;
+        //     required unsigned long length;
+        // };
+        const parsedOptions = {};
+
+        if (options.numberOfChannels !== undefined) {
+          parsedOptions.numberOfChannels = conversions['unsigned long'](options.numberOfChannels, {
+            enforceRange: true,
+            context: `Failed to construct 'AudioRingBuffer': Failed to read the 'numberOfChannels' property from AudioRingBufferOptions: The provided value '${options.numberOfChannels}'`,
+          });
+        } else {
+          parsedOptions.numberOfChannels = 1;
+        }
+
+        if (options.length === undefined) {
+          throw new TypeError(`Failed to construct 'AudioRingBuffer': Failed to read the 'length' property from AudioRingBufferOptions: required member is undefined`);
+        }
+
+        parsedOptions.length = conversions['unsigned long'](options.length, {
+          enforceRange: true,
+          context: `Failed to construct 'AudioRingBuffer': Failed to read the 'length' property from AudioRingBufferOptions: The provided value '${options.length}'`,
+        });
+
+        if (parsedOptions.numberOfChannels === 0) {
+          throw new DOMException(`Failed to construct 'AudioRingBuffer': The number of channels provided (0) is outside the range [1, 4294967295]`, 'NotSupportedError');
+        }
+
+        if (parsedOptions.length === 0) {
+          throw new DOMException(`Failed to construct 'AudioRingBuffer': The length provided (0) is outside the range [1, 4294967295]`, 'NotSupportedError');
+        }
+
+        const { numberOfChannels, length } = parsedOptions;
+        const byteLength = nativeBinding.AudioRingBuffer.HEADER_BYTE_LENGTH
+          + numberOfChannels * length * Float32Array.BYTES_PER_ELEMENT;
+        const buffer = new SharedArrayBuffer(byteLength);
+
+        try {
+          napiObj = new nativeBinding.AudioRingBuffer(new Uint8Array(buffer), numberOfChannels, length);
+        } catch (err) {
+          throwSanitizedError(err);
+        }
+      }
+
+      Object.defineProperty(this, kNapiObj, {
+        value: napiObj,
+        ...kHiddenProperty,
+      });
+    }
+
+    /**
+     * SharedArrayBuffer holding the ring buffer, to be given to the other side
+     */
+    get buffer() {
+      if (!(this instanceof AudioRingBuffer)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRingBuffer'`);
+      }
+
+      return this[kNapiObj].buffer;
+    }
+
+    get numberOfChannels() {
+      if (!(this instanceof AudioRingBuffer)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRingBuffer'`);
+      }
+
+      return this[kNapiObj].numberOfChannels;
+    }
+
+    get length() {
+      if (!(this instanceof AudioRingBuffer)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRingBuffer'`);
+      }
+
+      return this[kNapiObj].length;
+    }
+
+    /**
+     * Number of frames that can be pulled
+     */
+    get availableRead() {
+      if (!(this instanceof AudioRingBuffer)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRingBuffer'`);
+      }
+
+      return this[kNapiObj].availableRead;
+    }
+
+    /**
+     * Number of frames that can be pushed
+     */
+    get availableWrite() {
+      if (!(this instanceof AudioRingBuffer)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRingBuffer'`);
+      }
+
+      return this[kNapiObj].availableWrite;
+    }
+
+    /**
+     * Number of frames requested by `pull` while the ring buffer was empty
+     */
+    get underrunFrames() {
+      if (!(this instanceof AudioRingBuffer)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRingBuffer'`);
+      }
+
+      return this[kNapiObj].underrunFrames;
+    }
+
+    /**
+     * Number of frames dropped by `push` while the ring buffer was full
+     */
+    get overrunFrames() {
+      if (!(this instanceof AudioRingBuffer)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRingBuffer'`);
+      }
+
+      return this[kNapiObj].overrunFrames;
+    }
+
+    /**
+     * Copy the channels, which must have the same length, at the end of the
+     * ring buffer, missing channels are filled with zeros.
+     *
+     * @return {number} Number of frames written, the frames that do not fit
+     *  are dropped and counted in `overrunFrames`
+     */
+    push(channels) {
+      if (!(this instanceof AudioRingBuffer)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRingBuffer'`);
+      }
+
+      this.#checkChannels('push', arguments.length, channels);
+
+      return this[kNapiObj].push(channels);
+    }
+
+    /**
+     * Copy the frames at the start of the ring buffer into the channels, which
+     * must have the same length.
+     *
+     * @return {number} Number of frames read, the missing frames are filled
+     *  with zeros and counted in `underrunFrames`
+     */
+    pull(channels) {
+      if (!(this instanceof AudioRingBuffer)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'AudioRingBuffer'`);
+      }
+
+      this.#checkChannels('pull', arguments.length, channels);
+
+      return this[kNapiObj].pull(channels);
+    }
+
+    #checkChannels(method, numberOfArguments, channels) {
+      if (numberOfArguments < 1) {
+        throw new TypeError(`Failed to execute '${method}' on 'AudioRingBuffer': 1 argument required, but only ${numberOfArguments} present`);
+      }
+
+      if (!Array.isArray(channels) || !channels.every(channel => channel instanceof Float32Array)) {
+        throw new TypeError(`Failed to execute '${method}' on 'AudioRingBuffer': parameter 1 is not of type 'sequence<Float32Array>'`);
+      }
+
+      if (channels.length > this[kNapiObj].numberOfChannels) {
+        throw new DOMException(`Failed to execute '${method}' on 'AudioRingBuffer': The number of channels provided (${channels.length}) is greater than the number of channels of the ring buffer (${this[kNapiObj].numberOfChannels})`, 'IndexSizeError');
+      }
+
+      if (channels.some(channel => channel.length !== channels[0].length)) {
+        throw new DOMException(`Failed to execute '${method}' on 'AudioRingBuffer': The provided channels must have the same length`, 'IndexSizeError');
+      }
+    }
+  }
+
+  Object.defineProperties(AudioRingBuffer, {
+    length: {
+      __proto__: null,
+      writable: false,
+      enumerable: false,
+      configurable: true,
+      value: 1,
+    },
+  });
+
+  Object.defineProperties(AudioRingBuffer.prototype, {
+    [Symbol.toStringTag]: {
+      __proto__: null,
+      writable: false,
+      enumerable: false,
+      configurable: true,
+      value: 'AudioRingBuffer',
+    },
+
+    buffer: kEnumerableProperty,
+    numberOfChannels: kEnumerableProperty,
+    length: kEnumerableProperty,
+    availableRead: kEnumerableProperty,
+    availableWrite: kEnumerableProperty,
+    underrunFrames: kEnumerableProperty,
+    overrunFrames: kEnumerableProperty,
+    push: kEnumerableProperty,
+    pull: kEnumerableProperty,
+  });
+
+  return AudioRingBuffer;
+};
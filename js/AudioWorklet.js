@@ -4,112 +4,122 @@ const {
 const fs = require('node:fs').promises;
 const { existsSync } = require('node:fs');
 const path = require('node:path');
+const {
+  pathToFileURL,
+  fileURLToPath,
+} = require('node:url');
 const {
   Worker,
   MessageChannel,
 } = require('node:worker_threads');
 
+const conversions = require('webidl-conversions');
+
+// processors compiled to WebAssembly are handled by the main thread
+const {
+  AudioWorkletWasmModule,
+} = require('../load-native.cjs');
+
 const {
   kProcessorRegistered,
   kGetParameterDescriptors,
+  kGetWasmProcessor,
   kCreateProcessor,
   kPrivateConstructor,
   kWorkletRelease,
+  kWorkletThreadCount,
+  kNextWorkletThread,
+  kWarnSequentialProcess,
   kCheckProcessorsCreated,
 } = require('./lib/symbols.js');
 const {
   kEnumerableProperty,
 } = require('./lib/utils.js');
+const {
+  parseParameterDescriptors,
+} = require('./lib/worklet.js');
 
 const caller = require('caller');
 // cf. https://www.npmjs.com/package/node-fetch#commonjs
 const fetch = (...args) => import('node-fetch').then(({default: fetch}) => fetch(...args));
 
 /**
- * Retrieve code with different module resolution strategies
- * - file - absolute or relative to cwd path
+ * Resolve the URL of a module with different module resolution strategies
+ * - file - absolute or relative to cwd path, or file URL
  * - URL
  * - Blob
  * - fallback: relative to caller site
  *   + in fs
  *   + caller site is url - required for wpt, probably no other use case
+ *
+ * Returns a file, http(s) or blob URL, `method` is the name of the calling
+ * method to be used in error messages. Must be called synchronously from the
+ * public method for the caller site to be retrieved.
  */
-const resolveModule = async (moduleUrl) => {
-  let code;
-
+const resolveModuleUrl = (moduleUrl, method) => {
   if (existsSync(moduleUrl)) {
-    const pathname = moduleUrl;
-
-    try {
-      const buffer = await fs.readFile(pathname);
-      code = buffer.toString();
-    } catch (err) {
-      throw new Error(`Failed to execute 'addModule' on 'AudioWorklet': ${err.message}`);
-    }
-  } else if (moduleUrl.startsWith('http')) {
-    try {
-        const res = await fetch(moduleUrl);
-        code = await res.text();
-      } catch (err) {
-        throw new Error(`Failed to execute 'addModule' on 'AudioWorklet': ${err.message}`);
-      }
-  } else if (moduleUrl.startsWith('blob:')) {
-    try {
-      const blob = resolveObjectURL(moduleUrl);
-      code = await blob.text();
-    } catch (err) {
-      throw new Error(`Failed to execute 'addModule' on 'AudioWorklet': ${err.message}`);
-    }
+    return pathToFileURL(path.resolve(moduleUrl)).href;
+  } else if (
+    moduleUrl.startsWith('http')
+    || moduleUrl.startsWith('blob:')
+    || moduleUrl.startsWith('file:')
+  ) {
+    return moduleUrl;
   } else {
     // get caller site from error stack trace
     const callerSite = caller(2);
 
     if (callerSite.startsWith('http')) { // this branch exists for wpt where caller site is an url
-      let url;
       // handle origin relative and caller path relative URLs
-      if (moduleUrl.startsWith('/')) {
-        const origin = new URL(baseUrl).origin;
-        url = origin + moduleUrl;
-      } else {
-        // we know separators are '/'
-        const baseUrl = callerSite.substr(0, callerSite.lastIndexOf('/'));
-        url = baseUrl + '/' + moduleUrl;
-      }
-
-      try {
-        const res = await fetch(url);
-        code = await res.text();
-      } catch (err) {
-        throw new Error(`Failed to execute 'addModule' on 'AudioWorklet': ${err.message}`);
-      }
+      return new URL(moduleUrl, callerSite).href;
     } else {
       const dirname = callerSite.substr(0, callerSite.lastIndexOf(path.sep));
       const absDirname = dirname.replace('file://', '');
       const pathname = path.join(absDirname, moduleUrl);
 
       if (existsSync(pathname)) {
-        try {
-          const buffer = await fs.readFile(pathname);
-          code = buffer.toString();
-        } catch (err) {
-          throw new Error(`Failed to execute 'addModule' on 'AudioWorklet': ${err.message}`);
-        }
+        return pathToFileURL(pathname).href;
       } else {
-        throw new Error(`Failed to execute 'addModule' on 'AudioWorklet': Cannot resolve module ${moduleUrl}`);
+        throw new Error(`Failed to execute '${method}' on 'AudioWorklet': Cannot resolve module ${moduleUrl}`);
       }
     }
   }
+};
 
-  return code;
-}
+/**
+ * Retrieve the content of a module resolved by `resolveModuleUrl`
+ *
+ * Returns a Buffer, `method` is the name of the calling method to be used in
+ * error messages
+ */
+const fetchModule = async (url, method) => {
+  try {
+    if (url.startsWith('file:')) {
+      return await fs.readFile(fileURLToPath(url));
+    } else if (url.startsWith('blob:')) {
+      const blob = resolveObjectURL(url);
+      return Buffer.from(await blob.arrayBuffer());
+    } else {
+      const res = await fetch(url);
+      return Buffer.from(await res.arrayBuffer());
+    }
+  } catch (err) {
+    throw new Error(`Failed to execute '${method}' on 'AudioWorklet': ${err.message}`);
+  }
+};
 
 class AudioWorklet {
-  #workletId = null;
+  #workletIds = null;
   #sampleRate = null;
-  #port = null;
+  // one Worker per AudioWorklet thread, cf. `workletThreads` context option
+  #workers = [];
+  #nextWorkletThread = 0;
+  #sequentialProcessWarned = false;
+  #reloadCount = 0;
   #idPromiseMap = new Map();
   #promiseId = 0;
   #workletParamDescriptorsMap = new Map();
+  #wasmProcessorsMap = new Map();
   #pendingCreateProcessors = new Set();
 
   constructor(options) {
@@ -120,12 +130,12 @@ class AudioWorklet {
       throw new TypeError('Illegal constructor');
     }
 
-    this.#workletId = options.workletId;
+    this.#workletIds = options.workletIds;
     this.#sampleRate = options.sampleRate;
   }
 
-  #bindEvents() {
-    this.#port.on('message', event => {
+  #bindEvents(worker) {
+    worker.on('message', event => {
       switch (event.cmd) {
         case 'node-web-audio-api:worklet:module-added': {
           const { promiseId } = event;
@@ -145,6 +155,8 @@ class AudioWorklet {
         case 'node-web-audio-api:worlet:processor-registered': {
           const { name, parameterDescriptors } = event;
           this.#workletParamDescriptorsMap.set(name, parameterDescriptors);
+          // the last registered processor wins
+          this.#wasmProcessorsMap.delete(name);
           break;
         }
         case 'node-web-audio-api:worklet:processor-created': {
@@ -157,39 +169,134 @@ class AudioWorklet {
   }
 
   get port() {
-    return this.#port;
+    return this.#workers.length > 0 ? this.#workers[0] : null;
   }
 
-  async addModule(moduleUrl) {
-    const code = await resolveModule(moduleUrl);
+  async addModule(moduleUrl, options = {}) {
+    let url = resolveModuleUrl(moduleUrl, 'addModule');
 
-    // launch Worker if not exists
-    if (!this.#port) {
-      await new Promise(resolve => {
-        const workletPathname = path.join(__dirname, 'AudioWorkletGlobalScope.js');
-        this.#port = new Worker(workletPathname, {
+    if (options && typeof options !== 'object') {
+      throw new TypeError(`Failed to execute 'addModule' on 'AudioWorklet': argument 2 is not of type 'WorkletOptions'`);
+    }
+
+    // non spec compliant, evaluate the module again and swap the existing
+    // processors of the names it registers, cf. README
+    const replace = options ? conversions['boolean'](options.replace) : false;
+
+    // Blob URLs cannot be resolved from the Workers
+    if (url.startsWith('blob:')) {
+      const code = await fetchModule(url, 'addModule');
+      url = `data:text/javascript;base64,${code.toString('base64')}`;
+    }
+
+    // a new fragment bypasses the module map of the Workers, the loader
+    // propagates it to the relative imports of the module
+    if (replace) {
+      url = `${url.split('#')[0]}#node-web-audio-api-reload-${this.#reloadCount++}`;
+    }
+
+    // launch Workers if not exists
+    if (this.#workers.length === 0) {
+      const workletPathname = path.join(__dirname, 'AudioWorkletGlobalScope.js');
+
+      this.#workers = this.#workletIds.map(workletId => {
+        const worker = new Worker(workletPathname, {
           workerData: {
-            workletId: this.#workletId,
+            workletId,
             sampleRate: this.#sampleRate,
           },
         });
-        this.#port.on('online', resolve);
 
-        this.#bindEvents();
+        this.#bindEvents(worker);
+
+        return worker;
       });
+
+      await Promise.all(this.#workers.map(worker => {
+        return new Promise(resolve => worker.on('online', resolve));
+      }));
+    }
+
+    // The module is imported in every Workers, so that the processors can
+    // be created in any of them. These promises are resolved when the Workers
+    // return the name and parameterDescriptors from the added module
+    await Promise.all(this.#workers.map(worker => {
+      const promiseId = this.#promiseId++;
+
+      return new Promise((resolve, reject) => {
+        this.#idPromiseMap.set(promiseId, { resolve, reject });
+
+        worker.postMessage({
+          cmd: 'node-web-audio-api:worklet:add-module',
+          url,
+          replace,
+          promiseId,
+        });
+      });
+    }));
+  }
+
+  /**
+   * Non spec compliant, register the processors implemented by a WebAssembly
+   * module, cf. README for the interface the module must implement
+   *
+   * These processors are not run in the AudioWorkletGlobalScope but directly
+   * on the render thread.
+   */
+  async addWasmModule(moduleUrl) {
+    const url = resolveModuleUrl(moduleUrl, 'addWasmModule');
+    const buffer = await fetchModule(url, 'addWasmModule');
+    const context = `Failed to execute 'addWasmModule' on 'AudioWorklet'`;
+
+    let module;
+    let descriptors;
+
+    try {
+      module = new AudioWorkletWasmModule(buffer);
+      descriptors = JSON.parse(module.descriptors);
+    } catch (err) {
+      throw new TypeError(`${context}: ${err.message}`);
     }
 
-    const promiseId = this.#promiseId++;
-    // This promise is resolved when the Worker returns the name and
-    // parameterDescriptors from the added module
-    await new Promise((resolve, reject) => {
-      this.#idPromiseMap.set(promiseId, { resolve, reject });
+    if (!Array.isArray(descriptors)) {
+      throw new TypeError(`${context}: Invalid module: 'awp_descriptors' does not describe an array of processors`);
+    }
 
-      this.#port.postMessage({
-        cmd: 'node-web-audio-api:worklet:add-module',
-        code,
-        promiseId,
+    // parse everything before registering anything
+    const processors = descriptors.map((descriptor, index) => {
+      if (typeof descriptor !== 'object' || descriptor === null) {
+        throw new TypeError(`${context}: Invalid module: processor at index ${index} is not an object`);
+      }
+
+      const name = conversions['DOMString'](descriptor.name, {
+        context: `${context}: name (${descriptor.name})`,
       });
+
+      if (name === '') {
+        throw new DOMException(`${context}: name is empty`, 'NotSupportedError');
+      }
+
+      const parameterDescriptors = parseParameterDescriptors(
+        descriptor.parameterDescriptors === undefined ? [] : descriptor.parameterDescriptors,
+        name,
+        context,
+      );
+
+      return { name, index, parameterDescriptors };
+    });
+
+    processors.forEach(({ name }, index) => {
+      if (
+        this.#workletParamDescriptorsMap.has(name)
+        || processors.findIndex(processor => processor.name === name) !== index
+      ) {
+        throw new DOMException(`${context}: A processor with name '${name}' has already been registered`, 'NotSupportedError');
+      }
+    });
+
+    processors.forEach(({ name, index, parameterDescriptors }) => {
+      this.#workletParamDescriptorsMap.set(name, parameterDescriptors);
+      this.#wasmProcessorsMap.set(name, { module, index });
     });
   }
 
@@ -214,12 +321,44 @@ class AudioWorklet {
     return this.#workletParamDescriptorsMap.get(name);
   }
 
+  // Returns the compiled module and the index of the processor in the module
+  // if `name` has been registered with `addWasmModule`
+  [kGetWasmProcessor](name) {
+    return this.#wasmProcessorsMap.get(name);
+  }
+
+  // Number of AudioWorklet threads of the context
+  [kWorkletThreadCount]() {
+    return this.#workletIds.length;
+  }
+
+  // Distribute the processors which are not explicitly pinned to a thread
+  // over the AudioWorklet threads, in order of creation
+  [kNextWorkletThread]() {
+    const index = this.#nextWorkletThread;
+    this.#nextWorkletThread = (index + 1) % this.#workletIds.length;
+    return index;
+  }
+
+  // The render thread waits for the `process` calls of the nodes that are not
+  // pipelined one after the other, whatever the number of threads, warn once
+  // per context that their processors do not run concurrently
+  [kWarnSequentialProcess]() {
+    if (this.#workletIds.length > 1 && !this.#sequentialProcessWarned) {
+      this.#sequentialProcessWarned = true;
+      process.emitWarning(
+        `The processors of AudioWorkletNodes that are not 'pipelined' do not run concurrently, even with several 'workletThreads'`,
+        'AudioWorkletWarning',
+      );
+    }
+  }
+
   [kCreateProcessor](name, options, id) {
     this.#pendingCreateProcessors.add(id);
 
     const { port1, port2 } = new MessageChannel();
     // @todo - check if some processorOptions must be transfered as well
-    this.#port.postMessage({
+    this.#workers[options.workletThreadIndex].postMessage({
       cmd: 'node-web-audio-api:worklet:create-processor',
       name,
       id,
@@ -231,14 +370,14 @@ class AudioWorklet {
   }
 
   async [kWorkletRelease]() {
-    if (this.#port) {
-      await new Promise(resolve => {
-        this.#port.on('exit', resolve);
-        this.#port.postMessage({
+    await Promise.all(this.#workers.map(worker => {
+      return new Promise(resolve => {
+        worker.on('exit', resolve);
+        worker.postMessage({
           cmd: 'node-web-audio-api:worklet:exit',
         });
       });
-    }
+    }));
   }
 }
 
@@ -261,6 +400,7 @@ Object.defineProperties(AudioWorklet.prototype, {
     value: 'AudioWorklet',
   },
   addModule: kEnumerableProperty,
+  addWasmModule: kEnumerableProperty,
   port: kEnumerableProperty,
 });
 
@@ -3,14 +3,25 @@ const {
   workerData,
   markAsUntransferable,
 } = require('node:worker_threads');
+const { register } = require('node:module');
+const { pathToFileURL } = require('node:url');
 
 const conversions = require('webidl-conversions');
 
+const nativeBinding = require('../load-native.cjs');
 // these are defined in rust side
 const {
+  copy_from_shared_audio_buffer,
   exit_audio_worklet_global_scope,
+  replace_audio_worklet_processor,
   run_audio_worklet_global_scope,
-} = require('../load-native.cjs');
+  take_shared_audio_buffer,
+} = nativeBinding;
+
+const {
+  parseParameterDescriptors,
+  takeAudioBuffers,
+} = require('./lib/worklet.js');
 
 const {
   workletId,
@@ -18,85 +29,37 @@ const {
 } = workerData;
 
 const kWorkletQueueTask = Symbol.for('node-web-audio-api:worklet-queue-task');
-const kWorkletCallableProcess = Symbol.for('node-web-audio-api:worklet-callable-process');
-const kWorkletInputs = Symbol.for('node-web-audio-api:worklet-inputs');
-const kWorkletOutputs = Symbol.for('node-web-audio-api:worklet-outputs');
-const kWorkletParams = Symbol.for('node-web-audio-api:worklet-params');
-const kWorkletParamsCache = Symbol.for('node-web-audio-api:worklet-params-cache');
-const kWorkletGetBuffer = Symbol.for('node-web-audio-api:worklet-get-buffer');
-const kWorkletRecycleBuffer = Symbol.for('node-web-audio-api:worklet-recycle-buffer');
-const kWorkletRecycleBuffer1 = Symbol.for('node-web-audio-api:worklet-recycle-buffer-1');
 const kWorkletMarkAsUntransferable = Symbol.for('node-web-audio-api:worklet-mark-as-untransferable');
+const kWorkletProcessorName = Symbol.for('node-web-audio-api:worklet-processor-name');
+const kWorkletProcessorOptions = Symbol.for('node-web-audio-api:worklet-processor-options');
 // const kWorkletOrderedParamNames = Symbol.for('node-web-audio-api:worklet-ordered-param-names');
 
 
+// load the modules added with `addModule`, and their imports, as ES modules
+// resolved relative to their URL, including over http
+register('./lib/worklet-loader.mjs', pathToFileURL(__filename));
+
 const nameProcessorCtorMap = new Map();
 const processors = {};
 let pendingProcessorConstructionData = null;
+// processors registered by a module added with `replace`, applied once the
+// whole module has been evaluated
+let pendingReplacements = null;
+// modules are evaluated one after the other, so that `pendingReplacements`
+// belongs to a single module
+let moduleQueue = Promise.resolve();
 let loopStarted = false;
 let runLoopImmediateId = null;
 
-class BufferPool {
-  #bufferSize;
-  #pool;
-
-  constructor(bufferSize, initialPoolSize) {
-    this.#bufferSize = bufferSize;
-    this.#pool = new Array(initialPoolSize);
-
-    for (let i = 0; i < this.#pool.length; i++) {
-      this.#pool[i] = this.#allocate();
-    }
-  }
-
-  #allocate() {
-    const float32 = new Float32Array(this.#bufferSize);
-    markAsUntransferable(float32);
-    // Mark underlying buffer as untransfrable too, this will fail one of
-    // the task in `audioworkletprocessor-process-frozen-array.https.html`
-    // but prevent segmentation fault
-    markAsUntransferable(float32.buffer);
-
-    return float32;
-  }
-
-  get() {
-    if (this.#pool.length === 0) {
-      return this.#allocate();
-    }
-
-    return this.#pool.pop();
-  }
-
-  recycle(buffer) {
-    // make sure we cannot polute our pool
-    if (buffer.length === this.#bufferSize) {
-      this.#pool.push(buffer);
-    }
-  }
-}
-
 const renderQuantumSize = 128;
 
-const pool128 = new BufferPool(renderQuantumSize, 256);
-const pool1 = new BufferPool(1, 64);
-// allow rust to access some methods required when io layout change
-globalThis[kWorkletGetBuffer] = () => pool128.get();
-globalThis[kWorkletRecycleBuffer] = buffer => pool128.recycle(buffer);
-globalThis[kWorkletRecycleBuffer1] = buffer => pool1.recycle(buffer);
+// allow rust to protect the memory shared with the render thread, i.e. the
+// inputs, outputs and params given to `process`
 globalThis[kWorkletMarkAsUntransferable] = obj => {
   markAsUntransferable(obj);
   return obj;
 }
 
-function isIterable(obj) {
-  // checks for null and undefined
-  if (obj === null || obj === undefined) {
-    return false;
-  }
-  return typeof obj[Symbol.iterator] === 'function';
-}
-
 // cf. https://stackoverflow.com/a/46759625
 function isConstructor(f) {
   try {
@@ -107,6 +70,55 @@ function isConstructor(f) {
   return true;
 }
 
+// read-only counterpart of the AudioBuffers given in `processorOptions`, which
+// shares the channel data of the main thread AudioBuffer. The render thread may
+// read the same memory, so that it is only read by `copyFromChannel`, while
+// `getChannelData` returns a copy of the channel made on first call
+function createSharedAudioBuffer(id) {
+  const shared = take_shared_audio_buffer(id);
+  const { sampleRate, length, numberOfChannels } = shared;
+  const channels = new Array(numberOfChannels).fill(null);
+
+  function checkChannel(method, channel) {
+    if (!(channel >= 0 && channel < numberOfChannels)) {
+      throw new DOMException(`Failed to execute '${method}' on 'AudioBuffer': channel index (${channel}) exceeds number of channels (${numberOfChannels})`, 'IndexSizeError');
+    }
+  }
+
+  return Object.freeze({
+    sampleRate,
+    length,
+    duration: length / sampleRate,
+    numberOfChannels,
+    copyFromChannel(destination, channelNumber, bufferOffset = 0) {
+      if (!(destination instanceof Float32Array)) {
+        throw new TypeError(`Failed to execute 'copyFromChannel' on 'AudioBuffer': parameter 1 is not of type 'Float32Array'`);
+      }
+
+      checkChannel('copyFromChannel', channelNumber);
+
+      bufferOffset = conversions['unsigned long'](bufferOffset, {
+        context: `Failed to execute 'copyFromChannel' on 'AudioBuffer': bufferOffset`,
+      });
+
+      // the data pointer of empty arrays may be null
+      if (destination.length > 0) {
+        copy_from_shared_audio_buffer(shared, destination, channelNumber, bufferOffset);
+      }
+    },
+    getChannelData(channel) {
+      checkChannel('getChannelData', channel);
+
+      if (channels[channel] === null) {
+        channels[channel] = new Float32Array(length);
+        this.copyFromChannel(channels[channel], channel);
+      }
+
+      return channels[channel];
+    },
+  });
+}
+
 function runLoop() {
   // block until we need to render a quantum
   run_audio_worklet_global_scope(workletId, processors);
@@ -119,6 +131,8 @@ globalThis.currentFrame = 0;
 globalThis.sampleRate = sampleRate;
 // @todo - implement in upstream crate
 globalThis.renderQuantumSize = renderQuantumSize;
+// non spec compliant, the other side of the ring buffers created in the main thread
+globalThis.AudioRingBuffer = require('./AudioRingBuffer.js')({}, nativeBinding);
 
 globalThis.AudioWorkletProcessor = class AudioWorkletProcessor {
   static get parameterDescriptors() {
@@ -128,35 +142,12 @@ globalThis.AudioWorkletProcessor = class AudioWorkletProcessor {
   #port = null;
 
   constructor() {
-    const {
-      port,
-      numberOfInputs,
-      numberOfOutputs,
-      parameterDescriptors,
-    } = pendingProcessorConstructionData;
-
-    // Mark [[callable process]] as true, set to false in render quantum
-    // either "process" doese not exists, either it throws an error
-    this[kWorkletCallableProcess] = true;
-
-    // Populate with dummy values which will be replaced in first render call
-    this[kWorkletInputs] = new Array(numberOfInputs).fill([]);
-    this[kWorkletOutputs] = new Array(numberOfOutputs).fill([]);
-
-    // Object to be reused as `process` parameters argument
-    this[kWorkletParams] = {};
-    // Cache of 2 Float32Array (of length 128 and 1) for each param, to be reused on
-    // each process call according to the size the param for the current render quantum
-    this[kWorkletParamsCache] = {};
-
-    parameterDescriptors.forEach(desc => {
-      this[kWorkletParamsCache][desc.name] = [
-        pool128.get(), // should be globalThis.renderQuantumSize
-        pool1.get(),
-      ]
-    });
+    const { port, name, options } = pendingProcessorConstructionData;
 
     this.#port = port;
+    // required to construct the processor again when its module is reloaded
+    this[kWorkletProcessorName] = name;
+    this[kWorkletProcessorOptions] = options;
   }
 
   get port() {
@@ -183,7 +174,10 @@ globalThis.registerProcessor = function registerProcessor(name, processorCtor) {
     throw new DOMException(`Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': name is empty`, 'NotSupportedError');
   }
 
-  if (nameProcessorCtorMap.has(name)) {
+  if (
+    (nameProcessorCtorMap.has(name) && pendingReplacements === null)
+    || (pendingReplacements !== null && pendingReplacements.has(parsedName))
+  ) {
     throw new DOMException(`Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': A processor with name '${name}' has already been registered in this scope`, 'NotSupportedError');
   }
 
@@ -195,99 +189,98 @@ globalThis.registerProcessor = function registerProcessor(name, processorCtor) {
     throw new TypeError(`Cannot execute 'registerProcessor")' in 'AudoWorkletGlobalScope': argument 2 for name '${name}' is not is not a valid AudioWorkletProcessor`);
   }
 
-  // must support Array, Set or iterators
-  let parameterDescriptorsValue = processorCtor.parameterDescriptors;
+  const parsedParamDescriptors = parseParameterDescriptors(
+    processorCtor.parameterDescriptors,
+    name,
+    `Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope'`,
+  );
 
-  if (!isIterable(parameterDescriptorsValue)) {
-    throw new TypeError(`Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}: 'parameterDescriptors' is not iterable'`);
+  if (pendingReplacements !== null) {
+    pendingReplacements.set(parsedName, {
+      processorCtor,
+      parameterDescriptors: parsedParamDescriptors,
+    });
+  } else {
+    defineProcessor(parsedName, processorCtor, parsedParamDescriptors);
   }
+};
 
-  const paramDescriptors = Array.from(parameterDescriptorsValue);
-  const parsedParamDescriptors = [];
-
-  // Parse AudioParamDescriptor sequence
-  // cf. https://webaudio.github.io/web-audio-api/#AudioParamDescriptor
-  for (let i = 0; i < paramDescriptors.length; i++) {
-    const descriptor = paramDescriptors[i];
-    const parsedDescriptor = {};
-
-    if (typeof descriptor !== 'object' || descriptor === null) {
-      throw new TypeError(`Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}: Element at index ${i} is not an instance of 'AudioParamDescriptor'`);
-    }
+function defineProcessor(name, processorCtor, parameterDescriptors) {
+  // store constructor
+  nameProcessorCtorMap.set(name, processorCtor);
+  // send param descriptors back to main thread
+  parentPort.postMessage({
+    cmd: 'node-web-audio-api:worlet:processor-registered',
+    name,
+    parameterDescriptors,
+  });
+}
 
-    if (descriptor.name === undefined) {
-      throw new TypeError(`Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}: Element at index ${i} is not an instance of 'AudioParamDescriptor'`);
-    }
+function constructProcessor(processorCtor, name, options, port) {
+  // rewrap options of interest for the AudioWorkletNodeBaseClass
+  pendingProcessorConstructionData = { port, name, options };
 
-    parsedDescriptor.name = conversions['DOMString'](descriptor.name, {
-      context: `Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}: Invalid 'name' for 'AudioParamDescriptor' at index ${i}`,
-    });
+  try {
+    return new processorCtor(options);
+  } finally {
+    pendingProcessorConstructionData = null;
+  }
+}
 
-    if (descriptor.defaultValue !== undefined) {
-      parsedDescriptor.defaultValue = conversions['float'](descriptor.defaultValue, {
-        context: `Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}: Invalid 'defaultValue' for 'AudioParamDescriptor' at index ${i}`,
-      });
-    } else {
-      parsedDescriptor.defaultValue = 0;
+// Swap the existing processors of the names registered again by a module
+// added with `replace` for instances of their new constructor. This happens
+// between two render quanta, as `process` is called from this thread too.
+// Nothing is replaced if a constructor or a `migrate` hook throws.
+function replaceProcessors(registrations) {
+  const instances = [];
+
+  for (const [id, processor] of Object.entries(processors)) {
+    // the constructor of the processor failed
+    if (processor === undefined) {
+      continue;
     }
 
-    if (descriptor.maxValue !== undefined) {
-      parsedDescriptor.maxValue = conversions['float'](descriptor.maxValue, {
-        context: `Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}: Invalid 'maxValue' for 'AudioParamDescriptor' at index ${i}`,
-      });
-    } else {
-      parsedDescriptor.maxValue = 3.4028235e38;
-    }
+    const name = processor[kWorkletProcessorName];
 
-    if (descriptor.minValue !== undefined) {
-      parsedDescriptor.minValue = conversions['float'](descriptor.minValue, {
-        context: `Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}: Invalid 'minValue' for 'AudioParamDescriptor' at index ${i}`,
-      });
-    } else {
-      parsedDescriptor.minValue = -3.4028235e38;
+    if (!registrations.has(name)) {
+      continue;
     }
 
-    if (descriptor.automationRate !== undefined) {
-      if (!['a-rate', 'k-rate'].includes(descriptor.automationRate)) {
-        throw new TypeError(`Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}: The provided value '${descriptor.automationRate}' is not a valid enum value of type AutomationRate for 'AudioParamDescriptor' at index ${i}`);
-      }
+    const { processorCtor } = registrations.get(name);
+    const options = processor[kWorkletProcessorOptions];
+    const instance = constructProcessor(processorCtor, name, options, processor.port);
 
-      parsedDescriptor.automationRate = conversions['DOMString'](descriptor.automationRate, {
-        context: `Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}: The provided value '${descriptor.automationRate}'`,
-      });
-    } else {
-      parsedDescriptor.automationRate = 'a-rate';
+    if (typeof instance.migrate === 'function') {
+      instance.migrate(processor);
     }
 
-    parsedParamDescriptors.push(parsedDescriptor);
+    instances.push([id, instance]);
   }
 
-  // check for duplicate parame names and consistency of min, max and default values
-  const paramNames = [];
-
-  for (let i = 0; i < parsedParamDescriptors.length; i++) {
-    const { name, defaultValue, minValue, maxValue } = parsedParamDescriptors[i];
+  registrations.forEach(({ processorCtor, parameterDescriptors }, name) => {
+    defineProcessor(name, processorCtor, parameterDescriptors);
+  });
 
-    if (paramNames.includes(name)) {
-      throw new DOMException(`Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}': 'AudioParamDescriptor' with name '${name}' already declared`, 'NotSupportedError');
-    }
+  instances.forEach(([id, instance]) => {
+    processors[id] = instance;
+    replace_audio_worklet_processor(Number(id), instance);
+  });
+}
 
-    paramNames.push(name);
+async function addModule(url, replace) {
+  const registrations = replace ? new Map() : null;
+  pendingReplacements = registrations;
 
-    if (!(minValue <= defaultValue && defaultValue <= maxValue)) {
-      throw new DOMException(`Cannot execute 'registerProcessor' in 'AudoWorkletGlobalScope': Invalid 'parameterDescriptors' for processor '${name}': The constraint minValue <= defaultValue <= maxValue is not met`, 'InvalidStateError');
-    }
+  try {
+    await import(url);
+  } finally {
+    pendingReplacements = null;
   }
 
-  // store constructor
-  nameProcessorCtorMap.set(parsedName, processorCtor);
-  // send param descriptors back to main thread
-  parentPort.postMessage({
-    cmd: 'node-web-audio-api:worlet:processor-registered',
-    name: parsedName,
-    parameterDescriptors: parsedParamDescriptors,
-  });
-};
+  if (registrations !== null) {
+    replaceProcessors(registrations);
+  }
+}
 
 
 // @todo - recheck this, not sure this is relevant in our case
@@ -313,17 +306,15 @@ parentPort.on('message', event => {
       break;
     }
     case 'node-web-audio-api:worklet:add-module': {
-      const { code, promiseId } = event;
-      const func = new Function('AudioWorkletProcessor', 'registerProcessor', code);
+      const { url, replace, promiseId } = event;
 
-      try {
-        func(AudioWorkletProcessor, registerProcessor);
+      moduleQueue = moduleQueue.then(() => addModule(url, replace)).then(() => {
         // send registered param descriptors on main thread and resolve Promise
         parentPort.postMessage({
           cmd: 'node-web-audio-api:worklet:module-added',
           promiseId,
         });
-      } catch (err) {
+      }).catch(err => {
         parentPort.postMessage({
           cmd: 'node-web-audio-api:worklet:add-module-failed',
           promiseId,
@@ -331,30 +322,22 @@ parentPort.on('message', event => {
           name: err.name,
           message: err.message,
         });
-      }
+      });
       break;
     }
     case 'node-web-audio-api:worklet:create-processor': {
       const { name, id, options, port } = event;
       const ctor = nameProcessorCtorMap.get(name);
 
-      // rewrap options of interest for the AudioWorkletNodeBaseClass
-      pendingProcessorConstructionData = {
-        port,
-        numberOfInputs: options.numberOfInputs,
-        numberOfOutputs: options.numberOfOutputs,
-        parameterDescriptors: ctor.parameterDescriptors,
-      };
-
       let instance;
 
       try {
-        instance = new ctor(options);
+        options.processorOptions = takeAudioBuffers(options.processorOptions, createSharedAudioBuffer);
+        instance = constructProcessor(ctor, name, options, port);
       } catch (err) {
         port.postMessage({ cmd: 'node-web-audio-api:worklet:ctor-error', err });
       }
 
-      pendingProcessorConstructionData = null;
       // store in global so that Rust can match the JS processor
       // with its corresponding NapiAudioWorkletProcessor
       processors[`${id}`] = instance;
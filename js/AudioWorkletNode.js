@@ -1,4 +1,7 @@
 /* eslint-disable no-unused-vars */
+const {
+  MessageChannel,
+} = require('node:worker_threads');
 const conversions = require('webidl-conversions');
 const {
   toSanitizedSequence,
@@ -10,8 +13,15 @@ const {
   kNapiObj,
   kProcessorRegistered,
   kGetParameterDescriptors,
+  kGetWasmProcessor,
+  kWorkletThreadCount,
+  kNextWorkletThread,
+  kWarnSequentialProcess,
   kPrivateConstructor,
   kCreateProcessor,
+  kParamOwner,
+  kBatchedNodes,
+  kDisableBatching,
 } = require('./lib/symbols.js');
 const {
   kEnumerableProperty,
@@ -22,6 +32,9 @@ const {
 const {
   ErrorEvent,
 } = require('./Events.js');
+const {
+  shareAudioBuffers,
+} = require('./lib/worklet.js');
 
 /* eslint-enable no-unused-vars */
 
@@ -29,10 +42,31 @@ const AudioNode = require('./AudioNode.js');
 const AudioParamMap = require('./AudioParamMap.js');
 const IMPLEMENTATION_MAX_NUMBER_OF_CHANNELS = 32;
 
+// Messages exchanged with WebAssembly processors are raw bytes
+function toBuffer(data) {
+  if (typeof data === 'string') {
+    return Buffer.from(data);
+  } else if (data instanceof ArrayBuffer) {
+    return Buffer.from(data);
+  } else if (ArrayBuffer.isView(data)) {
+    return Buffer.from(data.buffer, data.byteOffset, data.byteLength);
+  }
+
+  return null;
+}
+
 module.exports = (jsExport, nativeBinding) => {
+  // AudioBuffers of the processorOptions that have not been taken by a Worker
+  function releaseSharedAudioBuffers(ids) {
+    ids.forEach(id => nativeBinding.release_shared_audio_buffer(id));
+  }
+
+  const sharedAudioBuffersRegistry = new FinalizationRegistry(releaseSharedAudioBuffers);
+
   class AudioWorkletNode extends AudioNode {
     #port = null;
     #parameters = {};
+    #batchLatency = 0;
 
     constructor(context, name, options) {
       if (arguments.length < 2) {
@@ -146,6 +180,98 @@ module.exports = (jsExport, nativeBinding) => {
         parsedOptions.processorOptions = {};
       }
 
+      // Non spec compliant, watchdog of the `process` calls
+      // - processDeadline: maximum duration (in seconds) of a `process` call
+      //   before the render quantum is rendered as silence. For realtime
+      //   contexts, defaults to a budget of a render quantum shared by all
+      //   the nodes without processDeadline (null)
+      // - maxMissedQuanta: number of consecutive missed render quanta after
+      //   which the processor is not called anymore
+      if (options && options.processDeadline !== undefined) {
+        parsedOptions.processDeadline = conversions['unrestricted double'](options.processDeadline, {
+          context: `Failed to construct 'AudioWorkletNode': Failed to read the 'processDeadline' property from AudioWorkletNodeOptions: The provided value (${options.processDeadline})`,
+        });
+
+        if (!(parsedOptions.processDeadline > 0)) {
+          throw new RangeError(`Failed to construct 'AudioWorkletNode': Invalid 'processDeadline' property from AudioWorkletNodeOptions: The provided value (${options.processDeadline}) should be strictly positive`);
+        }
+      } else if (context instanceof jsExport.OfflineAudioContext) {
+        parsedOptions.processDeadline = Infinity;
+      } else {
+        parsedOptions.processDeadline = null;
+      }
+
+      if (options && options.maxMissedQuanta !== undefined) {
+        parsedOptions.maxMissedQuanta = conversions['unsigned long'](options.maxMissedQuanta, {
+          enforceRange: true,
+          context: `Failed to construct 'AudioWorkletNode': Failed to read the 'maxMissedQuanta' property from AudioWorkletNodeOptions: The provided value (${options.maxMissedQuanta})`,
+        });
+
+        if (parsedOptions.maxMissedQuanta === 0) {
+          throw new RangeError(`Failed to construct 'AudioWorkletNode': Invalid 'maxMissedQuanta' property from AudioWorkletNodeOptions: The provided value (${options.maxMissedQuanta}) should be strictly positive`);
+        }
+      } else {
+        parsedOptions.maxMissedQuanta = 128;
+      }
+
+      // Non spec compliant, AudioWorklet thread running the processor, cf.
+      // `workletThreads` AudioContext option
+      const workletThreadCount = context.audioWorklet[kWorkletThreadCount]();
+
+      if (options && options.workletThreadIndex !== undefined) {
+        parsedOptions.workletThreadIndex = conversions['unsigned long'](options.workletThreadIndex, {
+          enforceRange: true,
+          context: `Failed to construct 'AudioWorkletNode': Failed to read the 'workletThreadIndex' property from AudioWorkletNodeOptions: The provided value (${options.workletThreadIndex})`,
+        });
+
+        if (parsedOptions.workletThreadIndex >= workletThreadCount) {
+          throw new DOMException(`Failed to construct 'AudioWorkletNode': Invalid 'workletThreadIndex' property from AudioWorkletNodeOptions: The provided value (${options.workletThreadIndex}) is outside the number of AudioWorklet threads (${workletThreadCount})`, 'IndexSizeError');
+        }
+      } else {
+        parsedOptions.workletThreadIndex = context.audioWorklet[kNextWorkletThread]();
+      }
+
+      // Non spec compliant, the `process` calls run concurrently with the
+      // render thread and the other Workers until the next render quantum,
+      // the output of the node being delayed by a render quantum
+      if (options && options.pipelined !== undefined) {
+        parsedOptions.pipelined = conversions['boolean'](options.pipelined);
+      } else {
+        parsedOptions.pipelined = false;
+      }
+
+      if (!parsedOptions.pipelined) {
+        context.audioWorklet[kWarnSequentialProcess]();
+      }
+
+      // Non spec compliant, number of render quanta processed per round-trip
+      // with the Worker in an OfflineAudioContext
+      if (options && options.batchQuanta !== undefined) {
+        parsedOptions.batchQuanta = conversions['unsigned long'](options.batchQuanta, {
+          enforceRange: true,
+          context: `Failed to construct 'AudioWorkletNode': Failed to read the 'batchQuanta' property from AudioWorkletNodeOptions: The provided value (${options.batchQuanta})`,
+        });
+
+        if (parsedOptions.batchQuanta === 0) {
+          throw new RangeError(`Failed to construct 'AudioWorkletNode': Invalid 'batchQuanta' property from AudioWorkletNodeOptions: The provided value (${options.batchQuanta}) should be strictly positive`);
+        }
+
+        if (parsedOptions.batchQuanta > 1 && !(context instanceof jsExport.OfflineAudioContext)) {
+          throw new DOMException(`Failed to construct 'AudioWorkletNode': Invalid 'batchQuanta' property from AudioWorkletNodeOptions: render quanta can only be batched in an OfflineAudioContext`, 'NotSupportedError');
+        }
+
+        if (parsedOptions.batchQuanta > 1 && parsedOptions.pipelined) {
+          throw new DOMException(`Failed to construct 'AudioWorkletNode': Invalid 'batchQuanta' property from AudioWorkletNodeOptions: render quanta cannot be batched in a pipelined node`, 'NotSupportedError');
+        }
+
+        // a batch is processed at once, its render quanta cannot be missed
+        if (parsedOptions.batchQuanta > 1 && Number.isFinite(parsedOptions.processDeadline)) {
+          throw new DOMException(`Failed to construct 'AudioWorkletNode': Invalid 'batchQuanta' property from AudioWorkletNodeOptions: render quanta cannot be batched with a 'processDeadline'`, 'NotSupportedError');
+        }
+      } else {
+        parsedOptions.batchQuanta = 1;
+      }
+
       // AudioNodeOptions
       if (options && options.channelCount !== undefined) {
         parsedOptions.channelCount = conversions['unsigned long'](options.channelCount, {
@@ -182,15 +308,41 @@ module.exports = (jsExport, nativeBinding) => {
 
       // Create NapiAudioWorkletNode
       const parameterDescriptors = context.audioWorklet[kGetParameterDescriptors](parsedName);
+      const wasmProcessor = context.audioWorklet[kGetWasmProcessor](parsedName);
+      const wasmChannel = wasmProcessor !== undefined ? new MessageChannel() : null;
+      // events posted by the native processor, i.e. messages of WebAssembly
+      // processors and errors raised on the render thread
+      const processor = {
+        onevent: (_err, event) => {
+          switch (event.type) {
+            case 'message': {
+              wasmChannel.port2.postMessage(event.data);
+              break;
+            }
+            case 'processorerror': {
+              const message = `Failed to execute 'process' on '${parsedName}' AudioWorkletProcessor: ${event.message}`;
+              const error = new Error(message);
+              propagateEvent(this, new ErrorEvent('processorerror', { message, error }));
+              break;
+            }
+          }
+        },
+      };
+
+      if (wasmProcessor !== undefined) {
+        processor.wasm = {
+          module: wasmProcessor.module,
+          index: wasmProcessor.index,
+          sampleRate: context.sampleRate,
+        };
+      }
+
+      const args = [context[kNapiObj], parsedName, parsedOptions, parameterDescriptors, processor];
+
       let napiObj;
 
       try {
-        napiObj = new nativeBinding.AudioWorkletNode(
-          context[kNapiObj],
-          parsedName,
-          parsedOptions,
-          parameterDescriptors,
-        );
+        napiObj = new nativeBinding.AudioWorkletNode(...args);
       } catch (err) {
         throwSanitizedError(err);
       }
@@ -204,6 +356,7 @@ module.exports = (jsExport, nativeBinding) => {
       for (let name in this[kNapiObj].parameters) {
         const audioParam = new jsExport.AudioParam({
           [kNapiObj]: this[kNapiObj].parameters[name],
+          [kParamOwner]: this,
         });
 
         parameters.set(name, audioParam);
@@ -214,13 +367,67 @@ module.exports = (jsExport, nativeBinding) => {
         parameters,
       });
 
-      // Create JS processor
-      this.#port = context.audioWorklet[kCreateProcessor](
-        parsedName,
-        parsedOptions,
-        napiObj.id,
+      // WebAssembly processor, already running on the render thread
+      if (wasmChannel !== null) {
+        this.#port = wasmChannel.port1;
+
+        wasmChannel.port2.on('message', data => {
+          const buffer = toBuffer(data);
+
+          if (buffer === null) {
+            console.warn(`AudioWorkletNode: '${parsedName}' AudioWorkletProcessor only accepts strings, ArrayBuffers and ArrayBufferViews messages`);
+            return;
+          }
+
+          napiObj.wasmPort.postMessage(buffer);
+        });
+        // the user end of the port decides whether the process is kept alive
+        wasmChannel.port2.unref();
+
+        return;
+      }
+
+      // the render quanta of a processor with inputs or parameters are only
+      // known once rendered, a batch is processed once all its render quanta
+      // have been queued, which delays the output of the node
+      if (parsedOptions.batchQuanta > 1 && (parsedOptions.numberOfInputs > 0 || parameterDescriptors.length > 0)) {
+        this.#batchLatency = (parsedOptions.batchQuanta - 1) * 128 / context.sampleRate;
+        // cf. AudioNode.connect
+        context[kBatchedNodes] ??= new Set();
+        context[kBatchedNodes].add(this);
+      }
+
+      // AudioBuffers are not cloned, the processor accesses their channel data
+      const sharedAudioBufferIds = [];
+      parsedOptions.processorOptions = shareAudioBuffers(
+        parsedOptions.processorOptions,
+        jsExport.AudioBuffer,
+        audioBuffer => {
+          const id = nativeBinding.share_audio_buffer(audioBuffer[kNapiObj]);
+          sharedAudioBufferIds.push(id);
+          return id;
+        },
       );
 
+      // Create JS processor
+      try {
+        this.#port = context.audioWorklet[kCreateProcessor](
+          parsedName,
+          parsedOptions,
+          napiObj.id,
+        );
+      } catch (err) {
+        // e.g. DataCloneError, the Worker will never take the buffers
+        releaseSharedAudioBuffers(sharedAudioBufferIds);
+        throw err;
+      }
+
+      // the buffers that are not taken by the Worker, e.g. if the context is
+      // closed before the processor is created, are released with the node
+      if (sharedAudioBufferIds.length > 0) {
+        sharedAudioBuffersRegistry.register(this, sharedAudioBufferIds);
+      }
+
       this.#port.on('message', msg => {
         // ErrorEvent named processorerror
         switch (msg.cmd) {
@@ -264,6 +471,59 @@ module.exports = (jsExport, nativeBinding) => {
 
       return this.#port;
     }
+
+    get missedQuanta() {
+      if (!(this instanceof AudioWorkletNode)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioWorkletNode\'');
+      }
+
+      return this[kNapiObj].missedQuanta;
+    }
+
+    /**
+     * Non spec compliant, delay (in seconds) added to the output of the node
+     * by `batchQuanta`, i.e. `(batchQuanta - 1)` render quanta for processors
+     * with inputs or parameters and 0 otherwise
+     *
+     * The latency drops to 0 when the node is connected in a cycle, as its
+     * render quanta are then processed one at a time.
+     */
+    get batchLatency() {
+      if (!(this instanceof AudioWorkletNode)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioWorkletNode\'');
+      }
+
+      return this.#batchLatency;
+    }
+
+    // a delayed batch would feed back into itself, cf. AudioNode.connect
+    [kDisableBatching]() {
+      this[kNapiObj].disableBatching();
+      this.#batchLatency = 0;
+    }
+
+    /**
+     * Non spec compliant, durations (in seconds) of the `process` calls since
+     * the creation of the node or the last call to `resetStats`, measured from
+     * the render thread requesting the call to the Worker answering it
+     *
+     * `lateQuanta` counts the calls that lasted longer than a render quantum.
+     */
+    getStats() {
+      if (!(this instanceof AudioWorkletNode)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioWorkletNode\'');
+      }
+
+      return this[kNapiObj].getStats();
+    }
+
+    resetStats() {
+      if (!(this instanceof AudioWorkletNode)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'AudioWorkletNode\'');
+      }
+
+      this[kNapiObj].resetStats();
+    }
   }
 
   Object.defineProperties(AudioWorkletNode, {
@@ -286,6 +546,10 @@ module.exports = (jsExport, nativeBinding) => {
     },
     parameters: kEnumerableProperty,
     port: kEnumerableProperty,
+    missedQuanta: kEnumerableProperty,
+    batchLatency: kEnumerableProperty,
+    getStats: kEnumerableProperty,
+    resetStats: kEnumerableProperty,
   });
 
   return AudioWorkletNode;
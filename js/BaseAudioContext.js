@@ -25,11 +25,12 @@ const {
 const {
   kNapiObj,
   kPrivateConstructor,
+  kCreateTap,
 } = require('./lib/symbols.js');
 
 const AudioWorklet = require('./AudioWorklet.js');
 
-module.exports = (jsExport, _nativeBinding) => {
+module.exports = (jsExport, nativeBinding) => {
   class BaseAudioContext extends EventTarget {
     #audioWorklet = null;
     #destination = null;
@@ -53,7 +54,7 @@ module.exports = (jsExport, _nativeBinding) => {
 
       this.#audioWorklet = new AudioWorklet({
         [kPrivateConstructor]: true,
-        workletId: this[kNapiObj].workletId,
+        workletIds: this[kNapiObj].workletIds,
         sampleRate: this[kNapiObj].sampleRate,
       });
 
@@ -230,6 +231,30 @@ module.exports = (jsExport, _nativeBinding) => {
       return new jsExport.PeriodicWave(this, options);
     }
 
+    // non spec compliant, opt-in profiler attributing render time to nodes
+    createNodeProfiler() {
+      if (!(this instanceof BaseAudioContext)) {
+        throw new TypeError("Invalid Invocation: Value of 'this' must be of type 'BaseAudioContext'");
+      }
+
+      return new jsExport.AudioNodeProfiler({
+        [kNapiObj]: new nativeBinding.AudioNodeProfiler(this[kNapiObj]),
+        context: this,
+      });
+    }
+
+    // non spec compliant, cf. `AudioNode.tap`
+    [kCreateTap](node, options, callback) {
+      const napiTap = new nativeBinding.AudioNodeTap(this[kNapiObj], options.channels, options.bufferSize);
+
+      return new jsExport.AudioNodeTap({
+        [kNapiObj]: napiTap,
+        node,
+        output: options.output,
+        callback,
+      });
+    }
+
     // --------------------------------------------------------------------
     // Factory Methods (use the patched AudioNodes)
     // --------------------------------------------------------------------
@@ -451,6 +476,7 @@ module.exports = (jsExport, _nativeBinding) => {
     decodeAudioData: kEnumerableProperty,
     createBuffer: kEnumerableProperty,
     createPeriodicWave: kEnumerableProperty,
+    createNodeProfiler: kEnumerableProperty,
   });
 
   return BaseAudioContext;
@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -139,15 +140,19 @@ module.exports = (jsExport, nativeBinding) => {
 
       this.#frequency = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].frequency,
+        [kParamOwner]: this,
       });
       this.#detune = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].detune,
+        [kParamOwner]: this,
       });
       this.#Q = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].Q,
+        [kParamOwner]: this,
       });
       this.#gain = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].gain,
+        [kParamOwner]: this,
       });
     }
 
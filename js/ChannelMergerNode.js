@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -81,6 +82,7 @@ module.exports = (jsExport, nativeBinding) => {
 
       this.#offset = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].offset,
+        [kParamOwner]: this,
       });
     }
 
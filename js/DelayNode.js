@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -108,6 +109,7 @@ module.exports = (jsExport, nativeBinding) => {
 
       this.#delayTime = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].delayTime,
+        [kParamOwner]: this,
       });
     }
 
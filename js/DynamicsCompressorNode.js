@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -136,18 +137,23 @@ module.exports = (jsExport, nativeBinding) => {
 
       this.#threshold = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].threshold,
+        [kParamOwner]: this,
       });
       this.#knee = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].knee,
+        [kParamOwner]: this,
       });
       this.#ratio = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].ratio,
+        [kParamOwner]: this,
       });
       this.#attack = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].attack,
+        [kParamOwner]: this,
       });
       this.#release = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].release,
+        [kParamOwner]: this,
       });
     }
 
@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -100,6 +101,7 @@ module.exports = (jsExport, nativeBinding) => {
 
       this.#gain = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].gain,
+        [kParamOwner]: this,
       });
     }
 
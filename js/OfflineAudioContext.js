@@ -21,6 +21,7 @@ const {
 module.exports = function patchOfflineAudioContext(jsExport, nativeBinding) {
   class OfflineAudioContext extends jsExport.BaseAudioContext {
     #renderedBuffer = null;
+    #renderCapacity = null;
 
     constructor(...args) {
       if (arguments.length < 1) {
@@ -80,6 +81,11 @@ module.exports = function patchOfflineAudioContext(jsExport, nativeBinding) {
 
       super({ [kNapiObj]: napiObj });
 
+      // non spec compliant, load is measured against the audio time
+      this.#renderCapacity = new jsExport.AudioRenderCapacity({
+        [kNapiObj]: this[kNapiObj].renderCapacity,
+      });
+
       // Add function to Napi object to bridge from Rust events to JS EventTarget
       // They will be effectively registered on rust side when `startRendering` is called
       this[kNapiObj][kOnStateChange] = (function(_err, rawEvent) {
@@ -112,6 +118,14 @@ module.exports = function patchOfflineAudioContext(jsExport, nativeBinding) {
       return this[kNapiObj].length;
     }
 
+    get renderCapacity() {
+      if (!(this instanceof OfflineAudioContext)) {
+        throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'OfflineAudioContext'`);
+      }
+
+      return this.#renderCapacity;
+    }
+
     get oncomplete() {
       if (!(this instanceof OfflineAudioContext)) {
         throw new TypeError(`Invalid Invocation: Value of 'this' must be of type 'OfflineAudioContext'`);
@@ -205,6 +219,7 @@ module.exports = function patchOfflineAudioContext(jsExport, nativeBinding) {
     },
 
     length: kEnumerableProperty,
+    renderCapacity: kEnumerableProperty,
     oncomplete: kEnumerableProperty,
     startRendering: kEnumerableProperty,
     resume: kEnumerableProperty,
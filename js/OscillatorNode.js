@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -139,9 +140,11 @@ module.exports = (jsExport, nativeBinding) => {
 
       this.#frequency = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].frequency,
+        [kParamOwner]: this,
       });
       this.#detune = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].detune,
+        [kParamOwner]: this,
       });
     }
 
@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -217,21 +218,27 @@ module.exports = (jsExport, nativeBinding) => {
 
       this.#positionX = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].positionX,
+        [kParamOwner]: this,
       });
       this.#positionY = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].positionY,
+        [kParamOwner]: this,
       });
       this.#positionZ = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].positionZ,
+        [kParamOwner]: this,
       });
       this.#orientationX = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].orientationX,
+        [kParamOwner]: this,
       });
       this.#orientationY = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].orientationY,
+        [kParamOwner]: this,
       });
       this.#orientationZ = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].orientationZ,
+        [kParamOwner]: this,
       });
     }
 
@@ -13,6 +13,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
   kOnAudioProcess,
 } = require('./lib/symbols.js');
 const {
@@ -95,6 +96,20 @@ module.exports = (jsExport, nativeBinding) => {
         });
       }
 
+      // non standard, what to do with the `audioprocess` events when the
+      // handler lags behind, cf. README
+      if (options && options.overflow !== undefined) {
+        parsedOptions.overflow = conversions['DOMString'](options.overflow, {
+          context: `Failed to construct 'ScriptProcessorNode': Failed to read the 'overflow' property from ScriptProcessorNodeOptions: The provided value '${options.overflow}'`,
+        });
+
+        if (!['block', 'drop'].includes(parsedOptions.overflow)) {
+          throw new TypeError(`Failed to construct 'ScriptProcessorNode': Failed to read the 'overflow' property from ScriptProcessorNodeOptions: The provided value '${parsedOptions.overflow}' is not a valid enum value of type ScriptProcessorOverflowPolicy`);
+        }
+      } else {
+        parsedOptions.overflow = 'block';
+      }
+
       let napiObj;
 
       try {
@@ -129,6 +144,36 @@ module.exports = (jsExport, nativeBinding) => {
       return this[kNapiObj].bufferSize;
     }
 
+    // non standard, number of `audioprocess` events dropped with the `drop`
+    // overflow policy, their output is silent
+    get droppedEvents() {
+      if (!(this instanceof ScriptProcessorNode)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'ScriptProcessorNode\'');
+      }
+
+      return this[kNapiObj].droppedEvents;
+    }
+
+    // non standard, the node does not keep the process alive by default,
+    // `ref()` and `unref()` behave as their `Timeout` counterparts
+    ref() {
+      if (!(this instanceof ScriptProcessorNode)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'ScriptProcessorNode\'');
+      }
+
+      this[kNapiObj].ref();
+      return this;
+    }
+
+    unref() {
+      if (!(this instanceof ScriptProcessorNode)) {
+        throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'ScriptProcessorNode\'');
+      }
+
+      this[kNapiObj].unref();
+      return this;
+    }
+
     get onaudioprocess() {
       if (!(this instanceof ScriptProcessorNode)) {
         throw new TypeError('Invalid Invocation: Value of \'this\' must be of type \'ScriptProcessorNode\'');
@@ -167,7 +212,10 @@ module.exports = (jsExport, nativeBinding) => {
       value: 'ScriptProcessorNode',
     },
     bufferSize: kEnumerableProperty,
+    droppedEvents: kEnumerableProperty,
     onaudioprocess: kEnumerableProperty,
+    ref: kEnumerableProperty,
+    unref: kEnumerableProperty,
 
   });
 
@@ -32,6 +32,7 @@ const {
 const {
   kNapiObj,
   kAudioBuffer,
+  kParamOwner,
 } = require('./lib/symbols.js');
 /* eslint-enable no-unused-vars */
 
@@ -100,6 +101,7 @@ module.exports = (jsExport, nativeBinding) => {
 
       this.#pan = new jsExport.AudioParam({
         [kNapiObj]: this[kNapiObj].pan,
+        [kParamOwner]: this,
       });
     }
 
@@ -0,0 +1,227 @@
+const conversions = require('webidl-conversions');
+
+const { isFunction } = require('./utils.js');
+
+// Sink types implemented on top of the `none` sink of the render thread,
+// cf. src/sinks/mod.rs
+const kSinkTypes = ['stream', 'file', 'custom'];
+const kSampleFormats = ['f32', 's16'];
+const kFileFormats = ['wav', 'flac'];
+
+function isNodeWritable(stream) {
+  return stream !== null
+    && typeof stream === 'object'
+    && isFunction(stream.write)
+    && isFunction(stream.once);
+}
+
+function isWritableStream(stream) {
+  return stream !== null
+    && typeof stream === 'object'
+    && isFunction(stream.getWriter);
+}
+
+/**
+ * Parse the `AudioSinkOptions` given to the AudioContext constructor
+ *
+ * Returns `null` if the options describe a regular `{ type: 'none' }` sink,
+ * or the options to be given to the native context otherwise.
+ */
+exports.parseSinkOptions = function parseSinkOptions(sinkId, context) {
+  if (!('type' in sinkId) || !['none', ...kSinkTypes].includes(sinkId.type)) {
+    throw new TypeError(`${context}: Failed to read the 'type' property from 'AudioSinkOptions': The provided value (${sinkId.type}) is not a valid enum value of type AudioSinkType.`);
+  }
+
+  if (sinkId.type === 'none') {
+    return null;
+  }
+
+  const sink = { type: sinkId.type };
+
+  if (sinkId.numberOfChannels !== undefined) {
+    sink.numberOfChannels = conversions['unsigned long'](sinkId.numberOfChannels, {
+      enforceRange: true,
+      context: `${context}: Failed to read the 'numberOfChannels' property from 'AudioSinkOptions': The provided value (${sinkId.numberOfChannels})`,
+    });
+
+    if (sink.numberOfChannels === 0 || sink.numberOfChannels > 32) {
+      throw new DOMException(`${context}: Failed to read the 'numberOfChannels' property from 'AudioSinkOptions': The provided value (${sinkId.numberOfChannels}) is outside the range [1, 32]`, 'NotSupportedError');
+    }
+  } else {
+    sink.numberOfChannels = 2;
+  }
+
+  if (sinkId.bufferSize !== undefined) {
+    sink.bufferSize = conversions['unsigned long'](sinkId.bufferSize, {
+      enforceRange: true,
+      context: `${context}: Failed to read the 'bufferSize' property from 'AudioSinkOptions': The provided value (${sinkId.bufferSize})`,
+    });
+
+    if (sink.bufferSize === 0) {
+      throw new DOMException(`${context}: Failed to read the 'bufferSize' property from 'AudioSinkOptions': The provided value (${sinkId.bufferSize}) should be strictly positive`, 'NotSupportedError');
+    }
+  } else {
+    sink.bufferSize = 1024;
+  }
+
+  if (sinkId.type === 'stream') {
+    if (!isNodeWritable(sinkId.stream) && !isWritableStream(sinkId.stream)) {
+      throw new TypeError(`${context}: Failed to read the 'stream' property from 'AudioSinkOptions': The provided value is neither a Node.js 'Writable' nor a 'WritableStream'`);
+    }
+
+    if (sinkId.format !== undefined) {
+      if (!kSampleFormats.includes(sinkId.format)) {
+        throw new TypeError(`${context}: Failed to read the 'format' property from 'AudioSinkOptions': The provided value (${sinkId.format}) is not a valid enum value of type AudioSinkFormat.`);
+      }
+
+      sink.format = sinkId.format;
+    } else {
+      sink.format = 'f32';
+    }
+  }
+
+  if (sinkId.type === 'file') {
+    if (sinkId.path === undefined) {
+      throw new TypeError(`${context}: Failed to read the 'path' property from 'AudioSinkOptions': Required member is undefined.`);
+    }
+
+    sink.path = conversions['DOMString'](sinkId.path, {
+      context: `${context}: Failed to read the 'path' property from 'AudioSinkOptions': The provided value (${sinkId.path})`,
+    });
+
+    sink.format = sinkId.format !== undefined ? sinkId.format : 'wav';
+
+    if (!kFileFormats.includes(sink.format)) {
+      throw new TypeError(`${context}: Failed to read the 'format' property from 'AudioSinkOptions': The provided value (${sinkId.format}) is not a valid enum value of type AudioSinkFileFormat.`);
+    }
+
+    if (sink.format === 'flac' && sink.numberOfChannels > 8) {
+      throw new DOMException(`${context}: Failed to read the 'numberOfChannels' property from 'AudioSinkOptions': The provided value (${sink.numberOfChannels}) is outside the range [1, 8] of 'flac' files`, 'NotSupportedError');
+    }
+
+    if (sinkId.sampleFormat !== undefined) {
+      if (!kSampleFormats.includes(sinkId.sampleFormat)) {
+        throw new TypeError(`${context}: Failed to read the 'sampleFormat' property from 'AudioSinkOptions': The provided value (${sinkId.sampleFormat}) is not a valid enum value of type AudioSinkFormat.`);
+      }
+
+      sink.sampleFormat = sinkId.sampleFormat;
+    } else {
+      sink.sampleFormat = 'f32';
+    }
+  }
+
+  if (sinkId.type === 'custom') {
+    if (!isFunction(sinkId.onData)) {
+      throw new TypeError(`${context}: Failed to read the 'onData' property from 'AudioSinkOptions': The provided value is not a function`);
+    }
+  }
+
+  return sink;
+};
+
+/**
+ * Create the function that forwards the chunks rendered by a `stream` sink to
+ * the user provided stream.
+ *
+ * The render thread runs on its own clock, so chunks received while the
+ * stream applies backpressure are dropped rather than buffered.
+ */
+exports.createStreamSinkWriter = function createStreamSinkWriter(stream) {
+  if (isNodeWritable(stream)) {
+    let waitingDrain = false;
+
+    return function write(_err, chunk) {
+      if (waitingDrain || stream.destroyed || stream.writableEnded) {
+        return;
+      }
+
+      if (!stream.write(chunk)) {
+        waitingDrain = true;
+        stream.once('drain', () => waitingDrain = false);
+      }
+    };
+  } else {
+    const writer = stream.getWriter();
+    let closed = false;
+
+    writer.closed.catch(() => {}).finally(() => closed = true);
+
+    return function write(_err, chunk) {
+      if (closed || writer.desiredSize <= 0) {
+        return;
+      }
+
+      writer.write(chunk).catch(() => {});
+    };
+  }
+};
+
+/**
+ * Create the function that forwards the blocks rendered by a `custom` sink to
+ * the user defined `onData` callback.
+ *
+ * The callback receives an array of `Float32Array`, one per channel, each
+ * containing `bufferSize` frames (the last block may be shorter when the
+ * context is closed). Errors thrown by the callback are given to `onError`.
+ */
+exports.createCustomSinkWriter = function createCustomSinkWriter(sinkId, onError) {
+  return function write(_err, channels) {
+    try {
+      sinkId.onData(channels);
+    } catch (err) {
+      onError(err);
+    }
+  };
+};
+
+/**
+ * Parse the non standard `jack` options of the AudioContext (for outputs) and
+ * of `getUserMedia` audio constraints (for inputs)
+ *
+ * `connect` is a glob pattern, e.g. 'system:playback_*', matching the ports
+ * our ports are connected to in order, `null` or `false` to not connect them.
+ */
+exports.parseJackOptions = function parseJackOptions(jack = {}, isInput, context) {
+  if (typeof jack !== 'object' || jack === null) {
+    throw new TypeError(`${context}: Failed to read the 'jack' property: The provided value is not an object`);
+  }
+
+  const options = {};
+
+  options.clientName = jack.clientName !== undefined
+    ? conversions['DOMString'](jack.clientName, {
+      context: `${context}: Failed to read the 'clientName' property from 'JackOptions': The provided value (${jack.clientName})`,
+    })
+    : 'node-web-audio-api';
+
+  options.portPrefix = jack.portPrefix !== undefined
+    ? conversions['DOMString'](jack.portPrefix, {
+      context: `${context}: Failed to read the 'portPrefix' property from 'JackOptions': The provided value (${jack.portPrefix})`,
+    })
+    : (isInput ? 'in_' : 'out_');
+
+  if (jack.numberOfChannels !== undefined) {
+    options.numberOfChannels = conversions['unsigned long'](jack.numberOfChannels, {
+      enforceRange: true,
+      context: `${context}: Failed to read the 'numberOfChannels' property from 'JackOptions': The provided value (${jack.numberOfChannels})`,
+    });
+
+    if (options.numberOfChannels === 0 || options.numberOfChannels > 32) {
+      throw new DOMException(`${context}: Failed to read the 'numberOfChannels' property from 'JackOptions': The provided value (${jack.numberOfChannels}) is outside the range [1, 32]`, 'NotSupportedError');
+    }
+  } else {
+    options.numberOfChannels = 2;
+  }
+
+  if (jack.connect === null || jack.connect === false) {
+    options.connect = null;
+  } else if (jack.connect !== undefined) {
+    options.connect = conversions['DOMString'](jack.connect, {
+      context: `${context}: Failed to read the 'connect' property from 'JackOptions': The provided value (${jack.connect})`,
+    });
+  } else {
+    options.connect = isInput ? 'system:capture_*' : 'system:playback_*';
+  }
+
+  return options;
+};
@@ -4,8 +4,18 @@ module.exports.kPrivateConstructor = Symbol('node-web-audio-api:private-construc
 module.exports.kCreateProcessor = Symbol('node-web-audio-api:create-processor');
 module.exports.kProcessorRegistered = Symbol('node-web-audio-api:processor-registered');
 module.exports.kGetParameterDescriptors = Symbol('node-web-audio-api:get-parameter-descriptors');
+module.exports.kGetWasmProcessor = Symbol('node-web-audio-api:get-wasm-processor');
 module.exports.kWorkletRelease = Symbol('node-web-audio-api:worklet-release');
+module.exports.kWorkletThreadCount = Symbol('node-web-audio-api:worklet-thread-count');
+module.exports.kNextWorkletThread = Symbol('node-web-audio-api:next-worklet-thread');
+module.exports.kWarnSequentialProcess = Symbol('node-web-audio-api:warn-sequential-process');
 module.exports.kCheckProcessorsCreated = Symbol('node-web-audio-api:check-processor-created');
+module.exports.kCreateTap = Symbol('node-web-audio-api:create-tap');
+module.exports.kProfilerProbes = Symbol('node-web-audio-api:profiler-probes');
+module.exports.kParamOwner = Symbol('node-web-audio-api:param-owner');
+module.exports.kConnections = Symbol('node-web-audio-api:connections');
+module.exports.kBatchedNodes = Symbol('node-web-audio-api:batched-nodes');
+module.exports.kDisableBatching = Symbol('node-web-audio-api:disable-batching');
 
 // semi-private keys for events listeners
 
@@ -13,6 +23,9 @@ module.exports.kCheckProcessorsCreated = Symbol('node-web-audio-api:check-proces
 module.exports.kOnStateChange = Symbol.for('node-web-audio-api:onstatechange');
 // AudioContext
 module.exports.kOnSinkChange = Symbol.for('node-web-audio-api:onsinkchange');
+module.exports.kOnSinkData = Symbol.for('node-web-audio-api:onsinkdata');
+module.exports.kOnDeviceStatus = Symbol.for('node-web-audio-api:ondevicestatus');
+module.exports.kOnSchedulingWarning = Symbol.for('node-web-audio-api:onschedulingwarning');
 // # OfflineAudioContext
 // > [The onstatechange] event is fired before the complete event is fired
 // cf. https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-onstatechange
@@ -24,3 +37,5 @@ module.exports.kOnEnded = Symbol.for('node-web-audio-api:onended');
 module.exports.kOnAudioProcess = Symbol.for('node-web-audio-api:onaudioprocess');
 // # AudioRenderCapacity
 module.exports.kOnUpdate = Symbol.for('node-web-audio-api:onupdate');
+// # AudioNodeTap
+module.exports.kOnTapData = Symbol.for('node-web-audio-api:ontapdata');
@@ -0,0 +1,89 @@
+/**
+ * Module customization hooks of the AudioWorkletGlobalScope, registered in each
+ * Worker so that the modules added with `addModule` and their imports can be
+ * loaded over http
+ *
+ * File and data URLs are handled by the default Node.js loader. Blob URLs are
+ * not available in the Worker threads, their content is therefore sent by the
+ * main thread as a data URL.
+ *
+ * As in browsers, the worklet modules are always ES modules, whatever the
+ * `type` of the enclosing package.json, only packages imported with a bare
+ * specifier follow the Node.js rules.
+ *
+ * The modules added with `replace` are imported with a new URL fragment, so
+ * that they are evaluated again, the fragment being propagated to the modules
+ * they import.
+ */
+import { pathToFileURL } from 'node:url';
+
+const isHttp = url => url.startsWith('http:') || url.startsWith('https:');
+
+// Specifiers that can be resolved against any parent URL, e.g. `./dsp.js`
+const isRelative = specifier => /^\.{0,2}\//.test(specifier);
+// Specifiers that are neither relative nor URLs, e.g. `my-dsp-package`
+const isBare = specifier => !isRelative(specifier) && !/^[a-z][a-z0-9+.-]*:/i.test(specifier);
+
+// Fragment added to the URL of the modules added with `replace`
+const kReloadFragment = /#node-web-audio-api-reload-\d+$/;
+
+export async function resolve(specifier, context, nextResolve) {
+  const resolved = await resolveModule(specifier, context, nextResolve);
+  const reload = context.parentURL && context.parentURL.match(kReloadFragment);
+
+  // the modules imported by a reloaded module are reloaded too, except packages
+  if (reload && !isBare(specifier) && !resolved.url.includes('/node_modules/')) {
+    return { ...resolved, url: resolved.url.split('#')[0] + reload[0] };
+  }
+
+  return resolved;
+}
+
+async function resolveModule(specifier, context, nextResolve) {
+  const { parentURL } = context;
+
+  if (isHttp(specifier)) {
+    return { url: specifier, shortCircuit: true };
+  }
+
+  if (parentURL && isHttp(parentURL) && isRelative(specifier)) {
+    return { url: new URL(specifier, parentURL).href, shortCircuit: true };
+  }
+
+  // bare specifiers, e.g. packages, imported from a module that is not in the
+  // file system are resolved from the current working directory
+  if (parentURL && !parentURL.startsWith('file:') && isBare(specifier)) {
+    const cwdURL = pathToFileURL(`${process.cwd()}/`).href;
+    return nextResolve(specifier, { ...context, parentURL: cwdURL });
+  }
+
+  const resolved = await nextResolve(specifier, context);
+
+  if (
+    resolved.url.startsWith('file:')
+    && new URL(resolved.url).pathname.endsWith('.js')
+    && !resolved.url.includes('/node_modules/')
+  ) {
+    return { ...resolved, format: 'module' };
+  }
+
+  return resolved;
+}
+
+export async function load(url, context, nextLoad) {
+  if (isHttp(url)) {
+    const res = await fetch(url);
+
+    if (!res.ok) {
+      throw new Error(`Cannot fetch module ${url}: ${res.status} ${res.statusText}`);
+    }
+
+    return {
+      format: 'module',
+      source: await res.text(),
+      shortCircuit: true,
+    };
+  }
+
+  return nextLoad(url, context);
+}
@@ -0,0 +1,187 @@
+const conversions = require('webidl-conversions');
+
+function isIterable(obj) {
+  // checks for null and undefined
+  if (obj === null || obj === undefined) {
+    return false;
+  }
+  return typeof obj[Symbol.iterator] === 'function';
+}
+
+/**
+ * Parse and validate the `parameterDescriptors` of the processor `name`,
+ * `context` is the prefix of the error messages
+ *
+ * Shared by the JS processors registered in the AudioWorkletGlobalScope and
+ * by the WebAssembly processors registered through `addWasmModule`
+ */
+exports.parseParameterDescriptors = function parseParameterDescriptors(parameterDescriptorsValue, name, context) {
+  // must support Array, Set or iterators
+  if (!isIterable(parameterDescriptorsValue)) {
+    throw new TypeError(`${context}: Invalid 'parameterDescriptors' for processor '${name}: 'parameterDescriptors' is not iterable'`);
+  }
+
+  const paramDescriptors = Array.from(parameterDescriptorsValue);
+  const parsedParamDescriptors = [];
+
+  // Parse AudioParamDescriptor sequence
+  // cf. https://webaudio.github.io/web-audio-api/#AudioParamDescriptor
+  for (let i = 0; i < paramDescriptors.length; i++) {
+    const descriptor = paramDescriptors[i];
+    const parsedDescriptor = {};
+
+    if (typeof descriptor !== 'object' || descriptor === null) {
+      throw new TypeError(`${context}: Invalid 'parameterDescriptors' for processor '${name}: Element at index ${i} is not an instance of 'AudioParamDescriptor'`);
+    }
+
+    if (descriptor.name === undefined) {
+      throw new TypeError(`${context}: Invalid 'parameterDescriptors' for processor '${name}: Element at index ${i} is not an instance of 'AudioParamDescriptor'`);
+    }
+
+    parsedDescriptor.name = conversions['DOMString'](descriptor.name, {
+      context: `${context}: Invalid 'parameterDescriptors' for processor '${name}: Invalid 'name' for 'AudioParamDescriptor' at index ${i}`,
+    });
+
+    if (descriptor.defaultValue !== undefined) {
+      parsedDescriptor.defaultValue = conversions['float'](descriptor.defaultValue, {
+        context: `${context}: Invalid 'parameterDescriptors' for processor '${name}: Invalid 'defaultValue' for 'AudioParamDescriptor' at index ${i}`,
+      });
+    } else {
+      parsedDescriptor.defaultValue = 0;
+    }
+
+    if (descriptor.maxValue !== undefined) {
+      parsedDescriptor.maxValue = conversions['float'](descriptor.maxValue, {
+        context: `${context}: Invalid 'parameterDescriptors' for processor '${name}: Invalid 'maxValue' for 'AudioParamDescriptor' at index ${i}`,
+      });
+    } else {
+      parsedDescriptor.maxValue = 3.4028235e38;
+    }
+
+    if (descriptor.minValue !== undefined) {
+      parsedDescriptor.minValue = conversions['float'](descriptor.minValue, {
+        context: `${context}: Invalid 'parameterDescriptors' for processor '${name}: Invalid 'minValue' for 'AudioParamDescriptor' at index ${i}`,
+      });
+    } else {
+      parsedDescriptor.minValue = -3.4028235e38;
+    }
+
+    if (descriptor.automationRate !== undefined) {
+      if (!['a-rate', 'k-rate'].includes(descriptor.automationRate)) {
+        throw new TypeError(`${context}: Invalid 'parameterDescriptors' for processor '${name}: The provided value '${descriptor.automationRate}' is not a valid enum value of type AutomationRate for 'AudioParamDescriptor' at index ${i}`);
+      }
+
+      parsedDescriptor.automationRate = conversions['DOMString'](descriptor.automationRate, {
+        context: `${context}: Invalid 'parameterDescriptors' for processor '${name}: The provided value '${descriptor.automationRate}'`,
+      });
+    } else {
+      parsedDescriptor.automationRate = 'a-rate';
+    }
+
+    parsedParamDescriptors.push(parsedDescriptor);
+  }
+
+  // check for duplicate parame names and consistency of min, max and default values
+  const paramNames = [];
+
+  for (let i = 0; i < parsedParamDescriptors.length; i++) {
+    const { name, defaultValue, minValue, maxValue } = parsedParamDescriptors[i];
+
+    if (paramNames.includes(name)) {
+      throw new DOMException(`${context}: Invalid 'parameterDescriptors' for processor '${name}': 'AudioParamDescriptor' with name '${name}' already declared`, 'NotSupportedError');
+    }
+
+    paramNames.push(name);
+
+    if (!(minValue <= defaultValue && defaultValue <= maxValue)) {
+      throw new DOMException(`${context}: Invalid 'parameterDescriptors' for processor '${name}': The constraint minValue <= defaultValue <= maxValue is not met`, 'InvalidStateError');
+    }
+  }
+
+  return parsedParamDescriptors;
+};
+
+// key of the objects standing for an AudioBuffer in the processorOptions,
+// symbols are not kept by the structured clone algorithm
+const kSharedAudioBufferId = 'node-web-audio-api:shared-audio-buffer-id';
+
+function isPlainObject(value) {
+  if (typeof value !== 'object' || value === null) {
+    return false;
+  }
+
+  const proto = Object.getPrototypeOf(value);
+  return proto === Object.prototype || proto === null;
+}
+
+// copy the arrays and plain objects of `value`, replacing the values for
+// which `replace` returns something else than `undefined`
+function mapOptions(value, replace, copies) {
+  const replacement = replace(value);
+
+  if (replacement !== undefined) {
+    return replacement;
+  }
+
+  if (!Array.isArray(value) && !isPlainObject(value)) {
+    return value;
+  }
+
+  // keep the cycles and the shared references, as the structured clone does
+  if (copies.has(value)) {
+    return copies.get(value);
+  }
+
+  const copy = Array.isArray(value) ? [] : {};
+  copies.set(value, copy);
+
+  for (let key of Object.keys(value)) {
+    copy[key] = mapOptions(value[key], replace, copies);
+  }
+
+  return copy;
+}
+
+/**
+ * Replace the AudioBuffers found in the `processorOptions` of an
+ * AudioWorkletNode with the ID returned by `share`, so that the processor
+ * can access their channel data without copy
+ */
+exports.shareAudioBuffers = function shareAudioBuffers(processorOptions, AudioBuffer, share) {
+  const ids = new Map();
+
+  return mapOptions(processorOptions, value => {
+    if (!(value instanceof AudioBuffer)) {
+      return undefined;
+    }
+
+    // a buffer given twice is shared once
+    if (!ids.has(value)) {
+      ids.set(value, share(value));
+    }
+
+    return { [kSharedAudioBufferId]: ids.get(value) };
+  }, new Map());
+};
+
+/**
+ * Replace the IDs set by `shareAudioBuffers` with the values returned by
+ * `take`, called once per ID
+ */
+exports.takeAudioBuffers = function takeAudioBuffers(processorOptions, take) {
+  const buffers = new Map();
+
+  return mapOptions(processorOptions, value => {
+    if (!isPlainObject(value) || !(kSharedAudioBufferId in value)) {
+      return undefined;
+    }
+
+    const id = value[kSharedAudioBufferId];
+
+    if (!buffers.has(id)) {
+      buffers.set(id, take(id));
+    }
+
+    return buffers.get(id);
+  }, new Map());
+};
@@ -0,0 +1,189 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use web_audio_api::context::{AudioContext, BaseAudioContext};
+use web_audio_api::node::{AudioNode, AudioNodeOptions, ChannelCountMode, ChannelInterpretation};
+use web_audio_api::worklet::{
+    AudioParamValues, AudioWorkletGlobalScope, AudioWorkletNode, AudioWorkletNodeOptions,
+    AudioWorkletProcessor,
+};
+
+/// Wall time between two render quanta, in nanoseconds, beyond which the
+/// audio clock is considered to have been stopped, e.g. by `suspend`
+const MAX_CALLBACK_GAP: u64 = 500_000_000;
+
+/// Wall time, in nanoseconds, over which the drift must be measured before
+/// being reported
+const MIN_DRIFT_WINDOW: u64 = 1_000_000_000;
+
+/// Wall time, in nanoseconds, after the audio clock (re)starts during which
+/// the render thread may run ahead to fill the output buffers
+const WARM_UP: u64 = 1_000_000_000;
+
+/// Snapshot of the audio clock at the last render quantum
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ClockSnapshot {
+    /// Frame at the start of the last rendered quantum
+    pub current_frame: u64,
+    /// Wall time elapsed since the last rendered quantum, in nanoseconds
+    pub elapsed: u64,
+    /// Drift of the audio clock versus the system clock, in parts per million
+    pub drift: f64,
+}
+
+/// Mapping between the audio clock of an AudioContext and the system clock
+///
+/// Written by the render thread only and read from the main thread, the
+/// fields are published with a sequence lock so that a snapshot is never torn.
+pub(crate) struct AudioClock {
+    origin: Instant,
+    sequence: AtomicU64,
+    frame: AtomicU64,
+    /// nanoseconds since `origin`, `u64::MAX` until the first render quantum
+    timestamp: AtomicU64,
+    /// f64 bits
+    drift: AtomicU64,
+}
+
+impl Default for AudioClock {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+            sequence: AtomicU64::new(0),
+            frame: AtomicU64::new(0),
+            timestamp: AtomicU64::new(u64::MAX),
+            drift: AtomicU64::new(0_f64.to_bits()),
+        }
+    }
+}
+
+impl AudioClock {
+    fn now(&self) -> u64 {
+        self.origin.elapsed().as_nanos() as u64
+    }
+
+    fn publish(&self, frame: u64, timestamp: u64, drift: f64) {
+        // odd sequence while the fields are being written
+        self.sequence.fetch_add(1, Ordering::AcqRel);
+        self.frame.store(frame, Ordering::Release);
+        self.timestamp.store(timestamp, Ordering::Release);
+        self.drift.store(drift.to_bits(), Ordering::Release);
+        self.sequence.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Snapshot of the clock, `None` until the first render quantum
+    pub fn snapshot(&self) -> Option<ClockSnapshot> {
+        loop {
+            let sequence = self.sequence.load(Ordering::Acquire);
+
+            if sequence % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+
+            let frame = self.frame.load(Ordering::Acquire);
+            let timestamp = self.timestamp.load(Ordering::Acquire);
+            let drift = f64::from_bits(self.drift.load(Ordering::Acquire));
+
+            if self.sequence.load(Ordering::Acquire) != sequence {
+                continue;
+            }
+
+            if timestamp == u64::MAX {
+                return None;
+            }
+
+            return Some(ClockSnapshot {
+                current_frame: frame,
+                elapsed: self.now().saturating_sub(timestamp),
+                drift,
+            });
+        }
+    }
+
+    /// Create the processor feeding the clock from the render thread of the
+    /// given context
+    pub fn bind(self: &Arc<Self>, context: &AudioContext) {
+        let options = AudioWorkletNodeOptions {
+            number_of_inputs: 1,
+            number_of_outputs: 0,
+            output_channel_count: vec![],
+            parameter_data: Default::default(),
+            audio_node_options: AudioNodeOptions {
+                channel_count: 1,
+                channel_count_mode: ChannelCountMode::Explicit,
+                channel_interpretation: ChannelInterpretation::Discrete,
+            },
+            processor_options: ClockProbe {
+                clock: Arc::clone(self),
+                reference: None,
+                warmed_up: false,
+                last_call: 0,
+            },
+        };
+
+        let probe = AudioWorkletNode::new::<ClockProbe>(context, options);
+        context.destination().connect(&probe);
+    }
+}
+
+/// Processor timestamping each render quantum
+///
+/// The drift is measured from a reference quantum, which is taken again
+/// whenever the render thread has been stopped, so that the time spent
+/// suspended does not count as drift, and once the output is warmed up.
+struct ClockProbe {
+    clock: Arc<AudioClock>,
+    /// (frame, timestamp) of the reference quantum
+    reference: Option<(u64, u64)>,
+    warmed_up: bool,
+    last_call: u64,
+}
+
+impl AudioWorkletProcessor for ClockProbe {
+    type ProcessorOptions = ClockProbe;
+
+    fn constructor(opts: Self::ProcessorOptions) -> Self {
+        opts // the opts contain the full processor
+    }
+
+    fn process<'a, 'b>(
+        &mut self,
+        _inputs: &'b [&'a [&'a [f32]]],
+        _outputs: &'b mut [&'a mut [&'a mut [f32]]],
+        _params: AudioParamValues<'b>,
+        scope: &'b AudioWorkletGlobalScope,
+    ) -> bool {
+        let now = self.clock.now();
+        let frame = scope.current_frame;
+        let gap = now.saturating_sub(std::mem::replace(&mut self.last_call, now));
+
+        let (ref_frame, ref_timestamp) = match self.reference {
+            Some((_, ref_timestamp))
+                if gap < MAX_CALLBACK_GAP && !self.warmed_up && now - ref_timestamp >= WARM_UP =>
+            {
+                self.warmed_up = true;
+                *self.reference.insert((frame, now))
+            }
+            Some(reference) if gap < MAX_CALLBACK_GAP => reference,
+            _ => {
+                self.warmed_up = false;
+                *self.reference.insert((frame, now))
+            }
+        };
+
+        let wall_time = now - ref_timestamp;
+
+        let drift = if self.warmed_up && wall_time >= MIN_DRIFT_WINDOW {
+            let audio_time = (frame - ref_frame) as f64 / scope.sample_rate as f64;
+            (audio_time / (wall_time as f64 / 1e9) - 1.) * 1e6
+        } else {
+            0.
+        };
+
+        self.clock.publish(frame, now, drift);
+
+        true
+    }
+}
@@ -1,5 +1,5 @@
 use std::io::Cursor;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use napi::threadsafe_function::{ThreadSafeCallContext, ThreadsafeFunctionCallMode};
 use napi::*;
@@ -7,11 +7,34 @@ use napi_derive::js_function;
 use web_audio_api::context::*;
 use web_audio_api::Event;
 
+use crate::audio_clock::AudioClock;
+use crate::audio_worklet_node::{
+    allocate_process_call_channel, ProcessBudget, ProcessCallChannelGuard,
+};
+use crate::sinks::{
+    spawn_device_watchdog, AudioSink, AudioSinkOptions, DeviceLossPolicy, DeviceSink,
+    DeviceStatusEvent, JackOptions, JackSink, MirrorSink, PlayoutStats,
+};
+use crate::thread_scheduling::{ThreadScheduling, ThreadSchedulingOptions};
 use crate::*;
 
-/// Napi object wrapping the native AudioContext and the AudioWorklet ID
+/// Napi object wrapping the native AudioContext, the AudioWorklet channels, the
+/// optional sink handled by this crate, the device loss policy, the playout
+/// stats, the scheduling requested for the audio threads, the audio clock, the
+/// audio devices mirroring the output and the render quantum budget of the
+/// AudioWorklet processors
 #[derive(Clone)]
-pub(crate) struct NapiAudioContext(Arc<AudioContext>, usize);
+pub(crate) struct NapiAudioContext(
+    Arc<AudioContext>,
+    Arc<Vec<ProcessCallChannelGuard>>,
+    Arc<Mutex<Option<AudioSink>>>,
+    DeviceLossPolicy,
+    Arc<PlayoutStats>,
+    Arc<ThreadScheduling>,
+    Arc<AudioClock>,
+    Arc<Mutex<Vec<MirrorSink>>>,
+    Arc<ProcessBudget>,
+);
 
 // for debug purpose
 // impl Drop for NapiAudioContext {
@@ -27,6 +50,9 @@ impl NapiAudioContext {
             Property::new("outputLatency")?.with_getter(get_output_latency),
             Property::new("sinkId")?.with_getter(get_sink_id),
             Property::new("setSinkId")?.with_method(set_sink_id),
+            Property::new("getClockInfo")?.with_method(get_clock_info),
+            Property::new("addSink")?.with_method(add_sink),
+            Property::new("removeSink")?.with_method(remove_sink),
             Property::new("resume")?.with_method(resume),
             Property::new("suspend")?.with_method(suspend),
             Property::new("close")?.with_method(close),
@@ -43,8 +69,17 @@ impl NapiAudioContext {
         &self.0
     }
 
-    pub fn worklet_id(&self) -> usize {
-        self.1
+    /// IDs of the channels of the AudioWorklet threads
+    pub fn worklet_ids(&self) -> Vec<u64> {
+        self.1.iter().map(|channel| channel.worklet_id()).collect()
+    }
+
+    pub fn playout_stats(&self) -> Arc<PlayoutStats> {
+        Arc::clone(&self.4)
+    }
+
+    pub fn process_budget(&self) -> Arc<ProcessBudget> {
+        Arc::clone(&self.8)
     }
 }
 
@@ -92,6 +127,54 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     let sink_id_utf8 = sink_id_js.into_utf8()?.into_owned()?;
     let sink_id = sink_id_utf8.as_str().to_string();
 
+    let mut sink_options = AudioSinkOptions::from_js(&js_options)?;
+
+    let device_loss_policy_js = js_options
+        .get::<&str, JsString>("deviceLossPolicy")?
+        .unwrap();
+    let device_loss_policy_utf8 = device_loss_policy_js.into_utf8()?;
+    let device_loss_policy = DeviceLossPolicy::from_str(device_loss_policy_utf8.as_str()?);
+
+    let backend_js = js_options.get::<&str, JsString>("backend")?.unwrap();
+    let backend = backend_js.into_utf8()?.into_owned()?;
+
+    let (sample_rate, sink_id) = if backend == "jack" && sink_options.is_none() {
+        // render on the "none" sink and expose the frames as JACK ports
+        let js_jack_options = js_options.get_named_property::<JsObject>("jack")?;
+        let jack_sink = JackSink::open(JackOptions::from_js(&js_jack_options)?)?;
+        let jack_sample_rate = jack_sink.sample_rate();
+
+        if sample_rate.is_some_and(|sample_rate| sample_rate != jack_sample_rate) {
+            return Err(napi::Error::from_reason(format!(
+                "NotSupportedError - Failed to construct 'AudioContext': sampleRate must match the sample rate of the JACK server ({jack_sample_rate})"
+            )));
+        }
+
+        sink_options = Some(AudioSinkOptions::from_jack(jack_sink));
+
+        (Some(jack_sample_rate), String::from("none"))
+    } else if backend != "default" && sink_options.is_none() {
+        // render on the "none" sink and forward the frames to the device
+        let device = DeviceSink::open(backend, sink_id, sample_rate)?;
+        let sample_rate = Some(device.sample_rate());
+        sink_options = Some(AudioSinkOptions::from_device(device));
+
+        (sample_rate, String::from("none"))
+    } else {
+        (sample_rate, sink_id)
+    };
+
+    let js_render_thread = js_options.get_named_property::<JsObject>("renderThread")?;
+    let js_worklet_thread = js_options.get_named_property::<JsObject>("workletThread")?;
+    let lock_memory = js_options
+        .get_named_property::<JsBoolean>("lockMemory")?
+        .get_value()?;
+    let scheduling = Arc::new(ThreadScheduling::new(
+        ThreadSchedulingOptions::from_js(&js_render_thread)?,
+        ThreadSchedulingOptions::from_js(&js_worklet_thread)?,
+        lock_memory,
+    ));
+
     let audio_context_options = AudioContextOptions {
         latency_hint,
         sample_rate,
@@ -100,12 +183,41 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     };
 
     let audio_context = AudioContext::new(audio_context_options);
-    let worklet_id = crate::audio_worklet_node::allocate_process_call_channel();
+    // non spec compliant, number of Workers running the AudioWorkletProcessors
+    let worklet_threads = js_options
+        .get_named_property::<JsNumber>("workletThreads")?
+        .get_uint32()?;
+    let worklet_channels: Vec<_> = (0..worklet_threads)
+        .map(|_| allocate_process_call_channel(Some(Arc::clone(&scheduling))))
+        .collect();
+    let worklet_ids: Vec<u64> = worklet_channels
+        .iter()
+        .map(|channel| channel.worklet_id())
+        .collect();
+    scheduling.bind_render_thread(&audio_context);
+    let clock = Arc::new(AudioClock::default());
+    clock.bind(&audio_context);
+    let sink = sink_options
+        .map(|options| AudioSink::new(&audio_context, options))
+        .transpose()?;
+    let playout_stats = Arc::new(PlayoutStats::default());
+    playout_stats.set_available(sink.is_some() || audio_context.sink_id() == "none");
+    let process_budget = Arc::new(ProcessBudget::new(audio_context.sample_rate()));
 
     // -------------------------------------------------
     // Wrap context
     // -------------------------------------------------
-    let napi_audio_context = NapiAudioContext(Arc::new(audio_context), worklet_id);
+    let napi_audio_context = NapiAudioContext(
+        Arc::new(audio_context),
+        Arc::new(worklet_channels),
+        Arc::new(Mutex::new(sink)),
+        device_loss_policy,
+        playout_stats,
+        scheduling,
+        clock,
+        Arc::new(Mutex::new(vec![])),
+        process_budget,
+    );
     ctx.env.wrap(&mut js_this, napi_audio_context)?;
 
     js_this.define_properties(&[Property::new("Symbol.toStringTag")?
@@ -113,7 +225,7 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
         .with_property_attributes(PropertyAttributes::Static)])?;
 
     // -------------------------------------------------
-    // Bind AudioDestination and AudioRenderCapacity
+    // Bind AudioDestination, AudioRenderCapacity and AudioPlaybackStats
     // -------------------------------------------------
     let store_ref: &mut napi::Ref<()> = ctx.env.get_instance_data()?.unwrap();
     let store: JsObject = ctx.env.get_reference_value(store_ref)?;
@@ -126,8 +238,18 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     let js_obj = ctor.new_instance(&[&js_this])?;
     js_this.set_named_property("renderCapacity", &js_obj)?;
 
-    // internal id to retrieve worklet message channel
-    js_this.set_named_property("workletId", ctx.env.create_uint32(worklet_id as u32)?)?;
+    let ctor: JsFunction = store.get_named_property("AudioPlaybackStats")?;
+    let js_obj = ctor.new_instance(&[&js_this])?;
+    js_this.set_named_property("playbackStats", &js_obj)?;
+
+    // internal ids to retrieve worklet message channels
+    let mut js_worklet_ids = ctx.env.create_array_with_length(worklet_ids.len())?;
+
+    for (index, worklet_id) in worklet_ids.iter().enumerate() {
+        js_worklet_ids.set_element(index as u32, ctx.env.create_int64(*worklet_id as i64)?)?;
+    }
+
+    js_this.set_named_property("workletIds", js_worklet_ids)?;
 
     ctx.env.get_undefined()
 }
@@ -182,6 +304,88 @@ fn set_sink_id(ctx: CallContext) -> Result<JsUndefined> {
     ctx.env.get_undefined()
 }
 
+// The hrtime of the last render quantum is computed on the JS side, from
+// the time elapsed since then, to use the same clock as `process.hrtime`
+#[js_function]
+fn get_clock_info(ctx: CallContext) -> Result<JsObject> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioContext>(&js_this)?;
+    let sample_rate = napi_obj.unwrap().sample_rate() as f64;
+
+    let mut js_info = ctx.env.create_object()?;
+
+    match napi_obj.6.snapshot() {
+        Some(snapshot) => {
+            let current_frame = snapshot.current_frame as f64;
+            js_info.set_named_property("currentFrame", ctx.env.create_double(current_frame)?)?;
+            js_info.set_named_property(
+                "currentTime",
+                ctx.env.create_double(current_frame / sample_rate)?,
+            )?;
+            js_info
+                .set_named_property("elapsed", ctx.env.create_double(snapshot.elapsed as f64)?)?;
+            js_info.set_named_property("drift", ctx.env.create_double(snapshot.drift)?)?;
+        }
+        None => {
+            js_info.set_named_property("currentFrame", ctx.env.create_double(0.)?)?;
+            js_info.set_named_property("currentTime", ctx.env.create_double(0.)?)?;
+            js_info.set_named_property("elapsed", ctx.env.get_null()?)?;
+            js_info.set_named_property("drift", ctx.env.create_double(0.)?)?;
+        }
+    }
+
+    Ok(js_info)
+}
+
+#[js_function(3)]
+fn add_sink(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioContext>(&js_this)?;
+    let context = napi_obj.unwrap();
+
+    let sink_id = ctx.get::<JsString>(0)?.into_utf8()?.into_owned()?;
+    let backend = ctx.get::<JsString>(1)?.into_utf8()?.into_owned()?;
+    let latency = ctx.get::<JsNumber>(2)?.get_double()?;
+
+    let mut mirrors = napi_obj.7.lock().unwrap();
+
+    if mirrors.iter().any(|mirror| mirror.sink_id() == sink_id) {
+        return Err(napi::Error::from_reason(format!(
+            "InvalidStateError - Sink '{sink_id}' is already mirroring the context"
+        )));
+    }
+
+    let mirror = MirrorSink::open(context, &backend, sink_id, latency)?;
+    mirrors.push(mirror);
+
+    ctx.env.get_undefined()
+}
+
+#[js_function(1)]
+fn remove_sink(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioContext>(&js_this)?;
+    let context = napi_obj.unwrap();
+
+    let sink_id = ctx.get::<JsString>(0)?.into_utf8()?.into_owned()?;
+
+    let mut mirrors = napi_obj.7.lock().unwrap();
+
+    match mirrors
+        .iter()
+        .position(|mirror| mirror.sink_id() == sink_id)
+    {
+        Some(index) => mirrors.remove(index).close(context),
+        None => {
+            return Err(napi::Error::from_reason(format!(
+                "NotFoundError - Sink '{sink_id}' is not mirroring the context"
+            )))
+        }
+    }
+
+    ctx.env.get_undefined()
+}
+
 #[js_function]
 fn resume(ctx: CallContext) -> Result<JsObject> {
     let js_this = ctx.this_unchecked::<JsObject>();
@@ -217,11 +421,29 @@ fn close(ctx: CallContext) -> Result<JsObject> {
     let js_this = ctx.this_unchecked::<JsObject>();
     let napi_context = ctx.env.unwrap::<NapiAudioContext>(&js_this)?;
     let context_clone = Arc::clone(&napi_context.0);
+    let sink_clone = Arc::clone(&napi_context.2);
+    let mirrors_clone = Arc::clone(&napi_context.7);
+    let worklet_channels_clone = Arc::clone(&napi_context.1);
 
     ctx.env.execute_tokio_future(
         async move {
             context_clone.close().await;
-            Ok(())
+            // the AudioWorklet has been released on JS side, its channels can
+            // be reused by another context
+            worklet_channels_clone
+                .iter()
+                .for_each(|channel| channel.release());
+
+            let closed = match sink_clone.lock().unwrap().as_mut() {
+                Some(sink) => sink.close(),
+                None => Ok(()),
+            };
+
+            for mut mirror in mirrors_clone.lock().unwrap().drain(..) {
+                mirror.close(&context_clone);
+            }
+
+            closed
         },
         |&mut env, _val| env.get_undefined(),
     )
@@ -253,6 +475,7 @@ fn listen_to_events(ctx: CallContext) -> Result<JsUndefined> {
     let k_onstatechange = crate::utils::get_symbol_for(ctx.env, "node-web-audio-api:onstatechange");
     let statechange_cb = js_this.get_property(k_onstatechange).unwrap();
     let context_clone = Arc::clone(&napi_context.0);
+    let scheduling_clone = Arc::clone(&napi_context.5);
 
     let mut statechange_tsfn = ctx.env.create_threadsafe_function(
         &statechange_cb,
@@ -262,6 +485,7 @@ fn listen_to_events(ctx: CallContext) -> Result<JsUndefined> {
                 // clear all context listeners, so that it can be garbage collected
                 context_clone.clear_onsinkchange();
                 context_clone.clear_onstatechange();
+                scheduling_clone.clear_onwarning();
             }
 
             let mut event = ctx.env.create_object()?;
@@ -284,5 +508,54 @@ fn listen_to_events(ctx: CallContext) -> Result<JsUndefined> {
         statechange_tsfn.call(Ok(e), ThreadsafeFunctionCallMode::Blocking);
     });
 
+    let k_onschedulingwarning =
+        crate::utils::get_symbol_for(ctx.env, "node-web-audio-api:onschedulingwarning");
+    let schedulingwarning_cb = js_this.get_property(k_onschedulingwarning).unwrap();
+    let mut schedulingwarning_tsfn = ctx.env.create_threadsafe_function(
+        &schedulingwarning_cb,
+        0,
+        |ctx: ThreadSafeCallContext<String>| {
+            let message = ctx.env.create_string(&ctx.value)?;
+            Ok(vec![message])
+        },
+    )?;
+
+    let _ = schedulingwarning_tsfn.unref(ctx.env);
+
+    // warnings are raised from the audio threads, which must never block
+    napi_context.5.set_onwarning(move |msg| {
+        schedulingwarning_tsfn.call(Ok(msg), ThreadsafeFunctionCallMode::NonBlocking);
+    });
+
+    if let Some(sink) = napi_context.2.lock().unwrap().as_mut() {
+        sink.listen(ctx.env, &js_this, &napi_context.4)?;
+    }
+
+    let k_ondevicestatus =
+        crate::utils::get_symbol_for(ctx.env, "node-web-audio-api:ondevicestatus");
+    let devicestatus_cb = js_this.get_property(k_ondevicestatus).unwrap();
+    let mut devicestatus_tsfn = ctx.env.create_threadsafe_function(
+        &devicestatus_cb,
+        0,
+        |ctx: ThreadSafeCallContext<DeviceStatusEvent>| {
+            let mut event = ctx.env.create_object()?;
+            let event_type = ctx.env.create_string(ctx.value.type_)?;
+            event.set_named_property("type", event_type)?;
+            let message = ctx.env.create_string(&ctx.value.message)?;
+            event.set_named_property("message", message)?;
+            Ok(vec![event])
+        },
+    )?;
+
+    let _ = devicestatus_tsfn.unref(ctx.env);
+
+    spawn_device_watchdog(
+        Arc::downgrade(&napi_context.0),
+        Arc::clone(&napi_context.2),
+        napi_context.3,
+        devicestatus_tsfn,
+        napi_context.playout_stats(),
+    );
+
     ctx.env.get_undefined()
 }
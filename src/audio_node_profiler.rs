@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::*;
+
+use napi::*;
+use napi_derive::js_function;
+use web_audio_api::context::BaseAudioContext;
+use web_audio_api::node::{AudioNodeOptions, ChannelCountMode, ChannelInterpretation};
+use web_audio_api::worklet::{
+    AudioParamValues, AudioWorkletGlobalScope, AudioWorkletNode, AudioWorkletNodeOptions,
+    AudioWorkletProcessor,
+};
+
+use crate::audio_worklet_node::NapiAudioWorkletNode;
+
+const RENDER_QUANTUM_SIZE: usize = 128;
+
+/// Instant of the last probe call, shared by all the probes of a profiler
+struct ProfilerClock {
+    origin: Instant,
+    last_frame: AtomicU64,
+    /// nanoseconds since `origin`
+    last_call: AtomicU64,
+}
+
+/// Render time attributed to an observed node, in nanoseconds
+#[derive(Default)]
+struct NodeRenderStats {
+    quanta: AtomicU64,
+    total_time: AtomicU64,
+    max_time: AtomicU64,
+}
+
+impl NodeRenderStats {
+    fn reset(&self) {
+        self.quanta.store(0, Ordering::Relaxed);
+        self.total_time.store(0, Ordering::Relaxed);
+        self.max_time.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Processor connected to the output of an observed node
+///
+/// The render thread processes the nodes in topological order, so the probe
+/// runs after the observed node. The time elapsed since the previous probe
+/// call is attributed to the observed node.
+struct NodeProbe {
+    clock: Arc<ProfilerClock>,
+    stats: Arc<NodeRenderStats>,
+    active: Arc<AtomicBool>,
+    /// Max time measured across two render quanta, in nanoseconds, beyond
+    /// which the render thread is considered to have waited for the device
+    max_gap: u64,
+    /// Resume count of offline contexts, the time spent suspended is ignored
+    resume_count: Option<(Arc<AtomicU64>, u64)>,
+}
+
+impl AudioWorkletProcessor for NodeProbe {
+    type ProcessorOptions = NodeProbe;
+
+    fn constructor(opts: Self::ProcessorOptions) -> Self {
+        opts // the opts contain the full processor
+    }
+
+    fn process<'a, 'b>(
+        &mut self,
+        _inputs: &'b [&'a [&'a [f32]]],
+        _outputs: &'b mut [&'a mut [&'a mut [f32]]],
+        _params: AudioParamValues<'b>,
+        scope: &'b AudioWorkletGlobalScope,
+    ) -> bool {
+        let now = self.clock.origin.elapsed().as_nanos() as u64;
+        let last_frame = self
+            .clock
+            .last_frame
+            .swap(scope.current_frame, Ordering::Relaxed);
+        let last_call = self.clock.last_call.swap(now, Ordering::Relaxed);
+
+        let elapsed = now.saturating_sub(last_call);
+
+        let resumed = match &mut self.resume_count {
+            Some((resume_count, last_resume_count)) => {
+                let count = resume_count.load(Ordering::Relaxed);
+                std::mem::replace(last_resume_count, count) != count
+            }
+            None => false,
+        };
+
+        // the first probe of a quantum measures from the last probe of the
+        // previous quantum, unless the render thread has been idle meanwhile
+        let same_quantum = last_frame == scope.current_frame;
+        let contiguous = last_frame != u64::MAX && elapsed < self.max_gap && !resumed;
+
+        if same_quantum || contiguous {
+            self.stats.quanta.fetch_add(1, Ordering::Relaxed);
+            self.stats.total_time.fetch_add(elapsed, Ordering::Relaxed);
+            self.stats.max_time.fetch_max(elapsed, Ordering::Relaxed);
+        }
+
+        // let the probe be released once unobserved
+        self.active.load(Ordering::Relaxed)
+    }
+}
+
+struct ProbeHandle {
+    stats: Arc<NodeRenderStats>,
+    active: Arc<AtomicBool>,
+}
+
+enum ProfiledContext {
+    Realtime(NapiAudioContext),
+    Offline(NapiOfflineAudioContext),
+}
+
+pub(crate) struct NapiAudioNodeProfiler {
+    context: ProfiledContext,
+    clock: Arc<ProfilerClock>,
+    /// Probes of the observed nodes, removed once released
+    probes: HashMap<u32, ProbeHandle>,
+    next_probe_id: u32,
+}
+
+impl NapiAudioNodeProfiler {
+    pub fn create_js_class(env: &Env) -> Result<JsFunction> {
+        env.define_class(
+            "AudioNodeProfiler",
+            constructor,
+            &[
+                Property::new("createProbe")?.with_method(create_probe),
+                Property::new("releaseProbe")?.with_method(release_probe),
+                Property::new("getStats")?.with_method(get_stats),
+                Property::new("reset")?.with_method(reset),
+            ],
+        )
+    }
+
+    fn probe(&self, id: u32) -> Result<&ProbeHandle> {
+        self.probes
+            .get(&id)
+            .ok_or_else(|| napi::Error::from_reason(format!("RangeError - Unknown probe id {id}")))
+    }
+
+    fn create_probe_node(&self, mut probe: NodeProbe) -> AudioWorkletNode {
+        match &self.context {
+            ProfiledContext::Realtime(napi_context) => {
+                let context = napi_context.unwrap();
+                let quantum_duration = RENDER_QUANTUM_SIZE as f64 / context.sample_rate() as f64;
+                probe.max_gap = (quantum_duration * 1e9) as u64;
+
+                AudioWorkletNode::new::<NodeProbe>(context, probe_options(probe))
+            }
+            ProfiledContext::Offline(napi_context) => {
+                // the offline render loop never waits between two quanta
+                let resume_count = napi_context.resume_count();
+                let count = resume_count.load(Ordering::Relaxed);
+                probe.resume_count = Some((resume_count, count));
+
+                AudioWorkletNode::new::<NodeProbe>(napi_context.unwrap(), probe_options(probe))
+            }
+        }
+    }
+}
+
+fn probe_options(probe: NodeProbe) -> AudioWorkletNodeOptions<NodeProbe> {
+    AudioWorkletNodeOptions {
+        number_of_inputs: 1,
+        number_of_outputs: 0,
+        output_channel_count: vec![],
+        parameter_data: Default::default(),
+        audio_node_options: AudioNodeOptions {
+            channel_count: 1,
+            channel_count_mode: ChannelCountMode::Explicit,
+            channel_interpretation: ChannelInterpretation::Discrete,
+        },
+        processor_options: probe,
+    }
+}
+
+#[js_function(1)]
+fn constructor(ctx: CallContext) -> Result<JsUndefined> {
+    let mut js_this = ctx.this_unchecked::<JsObject>();
+
+    let js_audio_context = ctx.get::<JsObject>(0)?;
+
+    js_this.define_properties(&[
+        // this must be put on the instance and not in the prototype to be reachable
+        Property::new("Symbol.toStringTag")?
+            .with_value(&ctx.env.create_string("AudioNodeProfiler")?)
+            .with_property_attributes(PropertyAttributes::Static),
+    ])?;
+
+    let audio_context_name =
+        js_audio_context.get_named_property::<JsString>("Symbol.toStringTag")?;
+    let audio_context_utf8_name = audio_context_name.into_utf8()?.into_owned()?;
+    let audio_context_str = &audio_context_utf8_name[..];
+
+    let context = match audio_context_str {
+        "AudioContext" => {
+            let napi_audio_context = ctx.env.unwrap::<NapiAudioContext>(&js_audio_context)?;
+            ProfiledContext::Realtime(napi_audio_context.clone())
+        }
+        "OfflineAudioContext" => {
+            let napi_audio_context = ctx
+                .env
+                .unwrap::<NapiOfflineAudioContext>(&js_audio_context)?;
+            ProfiledContext::Offline(napi_audio_context.clone())
+        }
+        &_ => unreachable!(),
+    };
+
+    let clock = ProfilerClock {
+        origin: Instant::now(),
+        last_frame: AtomicU64::new(u64::MAX),
+        last_call: AtomicU64::new(0),
+    };
+
+    let napi_obj = NapiAudioNodeProfiler {
+        context,
+        clock: Arc::new(clock),
+        probes: HashMap::new(),
+        next_probe_id: 0,
+    };
+    ctx.env.wrap(&mut js_this, napi_obj)?;
+
+    ctx.env.get_undefined()
+}
+
+// Create a probe the observed node must be connected to, the connection is
+// done on the JS side which knows the concrete type of the observed node.
+#[js_function]
+fn create_probe(ctx: CallContext) -> Result<JsObject> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioNodeProfiler>(&js_this)?;
+
+    let stats = Arc::new(NodeRenderStats::default());
+    let active = Arc::new(AtomicBool::new(true));
+
+    let probe = NodeProbe {
+        clock: Arc::clone(&napi_obj.clock),
+        stats: Arc::clone(&stats),
+        active: Arc::clone(&active),
+        max_gap: u64::MAX,
+        resume_count: None,
+    };
+
+    let node = napi_obj.create_probe_node(probe);
+    let id = napi_obj.next_probe_id;
+    napi_obj.next_probe_id = id.wrapping_add(1);
+    napi_obj.probes.insert(id, ProbeHandle { stats, active });
+
+    let mut js_probe = NapiAudioWorkletNode::wrap_native(ctx.env, node)?;
+    js_probe.set_named_property("probeId", ctx.env.create_uint32(id)?)?;
+
+    Ok(js_probe)
+}
+
+#[js_function(1)]
+fn release_probe(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioNodeProfiler>(&js_this)?;
+
+    let id = ctx.get::<JsNumber>(0)?.get_uint32()?;
+    // the processor exits on its next render quantum
+    napi_obj.probe(id)?.active.store(false, Ordering::Relaxed);
+    napi_obj.probes.remove(&id);
+
+    ctx.env.get_undefined()
+}
+
+#[js_function(1)]
+fn get_stats(ctx: CallContext) -> Result<JsObject> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioNodeProfiler>(&js_this)?;
+
+    let id = ctx.get::<JsNumber>(0)?.get_uint32()?;
+    let stats = &napi_obj.probe(id)?.stats;
+
+    let quanta = stats.quanta.load(Ordering::Relaxed);
+    let total_time = stats.total_time.load(Ordering::Relaxed) as f64 / 1e9;
+    let max_time = stats.max_time.load(Ordering::Relaxed) as f64 / 1e9;
+
+    let mut js_stats = ctx.env.create_object()?;
+    js_stats.set_named_property("quanta", ctx.env.create_double(quanta as f64)?)?;
+    js_stats.set_named_property("totalTime", ctx.env.create_double(total_time)?)?;
+    js_stats.set_named_property("maxTime", ctx.env.create_double(max_time)?)?;
+
+    Ok(js_stats)
+}
+
+#[js_function]
+fn reset(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioNodeProfiler>(&js_this)?;
+
+    napi_obj
+        .probes
+        .values()
+        .for_each(|probe| probe.stats.reset());
+
+    ctx.env.get_undefined()
+}
@@ -0,0 +1,214 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::*;
+
+use napi::threadsafe_function::{
+    ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
+use napi::*;
+use napi_derive::js_function;
+
+use crate::audio_worklet_node::NapiAudioWorkletNode;
+use crate::sinks::{deinterleave, planar_to_js, Tap, TapPump, RING_BUFFER_BLOCKS};
+
+/// Max number of blocks waiting to be pulled by the JS callback, further
+/// blocks are dropped on an AudioContext and wait on an OfflineAudioContext
+const MAX_PENDING_BLOCKS: usize = 32;
+
+/// Interval between two checks of the pending blocks of an offline tap
+const PENDING_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+const RENDER_QUANTUM_SIZE: usize = 128;
+
+/// Block of planar samples, `None` signals that the tap is closed and that all
+/// the pending blocks have been delivered
+type TapData = Option<Vec<Vec<f32>>>;
+
+pub(crate) struct NapiAudioNodeTap {
+    tap: Tap,
+    buffer_size: usize,
+    /// Offline contexts render faster than realtime, their blocks are never
+    /// dropped but the render thread waits for JS to pull them
+    offline: bool,
+    /// Set once closed, the pending blocks are then flushed without waiting
+    closing: Arc<AtomicBool>,
+    pump: Option<TapPump>,
+    tapdata_tsfn: Option<ThreadsafeFunction<TapData>>,
+}
+
+impl NapiAudioNodeTap {
+    pub fn create_js_class(env: &Env) -> Result<JsFunction> {
+        env.define_class(
+            "AudioNodeTap",
+            constructor,
+            &[
+                Property::new("listen")?.with_method(listen),
+                Property::new("close")?.with_method(close),
+                Property::new("droppedFrames")?.with_getter(get_dropped_frames),
+            ],
+        )
+    }
+}
+
+#[js_function(3)]
+fn constructor(ctx: CallContext) -> Result<JsUndefined> {
+    let mut js_this = ctx.this_unchecked::<JsObject>();
+
+    let js_audio_context = ctx.get::<JsObject>(0)?;
+    let number_of_channels = ctx.get::<JsNumber>(1)?.get_uint32()? as usize;
+    let buffer_size = ctx.get::<JsNumber>(2)?.get_uint32()? as usize;
+
+    js_this.define_properties(&[
+        // this must be put on the instance and not in the prototype to be reachable
+        Property::new("Symbol.toStringTag")?
+            .with_value(&ctx.env.create_string("AudioNodeTap")?)
+            .with_property_attributes(PropertyAttributes::Static),
+    ])?;
+
+    let audio_context_name =
+        js_audio_context.get_named_property::<JsString>("Symbol.toStringTag")?;
+    let audio_context_utf8_name = audio_context_name.into_utf8()?.into_owned()?;
+    let audio_context_str = &audio_context_utf8_name[..];
+
+    // the ring buffer must have room for a render quantum on top of a block
+    let capacity = (buffer_size * RING_BUFFER_BLOCKS).max(buffer_size + RENDER_QUANTUM_SIZE);
+
+    let (mut tap, offline) = match audio_context_str {
+        "AudioContext" => {
+            let napi_audio_context = ctx.env.unwrap::<NapiAudioContext>(&js_audio_context)?;
+            let context = napi_audio_context.unwrap();
+            (Tap::new(context, number_of_channels, capacity), false)
+        }
+        "OfflineAudioContext" => {
+            let napi_audio_context = ctx
+                .env
+                .unwrap::<NapiOfflineAudioContext>(&js_audio_context)?;
+            let context = napi_audio_context.unwrap();
+            (
+                Tap::new_blocking(context, number_of_channels, capacity),
+                true,
+            )
+        }
+        &_ => unreachable!(),
+    };
+
+    // the connection to the tapped node is done on the JS side which knows
+    // the concrete type of the node
+    let js_node = NapiAudioWorkletNode::wrap_native(ctx.env, tap.take_node())?;
+    js_this.set_named_property("node", js_node)?;
+
+    let napi_obj = NapiAudioNodeTap {
+        tap,
+        buffer_size,
+        offline,
+        closing: Arc::new(AtomicBool::new(false)),
+        pump: None,
+        tapdata_tsfn: None,
+    };
+    ctx.env.wrap(&mut js_this, napi_obj)?;
+
+    ctx.env.get_undefined()
+}
+
+// Start delivering the blocks to the JS callback, which must be defined on the
+// instance beforehand
+#[js_function]
+fn listen(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioNodeTap>(&js_this)?;
+
+    let k_ontapdata = crate::utils::get_symbol_for(ctx.env, "node-web-audio-api:ontapdata");
+    let tapdata_cb: JsFunction = js_this.get_property(k_ontapdata)?;
+
+    // the queue is bounded by hand so that the end of stream is never dropped
+    let pending = Arc::new(AtomicUsize::new(0));
+    let pending_clone = Arc::clone(&pending);
+
+    let mut tapdata_tsfn = ctx.env.create_threadsafe_function(
+        &tapdata_cb,
+        0,
+        move |ctx: ThreadSafeCallContext<TapData>| {
+            let value = match ctx.value {
+                Some(channels) => {
+                    pending_clone.fetch_sub(1, Ordering::Relaxed);
+                    planar_to_js(&ctx.env, &channels)?.into_unknown()
+                }
+                None => ctx.env.get_null()?.into_unknown(),
+            };
+
+            Ok(vec![value])
+        },
+    )?;
+
+    // unref tsfn so they do not prevent the process to exit
+    let _ = tapdata_tsfn.unref(ctx.env);
+
+    let tsfn = tapdata_tsfn.clone();
+    let number_of_channels = napi_obj.tap.number_of_channels();
+    let dropped_frames = napi_obj.tap.dropped_frames();
+    let offline = napi_obj.offline;
+    let closing = Arc::clone(&napi_obj.closing);
+
+    let pump = napi_obj.tap.pump(napi_obj.buffer_size, move |samples| {
+        let number_of_frames = samples.len() / number_of_channels;
+
+        // JS is not pulling the blocks fast enough, offline the ring buffer
+        // then fills up and the render thread waits
+        if offline {
+            while pending.load(Ordering::Relaxed) >= MAX_PENDING_BLOCKS
+                && !closing.load(Ordering::Relaxed)
+            {
+                thread::sleep(PENDING_POLL_INTERVAL);
+            }
+        } else if pending.load(Ordering::Relaxed) >= MAX_PENDING_BLOCKS {
+            dropped_frames.fetch_add(number_of_frames as u64, Ordering::Relaxed);
+            return;
+        }
+
+        pending.fetch_add(1, Ordering::Relaxed);
+        let channels = deinterleave(samples, number_of_channels);
+        tsfn.call(Ok(Some(channels)), ThreadsafeFunctionCallMode::NonBlocking);
+    });
+
+    napi_obj.pump = Some(pump);
+    napi_obj.tapdata_tsfn = Some(tapdata_tsfn);
+
+    ctx.env.get_undefined()
+}
+
+// Flush the pending frames and stop the pump, the JS callback then receives
+// `null` once all the blocks have been delivered
+#[js_function]
+fn close(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioNodeTap>(&js_this)?;
+
+    // the JS thread can't pull the pending blocks while the pump is flushed
+    napi_obj.closing.store(true, Ordering::Relaxed);
+
+    if let Some(mut pump) = napi_obj.pump.take() {
+        pump.stop();
+    }
+
+    if let Some(mut tapdata_tsfn) = napi_obj.tapdata_tsfn.take() {
+        // keep the process alive until the end of stream is delivered, the
+        // tsfn is released when dropped
+        tapdata_tsfn.refer(ctx.env)?;
+        tapdata_tsfn.call(Ok(None), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+
+    ctx.env.get_undefined()
+}
+
+#[js_function]
+fn get_dropped_frames(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioNodeTap>(&js_this)?;
+
+    let dropped_frames = napi_obj.tap.dropped_frames().load(Ordering::Relaxed);
+
+    ctx.env.create_double(dropped_frames as f64)
+}
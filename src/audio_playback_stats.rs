@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use crate::*;
+
+use napi::*;
+use napi_derive::js_function;
+use web_audio_api::context::BaseAudioContext;
+
+use crate::sinks::PlayoutStats;
+
+/// Underruns and latencies are `null` when they are not measured, i.e. on an
+/// audio device of the default backend
+pub(crate) struct NapiAudioPlaybackStats(Arc<PlayoutStats>, f32);
+
+impl NapiAudioPlaybackStats {
+    pub fn create_js_class(env: &Env) -> Result<JsFunction> {
+        env.define_class(
+            "AudioPlaybackStats",
+            constructor,
+            &[
+                Property::new("underrunDuration")?.with_getter(get_underrun_duration),
+                Property::new("underrunEvents")?.with_getter(get_underrun_events),
+                Property::new("totalDuration")?.with_getter(get_total_duration),
+                Property::new("averageLatency")?.with_getter(get_average_latency),
+                Property::new("minimumLatency")?.with_getter(get_minimum_latency),
+                Property::new("maximumLatency")?.with_getter(get_maximum_latency),
+                Property::new("resetLatency")?.with_method(reset_latency),
+                // non spec compliant, cumulative counters in frames
+                Property::new("totalFrames")?.with_getter(get_total_frames),
+                Property::new("underrunFrames")?.with_getter(get_underrun_frames),
+            ],
+        )
+    }
+
+    pub fn unwrap(&self) -> &PlayoutStats {
+        &self.0
+    }
+
+    pub fn sample_rate(&self) -> f64 {
+        self.1 as f64
+    }
+}
+
+// https://webaudio.github.io/web-audio-api/#AudioPlaybackStats
+#[js_function(1)]
+fn constructor(ctx: CallContext) -> Result<JsUndefined> {
+    let mut js_this = ctx.this_unchecked::<JsObject>();
+
+    let js_audio_context = ctx.get::<JsObject>(0)?;
+
+    js_this.define_properties(&[
+        // this must be put on the instance and not in the prototype to be reachable
+        Property::new("Symbol.toStringTag")?
+            .with_value(&ctx.env.create_string("AudioPlaybackStats")?)
+            .with_property_attributes(PropertyAttributes::Static),
+    ])?;
+
+    let napi_audio_context = ctx.env.unwrap::<NapiAudioContext>(&js_audio_context)?;
+    let sample_rate = napi_audio_context.unwrap().sample_rate();
+    let playout_stats = napi_audio_context.playout_stats();
+
+    let napi_obj = NapiAudioPlaybackStats(playout_stats, sample_rate);
+    ctx.env.wrap(&mut js_this, napi_obj)?;
+
+    ctx.env.get_undefined()
+}
+
+#[js_function]
+fn get_underrun_duration(ctx: CallContext) -> Result<JsUnknown> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioPlaybackStats>(&js_this)?;
+    let stats = napi_obj.unwrap();
+
+    if !stats.is_available() {
+        return ctx.env.get_null().map(|v| v.into_unknown());
+    }
+
+    ctx.env
+        .create_double(stats.underrun_frames() as f64 / napi_obj.sample_rate())
+        .map(|v| v.into_unknown())
+}
+
+#[js_function]
+fn get_underrun_events(ctx: CallContext) -> Result<JsUnknown> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioPlaybackStats>(&js_this)?;
+    let stats = napi_obj.unwrap();
+
+    if !stats.is_available() {
+        return ctx.env.get_null().map(|v| v.into_unknown());
+    }
+
+    ctx.env
+        .create_double(stats.underrun_events() as f64)
+        .map(|v| v.into_unknown())
+}
+
+#[js_function]
+fn get_total_duration(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioPlaybackStats>(&js_this)?;
+    let stats = napi_obj.unwrap();
+
+    let duration = stats.total_frames() as f64 / napi_obj.sample_rate();
+    ctx.env.create_double(duration)
+}
+
+#[js_function]
+fn get_average_latency(ctx: CallContext) -> Result<JsUnknown> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioPlaybackStats>(&js_this)?;
+    let stats = napi_obj.unwrap();
+
+    if !stats.is_available() {
+        return ctx.env.get_null().map(|v| v.into_unknown());
+    }
+
+    ctx.env
+        .create_double(stats.average_latency())
+        .map(|v| v.into_unknown())
+}
+
+#[js_function]
+fn get_minimum_latency(ctx: CallContext) -> Result<JsUnknown> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioPlaybackStats>(&js_this)?;
+    let stats = napi_obj.unwrap();
+
+    if !stats.is_available() {
+        return ctx.env.get_null().map(|v| v.into_unknown());
+    }
+
+    ctx.env
+        .create_double(stats.minimum_latency())
+        .map(|v| v.into_unknown())
+}
+
+#[js_function]
+fn get_maximum_latency(ctx: CallContext) -> Result<JsUnknown> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioPlaybackStats>(&js_this)?;
+    let stats = napi_obj.unwrap();
+
+    if !stats.is_available() {
+        return ctx.env.get_null().map(|v| v.into_unknown());
+    }
+
+    ctx.env
+        .create_double(stats.maximum_latency())
+        .map(|v| v.into_unknown())
+}
+
+#[js_function]
+fn reset_latency(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioPlaybackStats>(&js_this)?;
+    let stats = napi_obj.unwrap();
+
+    stats.reset_latency();
+
+    ctx.env.get_undefined()
+}
+
+#[js_function]
+fn get_total_frames(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioPlaybackStats>(&js_this)?;
+    let stats = napi_obj.unwrap();
+
+    ctx.env.create_double(stats.total_frames() as f64)
+}
+
+#[js_function]
+fn get_underrun_frames(ctx: CallContext) -> Result<JsUnknown> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioPlaybackStats>(&js_this)?;
+    let stats = napi_obj.unwrap();
+
+    if !stats.is_available() {
+        return ctx.env.get_null().map(|v| v.into_unknown());
+    }
+
+    ctx.env
+        .create_double(stats.underrun_frames() as f64)
+        .map(|v| v.into_unknown())
+}
@@ -5,7 +5,58 @@ use napi::*;
 use napi_derive::js_function;
 use web_audio_api::{AudioRenderCapacity, AudioRenderCapacityEvent, AudioRenderCapacityOptions};
 
-pub(crate) struct NapiAudioRenderCapacity(AudioRenderCapacity);
+use crate::offline_render_capacity::OfflineRenderCapacity;
+
+/// Payload of the `update` event, common to realtime and offline contexts
+pub(crate) struct RenderCapacityUpdate {
+    pub timestamp: f64,
+    pub average_load: f64,
+    pub peak_load: f64,
+    pub underrun_ratio: f64,
+}
+
+impl From<AudioRenderCapacityEvent> for RenderCapacityUpdate {
+    fn from(event: AudioRenderCapacityEvent) -> Self {
+        Self {
+            timestamp: event.timestamp,
+            average_load: event.average_load,
+            peak_load: event.peak_load,
+            underrun_ratio: event.underrun_ratio,
+        }
+    }
+}
+
+pub(crate) enum RenderCapacity {
+    Realtime(AudioRenderCapacity),
+    Offline(OfflineRenderCapacity),
+}
+
+impl RenderCapacity {
+    fn start(&mut self, options: AudioRenderCapacityOptions) {
+        match self {
+            RenderCapacity::Realtime(capacity) => capacity.start(options),
+            RenderCapacity::Offline(capacity) => capacity.start(options),
+        }
+    }
+
+    fn stop(&mut self) {
+        match self {
+            RenderCapacity::Realtime(capacity) => capacity.stop(),
+            RenderCapacity::Offline(capacity) => capacity.stop(),
+        }
+    }
+
+    fn set_onupdate<F: FnMut(RenderCapacityUpdate) + Send + 'static>(&self, mut callback: F) {
+        match self {
+            RenderCapacity::Realtime(capacity) => {
+                capacity.set_onupdate(move |event| callback(event.into()))
+            }
+            RenderCapacity::Offline(capacity) => capacity.set_onupdate(callback),
+        }
+    }
+}
+
+pub(crate) struct NapiAudioRenderCapacity(RenderCapacity);
 
 impl NapiAudioRenderCapacity {
     pub fn create_js_class(env: &Env) -> Result<JsFunction> {
@@ -23,7 +74,7 @@ impl NapiAudioRenderCapacity {
         )
     }
 
-    pub fn unwrap(&mut self) -> &mut AudioRenderCapacity {
+    pub fn unwrap(&mut self) -> &mut RenderCapacity {
         &mut self.0
     }
 }
@@ -52,7 +103,13 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
         "AudioContext" => {
             let napi_audio_context = ctx.env.unwrap::<NapiAudioContext>(&js_audio_context)?;
             let audio_context = napi_audio_context.unwrap();
-            audio_context.render_capacity()
+            RenderCapacity::Realtime(audio_context.render_capacity())
+        }
+        "OfflineAudioContext" => {
+            let napi_audio_context = ctx
+                .env
+                .unwrap::<NapiOfflineAudioContext>(&js_audio_context)?;
+            RenderCapacity::Offline(OfflineRenderCapacity::new(napi_audio_context.clone()))
         }
         &_ => unreachable!(),
     };
@@ -75,6 +132,11 @@ fn start(ctx: CallContext) -> Result<JsUndefined> {
         .get_named_property::<JsNumber>("updateInterval")?
         .get_double()?;
 
+    // the handler is released by `stop` or once the rendering is finished
+    if let RenderCapacity::Offline(_) = node {
+        bind_onupdate(ctx.env, &js_this, node)?;
+    }
+
     node.start(AudioRenderCapacityOptions { update_interval });
 
     ctx.env.get_undefined()
@@ -97,12 +159,22 @@ fn listen_to_events(ctx: CallContext) -> Result<JsUndefined> {
     let napi_node = ctx.env.unwrap::<NapiAudioRenderCapacity>(&js_this)?;
     let node = napi_node.unwrap();
 
-    let k_onupdate = crate::utils::get_symbol_for(ctx.env, "node-web-audio-api:onupdate");
+    // The offline render capacity binds its handler in `start`, so that the
+    // context can be garbage collected if measurements are never started.
+    if let RenderCapacity::Realtime(_) = node {
+        bind_onupdate(ctx.env, &js_this, node)?;
+    }
+
+    ctx.env.get_undefined()
+}
+
+fn bind_onupdate(env: &Env, js_this: &JsObject, node: &RenderCapacity) -> Result<()> {
+    let k_onupdate = crate::utils::get_symbol_for(env, "node-web-audio-api:onupdate");
     let update_cb = js_this.get_property(k_onupdate).unwrap();
-    let mut update_tsfn = ctx.env.create_threadsafe_function(
+    let mut update_tsfn = env.create_threadsafe_function(
         &update_cb,
         0,
-        |ctx: ThreadSafeCallContext<AudioRenderCapacityEvent>| {
+        |ctx: ThreadSafeCallContext<RenderCapacityUpdate>| {
             let event = ctx.value;
             let mut js_event = ctx.env.create_object()?;
 
@@ -118,11 +190,11 @@ fn listen_to_events(ctx: CallContext) -> Result<JsUndefined> {
         },
     )?;
 
-    let _ = update_tsfn.unref(ctx.env);
+    let _ = update_tsfn.unref(env);
 
     node.set_onupdate(move |e| {
         update_tsfn.call(Ok(e), ThreadsafeFunctionCallMode::Blocking);
     });
 
-    ctx.env.get_undefined()
+    Ok(())
 }
@@ -0,0 +1,368 @@
+use std::mem::size_of;
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use napi::*;
+use napi_derive::js_function;
+
+/// Identifies a SharedArrayBuffer laid out as an `AudioRingBuffer`, i.e. "NWRB"
+const MAGIC: u32 = 0x4e57_5242;
+
+/// Header at the start of the SharedArrayBuffer, followed by the planar
+/// samples of each channel
+///
+/// The indices count the frames written and read since the creation of the
+/// buffer, each one being only stored by one side. They live on separate cache
+/// lines, together with the counter owned by the same side, so that the
+/// producer and the consumer do not contend.
+#[repr(C)]
+struct RingBufferHeader {
+    magic: u32,
+    number_of_channels: u32,
+    length: u32,
+    _pad0: [u8; 52],
+    // producer side
+    write_index: AtomicU64,
+    overrun_frames: AtomicU64,
+    _pad1: [u8; 48],
+    // consumer side
+    read_index: AtomicU64,
+    underrun_frames: AtomicU64,
+    _pad2: [u8; 48],
+}
+
+const HEADER_BYTE_LENGTH: usize = size_of::<RingBufferHeader>();
+
+/// Wait-free single producer single consumer queue of audio frames, mapped on
+/// a SharedArrayBuffer so that both sides can live in different threads, e.g.
+/// the main thread and an AudioWorkletGlobalScope
+///
+/// Each side creates its own instance over the same memory, one calling only
+/// `push` and the other only `pull`.
+pub(crate) struct NapiAudioRingBuffer {
+    // pointer to the start of the SharedArrayBuffer, which is kept alive by
+    // the JS object and is never detached nor moved
+    ptr: *mut u8,
+    number_of_channels: usize,
+    length: usize,
+}
+
+impl NapiAudioRingBuffer {
+    pub fn create_js_class(env: &Env) -> Result<JsFunction> {
+        env.define_class(
+            "AudioRingBuffer",
+            constructor,
+            &[
+                Property::new("HEADER_BYTE_LENGTH")?
+                    .with_value(&env.create_uint32(HEADER_BYTE_LENGTH as u32)?)
+                    .with_property_attributes(PropertyAttributes::Static),
+                Property::new("numberOfChannels")?.with_getter(get_number_of_channels),
+                Property::new("length")?.with_getter(get_length),
+                Property::new("push")?.with_method(push),
+                Property::new("pull")?.with_method(pull),
+                Property::new("availableRead")?.with_getter(get_available_read),
+                Property::new("availableWrite")?.with_getter(get_available_write),
+                Property::new("underrunFrames")?.with_getter(get_underrun_frames),
+                Property::new("overrunFrames")?.with_getter(get_overrun_frames),
+            ],
+        )
+    }
+
+    fn header(&self) -> &RingBufferHeader {
+        // alignment and size have been checked in the constructor
+        unsafe { &*(self.ptr as *const RingBufferHeader) }
+    }
+
+    fn channel_ptr(&self, channel: usize) -> *mut f32 {
+        unsafe {
+            self.ptr
+                .add(HEADER_BYTE_LENGTH)
+                .cast::<f32>()
+                .add(channel * self.length)
+        }
+    }
+
+    /// Number of frames between the indices. The header can be corrupted by
+    /// JS code writing in the SharedArrayBuffer, inconsistent indices are
+    /// therefore clamped so that the buffer looks full, and no copy can go out
+    /// of its bounds.
+    fn fill_level(&self, read: u64, write: u64) -> u64 {
+        write.wrapping_sub(read).min(self.length as u64)
+    }
+
+    fn available_read(&self) -> u64 {
+        let header = self.header();
+        let read = header.read_index.load(Ordering::Acquire);
+        let write = header.write_index.load(Ordering::Acquire);
+        self.fill_level(read, write)
+    }
+
+    /// Copy the frames into the ring buffer, the channels that are not given
+    /// are filled with zeros, returns the number of frames written
+    fn push(&self, channels: &[&[f32]], frames: usize) -> usize {
+        let header = self.header();
+        let length = self.length as u64;
+        // only stored by the producer, i.e. by us
+        let write = header.write_index.load(Ordering::Relaxed);
+        // synchronizes with the consumer having read the frames it releases
+        let read = header.read_index.load(Ordering::Acquire);
+
+        let available = (length - self.fill_level(read, write)) as usize;
+        let count = frames.min(available);
+        let start = (write % length) as usize;
+        // the frames may wrap around the end of the buffer
+        let first = count.min(self.length - start);
+
+        for channel in 0..self.number_of_channels {
+            let dst = self.channel_ptr(channel);
+
+            unsafe {
+                match channels.get(channel) {
+                    Some(src) => {
+                        ptr::copy_nonoverlapping(src.as_ptr(), dst.add(start), first);
+                        ptr::copy_nonoverlapping(src.as_ptr().add(first), dst, count - first);
+                    }
+                    None => {
+                        ptr::write_bytes(dst.add(start), 0, first);
+                        ptr::write_bytes(dst, 0, count - first);
+                    }
+                }
+            }
+        }
+
+        // publish the frames to the consumer
+        header
+            .write_index
+            .store(write.wrapping_add(count as u64), Ordering::Release);
+
+        if count < frames {
+            header
+                .overrun_frames
+                .fetch_add((frames - count) as u64, Ordering::Relaxed);
+        }
+
+        count
+    }
+
+    /// Copy the frames out of the ring buffer, the frames that are not
+    /// available are filled with zeros, returns the number of frames read
+    fn pull(&self, channels: &mut [&mut [f32]], frames: usize) -> usize {
+        let header = self.header();
+        let length = self.length as u64;
+        // only stored by the consumer, i.e. by us
+        let read = header.read_index.load(Ordering::Relaxed);
+        // synchronizes with the producer having written the frames it publishes
+        let write = header.write_index.load(Ordering::Acquire);
+
+        let available = self.fill_level(read, write) as usize;
+        let count = frames.min(available);
+        let start = (read % length) as usize;
+        let first = count.min(self.length - start);
+
+        for (channel, dst) in channels.iter_mut().enumerate() {
+            if channel < self.number_of_channels {
+                let src = self.channel_ptr(channel);
+
+                unsafe {
+                    ptr::copy_nonoverlapping(src.add(start), dst.as_mut_ptr(), first);
+                    ptr::copy_nonoverlapping(src, dst.as_mut_ptr().add(first), count - first);
+                }
+
+                dst[count..frames].fill(0.);
+            } else {
+                dst[..frames].fill(0.);
+            }
+        }
+
+        // give the frames back to the producer
+        header
+            .read_index
+            .store(read.wrapping_add(count as u64), Ordering::Release);
+
+        if count < frames {
+            header
+                .underrun_frames
+                .fetch_add((frames - count) as u64, Ordering::Relaxed);
+        }
+
+        count
+    }
+}
+
+// Map the ring buffer on the memory of `view`, a Uint8Array covering a whole
+// SharedArrayBuffer. If `numberOfChannels` and `length` are given, the header
+// is initialized, otherwise the buffer must have been initialized by another
+// instance.
+#[js_function(3)]
+fn constructor(ctx: CallContext) -> Result<JsUndefined> {
+    let mut js_this = ctx.this_unchecked::<JsObject>();
+
+    js_this.define_properties(&[
+        // this must be put on the instance and not in the prototype to be reachable
+        Property::new("Symbol.toStringTag")?
+            .with_value(&ctx.env.create_string("AudioRingBuffer")?)
+            .with_property_attributes(PropertyAttributes::Static),
+    ])?;
+
+    let mut js_view = ctx.get::<JsTypedArray>(0)?.into_value()?;
+    let byte_length = js_view.length;
+
+    if byte_length < HEADER_BYTE_LENGTH {
+        return Err(napi::Error::from_reason(
+            "TypeError - The SharedArrayBuffer is too small to hold an AudioRingBuffer".to_string(),
+        ));
+    }
+
+    let view: &mut [u8] = js_view.as_mut();
+    let ptr = view.as_mut_ptr();
+
+    if !ptr.cast::<RingBufferHeader>().is_aligned() {
+        return Err(napi::Error::from_reason(
+            "TypeError - The SharedArrayBuffer is not aligned on 8 bytes".to_string(),
+        ));
+    }
+
+    if ctx.length == 3 {
+        let number_of_channels = ctx.get::<JsNumber>(1)?.get_uint32()?;
+        let length = ctx.get::<JsNumber>(2)?.get_uint32()?;
+        // the buffer has just been allocated and is not shared yet
+        let header = unsafe { &mut *(ptr as *mut RingBufferHeader) };
+        header.number_of_channels = number_of_channels;
+        header.length = length;
+        header.magic = MAGIC;
+    }
+
+    let header = unsafe { &*(ptr as *const RingBufferHeader) };
+
+    if header.magic != MAGIC || header.length == 0 {
+        return Err(napi::Error::from_reason(
+            "TypeError - The SharedArrayBuffer has not been created by an AudioRingBuffer"
+                .to_string(),
+        ));
+    }
+
+    let number_of_channels = header.number_of_channels as usize;
+    let length = header.length as usize;
+
+    if byte_length < HEADER_BYTE_LENGTH + number_of_channels * length * size_of::<f32>() {
+        return Err(napi::Error::from_reason(
+            "TypeError - The SharedArrayBuffer is too small for the AudioRingBuffer it holds"
+                .to_string(),
+        ));
+    }
+
+    // keep the memory alive as long as the ring buffer
+    js_this.set_named_property("buffer", js_view.arraybuffer)?;
+
+    let napi_obj = NapiAudioRingBuffer {
+        ptr,
+        number_of_channels,
+        length,
+    };
+    ctx.env.wrap(&mut js_this, napi_obj)?;
+
+    ctx.env.get_undefined()
+}
+
+// Collect the Float32Array given as channels, and the number of frames which is
+// the length of the shortest one. Empty arrays are skipped as their data
+// pointer may be null.
+fn get_channels(js_channels: &JsObject) -> Result<(Vec<JsTypedArrayValue>, usize)> {
+    let number_of_channels = js_channels.get_array_length()?;
+    let mut channels = Vec::with_capacity(number_of_channels as usize);
+    let mut frames = usize::MAX;
+
+    for i in 0..number_of_channels {
+        let channel = js_channels.get_element::<JsTypedArray>(i)?.into_value()?;
+        frames = frames.min(channel.length);
+        channels.push(channel);
+    }
+
+    if channels.is_empty() || frames == 0 {
+        return Ok((vec![], 0));
+    }
+
+    Ok((channels, frames))
+}
+
+#[js_function(1)]
+fn push(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioRingBuffer>(&js_this)?;
+
+    let js_channels = ctx.get::<JsObject>(0)?;
+    let (js_channels, frames) = get_channels(&js_channels)?;
+    let channels: Vec<&[f32]> = js_channels.iter().map(|c| c.as_ref()).collect();
+
+    let count = napi_obj.push(&channels, frames);
+
+    ctx.env.create_uint32(count as u32)
+}
+
+#[js_function(1)]
+fn pull(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioRingBuffer>(&js_this)?;
+
+    let js_channels = ctx.get::<JsObject>(0)?;
+    let (mut js_channels, frames) = get_channels(&js_channels)?;
+    let mut channels: Vec<&mut [f32]> = js_channels.iter_mut().map(|c| c.as_mut()).collect();
+
+    let count = napi_obj.pull(&mut channels, frames);
+
+    ctx.env.create_uint32(count as u32)
+}
+
+#[js_function]
+fn get_number_of_channels(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioRingBuffer>(&js_this)?;
+
+    ctx.env.create_uint32(napi_obj.number_of_channels as u32)
+}
+
+#[js_function]
+fn get_length(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioRingBuffer>(&js_this)?;
+
+    ctx.env.create_uint32(napi_obj.length as u32)
+}
+
+#[js_function]
+fn get_available_read(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioRingBuffer>(&js_this)?;
+
+    ctx.env.create_uint32(napi_obj.available_read() as u32)
+}
+
+#[js_function]
+fn get_available_write(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioRingBuffer>(&js_this)?;
+
+    let available_write = napi_obj.length as u64 - napi_obj.available_read();
+
+    ctx.env.create_uint32(available_write as u32)
+}
+
+#[js_function]
+fn get_underrun_frames(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioRingBuffer>(&js_this)?;
+
+    let underrun_frames = napi_obj.header().underrun_frames.load(Ordering::Relaxed);
+
+    ctx.env.create_double(underrun_frames as f64)
+}
+
+#[js_function]
+fn get_overrun_frames(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiAudioRingBuffer>(&js_this)?;
+
+    let overrun_frames = napi_obj.header().overrun_frames.load(Ordering::Relaxed);
+
+    ctx.env.create_double(overrun_frames as f64)
+}
@@ -1,8 +1,13 @@
+use crate::audio_worklet_stats::ProcessorStats;
+use crate::audio_worklet_wasm::WasmAudioWorkletProcessor;
+use crate::thread_scheduling::ThreadScheduling;
 use crate::{NapiAudioContext, NapiAudioParam, NapiOfflineAudioContext};
 
 use crossbeam_channel::{self, Receiver, Sender};
 
-use napi::bindgen_prelude::Array;
+use napi::threadsafe_function::{
+    ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
 use napi::*;
 use napi_derive::js_function;
 
@@ -13,11 +18,18 @@ use web_audio_api::worklet::{
 };
 use web_audio_api::{AudioParamDescriptor, AutomationRate};
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::option::Option;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+const RENDER_QUANTUM_SIZE: usize = 128;
+
+/// Implementation max number of channels, the I/O buffers shared with the
+/// Worker are allocated upfront for this number of channels
+const MAX_CHANNELS: usize = 32;
 
 /// Unique ID generator for AudioWorkletProcessors
 static INCREMENTING_ID: AtomicU32 = AtomicU32::new(0);
@@ -32,18 +44,281 @@ enum WorkletCommand {
 struct ProcessorArguments {
     // processor unique ID
     id: u32,
-    // processor inputs (unsafely cast to static)
-    inputs: &'static [&'static [&'static [f32]]],
-    // processor ouputs (unsafely cast to static)
-    outputs: &'static [&'static [&'static [f32]]],
-    // processor audio params (unsafely cast to static)
-    param_values: &'static [(&'static str, &'static [f32])],
+    // memory shared with the Worker
+    arena: Arc<Mutex<ProcessorArena>>,
     // AudioWorkletGlobalScope currentTime
     current_time: f64,
     // AudioWorkletGlobalScope currentFrame
     current_frame: u64,
     // channel for tail_time return value
     tail_time_sender: Sender<bool>,
+    // instant at which the render thread issued the call
+    sent_at: Instant,
+    // durations of the calls, recorded by the Worker
+    stats: Arc<ProcessorStats>,
+}
+
+/// Memory of a ProcessorArena, allocated once so that the views of the Worker
+/// remain valid for the lifetime of the processor
+struct ArenaMemory {
+    ptr: *mut f32,
+    len: usize,
+}
+
+// The memory is only accessed by the thread holding the lock of the arena
+unsafe impl Send for ArenaMemory {}
+
+impl ArenaMemory {
+    fn new(len: usize) -> Self {
+        let memory = vec![0.; len].into_boxed_slice();
+        let ptr = Box::into_raw(memory) as *mut f32;
+
+        Self { ptr, len }
+    }
+
+    fn as_slice(&self) -> &[f32] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [f32] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl Drop for ArenaMemory {
+    fn drop(&mut self) {
+        let memory = std::ptr::slice_from_raw_parts_mut(self.ptr, self.len);
+        drop(unsafe { Box::from_raw(memory) });
+    }
+}
+
+/// Inputs, outputs and audio params of a processor
+///
+/// The render thread and the Worker exchange the render quanta through this
+/// memory rather than through the render thread buffers, so that a `process`
+/// call that overruns its deadline cannot touch memory that the render thread
+/// has moved on from. The Worker maps it once as an ArrayBuffer, and hands
+/// `Float32Array` views of it to `process`, which are rebuilt only when the
+/// layout changes. The Worker holds the lock for the whole call.
+///
+/// Each input and output is given room for `MAX_CHANNELS` channels, followed
+/// by one render quantum for each param.
+struct ProcessorArena {
+    memory: ArenaMemory,
+    // number of channels of each input
+    input_channels: Vec<usize>,
+    // number of channels of each output
+    output_channels: Vec<usize>,
+    // in the order of the parameter descriptors
+    param_names: Vec<String>,
+    // number of values of each param, i.e. 1 or 128
+    param_lengths: Vec<usize>,
+    // incremented each time the number of channels or values changes
+    layout_versions: LayoutVersions,
+    // render quanta queued by the render thread, cf. `batchQuanta`
+    batch: Option<ArenaBatch>,
+}
+
+/// Versions of the layout of the inputs, outputs and params of an arena, so
+/// that only the views whose layout changed are rebuilt
+#[derive(Clone, Copy, Default, PartialEq)]
+struct LayoutVersions {
+    inputs: u64,
+    outputs: u64,
+    params: u64,
+}
+
+/// Render quanta processed in a single round-trip with the Worker
+///
+/// The render thread queues a copy of the arena for each render quantum of the
+/// batch, the Worker then loads the queued render quanta one after the other
+/// in the arena to call `process` on each of them, and stores back their
+/// outputs.
+struct ArenaBatch {
+    quanta: Vec<BatchedQuantum>,
+    // number of queued render quanta
+    len: usize,
+}
+
+/// Copy of the memory and of the layout of an arena
+struct BatchedQuantum {
+    memory: Vec<f32>,
+    input_channels: Vec<usize>,
+    output_channels: Vec<usize>,
+    param_lengths: Vec<usize>,
+    current_time: f64,
+    current_frame: u64,
+}
+
+impl ProcessorArena {
+    fn new(
+        number_of_inputs: usize,
+        number_of_outputs: usize,
+        param_names: Vec<String>,
+        batch_quanta: usize,
+    ) -> Self {
+        let len = ((number_of_inputs + number_of_outputs) * MAX_CHANNELS + param_names.len())
+            * RENDER_QUANTUM_SIZE;
+
+        // allocated upfront as the render thread must not allocate
+        let batch = (batch_quanta > 1).then(|| ArenaBatch {
+            quanta: (0..batch_quanta)
+                .map(|_| BatchedQuantum {
+                    memory: vec![0.; len],
+                    input_channels: vec![0; number_of_inputs],
+                    output_channels: vec![0; number_of_outputs],
+                    param_lengths: vec![0; param_names.len()],
+                    current_time: 0.,
+                    current_frame: 0,
+                })
+                .collect(),
+            len: 0,
+        });
+
+        Self {
+            memory: ArenaMemory::new(len),
+            input_channels: vec![0; number_of_inputs],
+            output_channels: vec![0; number_of_outputs],
+            param_lengths: vec![0; param_names.len()],
+            param_names,
+            layout_versions: LayoutVersions::default(),
+            batch,
+        }
+    }
+
+    fn input_offset(&self, input: usize, channel: usize) -> usize {
+        (input * MAX_CHANNELS + channel) * RENDER_QUANTUM_SIZE
+    }
+
+    fn output_offset(&self, output: usize, channel: usize) -> usize {
+        ((self.input_channels.len() + output) * MAX_CHANNELS + channel) * RENDER_QUANTUM_SIZE
+    }
+
+    fn param_offset(&self, param: usize) -> usize {
+        let buses = self.input_channels.len() + self.output_channels.len();
+        (buses * MAX_CHANNELS + param) * RENDER_QUANTUM_SIZE
+    }
+
+    /// Copy the inputs and params of the render quantum, and match the layout
+    /// of the outputs, without allocating
+    fn write(
+        &mut self,
+        inputs: &[&[&[f32]]],
+        outputs: &[&mut [&mut [f32]]],
+        params: &AudioParamValues<'_>,
+    ) {
+        let mut inputs_changed = false;
+        let mut outputs_changed = false;
+        let mut params_changed = false;
+
+        for (input_number, input) in inputs.iter().enumerate() {
+            let number_of_channels = input.len().min(MAX_CHANNELS);
+            let previous =
+                std::mem::replace(&mut self.input_channels[input_number], number_of_channels);
+            inputs_changed |= previous != number_of_channels;
+
+            for (channel_number, channel) in input.iter().take(number_of_channels).enumerate() {
+                let offset = self.input_offset(input_number, channel_number);
+                self.memory.as_mut_slice()[offset..offset + RENDER_QUANTUM_SIZE]
+                    .copy_from_slice(channel);
+            }
+        }
+
+        for (output_number, output) in outputs.iter().enumerate() {
+            let number_of_channels = output.len().min(MAX_CHANNELS);
+            let previous =
+                std::mem::replace(&mut self.output_channels[output_number], number_of_channels);
+            outputs_changed |= previous != number_of_channels;
+        }
+
+        for param_number in 0..self.param_names.len() {
+            let values = params.get(&self.param_names[param_number]);
+            let previous = std::mem::replace(&mut self.param_lengths[param_number], values.len());
+            params_changed |= previous != values.len();
+
+            let offset = self.param_offset(param_number);
+            self.memory.as_mut_slice()[offset..offset + values.len()].copy_from_slice(&values);
+        }
+
+        self.layout_versions.inputs += u64::from(inputs_changed);
+        self.layout_versions.outputs += u64::from(outputs_changed);
+        self.layout_versions.params += u64::from(params_changed);
+    }
+
+    /// Copy the outputs of the render quantum
+    fn read(&self, outputs: &mut [&mut [&mut [f32]]]) {
+        self.read_from(self.memory.as_slice(), outputs);
+    }
+
+    fn read_from(&self, memory: &[f32], outputs: &mut [&mut [&mut [f32]]]) {
+        for (output_number, output) in outputs.iter_mut().enumerate() {
+            for (channel_number, channel) in output.iter_mut().enumerate() {
+                let offset = self.output_offset(output_number, channel_number);
+                channel.copy_from_slice(&memory[offset..offset + RENDER_QUANTUM_SIZE]);
+            }
+        }
+    }
+
+    /// Queue a copy of the render quantum written in the arena
+    fn queue_quantum(&mut self, current_time: f64, current_frame: u64) {
+        let batch = self.batch.as_mut().unwrap();
+        let quantum = &mut batch.quanta[batch.len];
+
+        quantum.memory.copy_from_slice(self.memory.as_slice());
+        quantum.input_channels.copy_from_slice(&self.input_channels);
+        quantum
+            .output_channels
+            .copy_from_slice(&self.output_channels);
+        quantum.param_lengths.copy_from_slice(&self.param_lengths);
+        quantum.current_time = current_time;
+        quantum.current_frame = current_frame;
+
+        batch.len += 1;
+    }
+
+    /// Number of queued render quanta
+    fn queued_quanta(&self) -> usize {
+        self.batch.as_ref().map_or(0, |batch| batch.len)
+    }
+
+    /// Copy the outputs of a render quantum of the last processed batch
+    fn read_batched(&self, index: usize, outputs: &mut [&mut [&mut [f32]]]) {
+        let batch = self.batch.as_ref().unwrap();
+        self.read_from(&batch.quanta[index].memory, outputs);
+    }
+
+    /// Load the inputs, params and layout of a queued render quantum in the
+    /// arena, the outputs are left untouched as for a regular call
+    fn load_quantum(&mut self, quantum: &BatchedQuantum) {
+        let outputs_start = self.output_offset(0, 0);
+        let params_start = self.param_offset(0);
+        let memory = self.memory.as_mut_slice();
+
+        memory[..outputs_start].copy_from_slice(&quantum.memory[..outputs_start]);
+        memory[params_start..].copy_from_slice(&quantum.memory[params_start..]);
+
+        if self.input_channels != quantum.input_channels {
+            self.input_channels.copy_from_slice(&quantum.input_channels);
+            self.layout_versions.inputs += 1;
+        }
+
+        if self.output_channels != quantum.output_channels {
+            self.output_channels
+                .copy_from_slice(&quantum.output_channels);
+            self.layout_versions.outputs += 1;
+        }
+
+        if self.param_lengths != quantum.param_lengths {
+            self.param_lengths.copy_from_slice(&quantum.param_lengths);
+            self.layout_versions.params += 1;
+        }
+    }
+
+    /// Store the outputs computed in the arena back in a queued render quantum
+    fn store_outputs(&self, quantum: &mut BatchedQuantum) {
+        let outputs = self.output_offset(0, 0)..self.param_offset(0);
+        quantum.memory[outputs.clone()].copy_from_slice(&self.memory.as_slice()[outputs]);
+    }
 }
 
 /// Message channel from render thread to Worker
@@ -52,17 +327,114 @@ struct ProcessCallChannel {
     recv: Receiver<WorkletCommand>,
     // mark that the worklet has been exited to prevent any further `process` call
     exited: Arc<AtomicBool>,
+    // scheduling requested for the Worker thread, if any
+    scheduling: Option<Arc<ThreadScheduling>>,
+}
+
+/// Slot of the global slab of ProcessCallChannel
+struct ProcessCallSlot {
+    // incremented each time the slot is released, so that the ID of a closed
+    // context never resolves to the channel of a newer one
+    generation: u32,
+    channel: Option<ProcessCallChannel>,
 }
 
+/// Generational slab of ProcessCallChannel, the slots of closed contexts are
+/// reused by the new ones
+struct ProcessCallChannels {
+    slots: Vec<ProcessCallSlot>,
+    free: Vec<usize>,
+}
+
+/// Generations are wrapped so that IDs are safe integers in JS
+const MAX_GENERATION: u32 = (1 << 21) - 1;
+
 /// Global map of ID -> ProcessCallChannel
 ///
 /// Every (Offline)AudioContext is assigned a new channel + ID. The ID is passed to the
 /// AudioWorklet Worker and to every AudioNode in the context so they can grab the channel and use
-/// message passing.
-static GLOBAL_PROCESS_CALL_CHANNEL_MAP: RwLock<Vec<ProcessCallChannel>> = RwLock::new(vec![]);
+/// message passing. The ID is only valid for the lifetime of the context, i.e. until
+/// it is closed or garbage collected.
+static GLOBAL_PROCESS_CALL_CHANNEL_MAP: RwLock<ProcessCallChannels> =
+    RwLock::new(ProcessCallChannels {
+        slots: vec![],
+        free: vec![],
+    });
+
+/// Pack the slot index and generation of a channel into its ID
+fn pack_worklet_id(index: usize, generation: u32) -> u64 {
+    (u64::from(generation) << 32) | index as u64
+}
+
+/// Unpack the slot index and generation of a channel from its ID
+fn unpack_worklet_id(id: u64) -> (usize, u32) {
+    ((id & u64::from(u32::MAX)) as usize, (id >> 32) as u32)
+}
+
+/// Handle on the channel of a context, the channel is released when the last
+/// handle is dropped, if it has not been released on close
+pub(crate) struct ProcessCallChannelGuard(u64);
+
+impl ProcessCallChannelGuard {
+    /// ID of the channel, to be passed to the AudioWorklet Worker
+    pub fn worklet_id(&self) -> u64 {
+        self.0
+    }
+
+    /// Release the channel, further `process` calls are cancelled
+    pub fn release(&self) {
+        release_process_call_channel(self.0);
+    }
+}
+
+impl Drop for ProcessCallChannelGuard {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Time the render thread may wait for the Workers during a render quantum,
+/// shared by the processors of an `AudioContext` without `processDeadline`, so
+/// that N blocking processors do not delay the render quantum by N deadlines
+pub(crate) struct ProcessBudget {
+    /// Duration of a render quantum
+    quantum: Duration,
+    /// Render quantum being waited for, and the instant the budget runs out
+    current: Mutex<Option<(u64, Instant)>>,
+}
+
+impl ProcessBudget {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            quantum: Duration::from_secs_f64(RENDER_QUANTUM_SIZE as f64 / sample_rate as f64),
+            current: Mutex::new(None),
+        }
+    }
+
+    /// Remaining wait for the render quantum starting at `current_frame`, the
+    /// budget starts with the first wait of the render quantum
+    fn remaining(&self, current_frame: u64) -> Duration {
+        // never contended, only the render thread waits for the Workers
+        let mut current = self.current.lock().unwrap();
+        let now = Instant::now();
+
+        let end = match *current {
+            Some((frame, end)) if frame == current_frame => end,
+            _ => {
+                let end = now + self.quantum;
+                *current = Some((current_frame, end));
+                end
+            }
+        };
+
+        end.saturating_duration_since(now)
+    }
+}
 
 /// Request a new channel + ID for a newly created (Offline)AudioContext
-pub(crate) fn allocate_process_call_channel() -> usize {
+pub(crate) fn allocate_process_call_channel(
+    scheduling: Option<Arc<ThreadScheduling>>,
+) -> ProcessCallChannelGuard {
     // Only one process message can be sent at same time from a given context,
     // but Drop messages could be send too, so let's take some room
     let (send, recv) = crossbeam_channel::bounded(32);
@@ -70,52 +442,115 @@ pub(crate) fn allocate_process_call_channel() -> usize {
         send,
         recv,
         exited: Arc::new(AtomicBool::new(false)),
+        scheduling,
     };
 
     // We need a write-lock to initialize the channel
     let mut write_lock = GLOBAL_PROCESS_CALL_CHANNEL_MAP.write().unwrap();
-    let id = write_lock.len();
-    write_lock.push(channel);
 
-    id
+    let index = match write_lock.free.pop() {
+        Some(index) => index,
+        None => {
+            write_lock.slots.push(ProcessCallSlot {
+                generation: 0,
+                channel: None,
+            });
+            write_lock.slots.len() - 1
+        }
+    };
+
+    let slot = &mut write_lock.slots[index];
+    slot.channel = Some(channel);
+
+    ProcessCallChannelGuard(pack_worklet_id(index, slot.generation))
+}
+
+/// Release the channel of a closed or garbage collected context, a no-op if
+/// the channel has already been released
+fn release_process_call_channel(id: u64) {
+    let (index, generation) = unpack_worklet_id(id);
+
+    let mut write_lock = GLOBAL_PROCESS_CALL_CHANNEL_MAP.write().unwrap();
+
+    let channel = match write_lock.slots.get_mut(index) {
+        Some(slot) if slot.generation == generation => {
+            slot.generation = (slot.generation + 1) & MAX_GENERATION;
+            slot.channel.take()
+        }
+        _ => None,
+    };
+
+    let Some(channel) = channel else {
+        return;
+    };
+
+    write_lock.free.push(index);
+    drop(write_lock);
+
+    // Prevent any other render call and handle the pending ones, as the
+    // Worker won't see them anymore
+    channel.exited.store(true, Ordering::SeqCst);
+
+    while let Ok(msg) = channel.recv.try_recv() {
+        if let WorkletCommand::Process(args) = msg {
+            let _ = args.tail_time_sender.send(false);
+        }
+    }
+}
+
+/// Run `f` on the channel of this context ID, if the context is still alive
+fn with_process_call_channel<T>(id: u64, f: impl FnOnce(&ProcessCallChannel) -> T) -> Option<T> {
+    let (index, generation) = unpack_worklet_id(id);
+    // shared read-lock, the slab is only locked for writing when a context is
+    // created or released
+    let read_lock = GLOBAL_PROCESS_CALL_CHANNEL_MAP.read().unwrap();
+
+    read_lock
+        .slots
+        .get(index)
+        .filter(|slot| slot.generation == generation)
+        .and_then(|slot| slot.channel.as_ref())
+        .map(f)
 }
 
 /// Obtain the WorkletCommand sender for this context ID
-fn process_call_sender(id: usize) -> Sender<WorkletCommand> {
-    // optimistically assume the channel exists and we can use a shared read-lock
-    GLOBAL_PROCESS_CALL_CHANNEL_MAP.read().unwrap()[id]
-        .send
-        .clone()
+///
+/// The sender of a released channel is disconnected
+fn process_call_sender(id: u64) -> Sender<WorkletCommand> {
+    with_process_call_channel(id, |channel| channel.send.clone())
+        .unwrap_or_else(|| crossbeam_channel::bounded(0).0)
 }
 
 /// Obtain the WorkletCommand receiver for this context ID
-fn process_call_receiver(id: usize) -> Receiver<WorkletCommand> {
-    // optimistically assume the channel exists and we can use a shared read-lock
-    GLOBAL_PROCESS_CALL_CHANNEL_MAP.read().unwrap()[id]
-        .recv
-        .clone()
+fn process_call_receiver(id: u64) -> Option<Receiver<WorkletCommand>> {
+    with_process_call_channel(id, |channel| channel.recv.clone())
+}
+
+/// Obtain the thread scheduling requested for the Worker of this context ID
+fn process_call_scheduling(id: u64) -> Option<Arc<ThreadScheduling>> {
+    with_process_call_channel(id, |channel| channel.scheduling.clone()).flatten()
 }
 
 /// Obtain the WorkletCommand exited flag for this context ID
-fn process_call_exited(id: usize) -> Arc<AtomicBool> {
-    // optimistically assume the channel exists and we can use a shared read-lock
-    GLOBAL_PROCESS_CALL_CHANNEL_MAP.read().unwrap()[id]
-        .exited
-        .clone()
+///
+/// A released channel is always flagged as exited
+fn process_call_exited(id: u64) -> Arc<AtomicBool> {
+    with_process_call_channel(id, |channel| Arc::clone(&channel.exited))
+        .unwrap_or_else(|| Arc::new(AtomicBool::new(true)))
 }
 
 /// Message channel inside the control thread to pass param descriptors of a given AudioWorkletNode
 /// into the static method AudioWorkletProcessor::parameter_descriptors
-struct AudioParamDescriptorsChannel {
+pub(crate) struct AudioParamDescriptorsChannel {
     send: Mutex<Sender<Vec<AudioParamDescriptor>>>,
-    recv: Receiver<Vec<AudioParamDescriptor>>,
+    pub(crate) recv: Receiver<Vec<AudioParamDescriptor>>,
 }
 
 /// Generate the AudioParamDescriptorsChannel
 ///
 /// It is shared by the whole application, so even by different AudioContexts. This is no issue
 /// because it's using a Mutex to prevent concurrency.
-fn audio_param_descriptor_channel() -> &'static AudioParamDescriptorsChannel {
+pub(crate) fn audio_param_descriptor_channel() -> &'static AudioParamDescriptorsChannel {
     static PAIR: OnceLock<AudioParamDescriptorsChannel> = OnceLock::new();
     PAIR.get_or_init(|| {
         let (send, recv) = crossbeam_channel::unbounded();
@@ -136,119 +571,185 @@ struct WorkletAbruptCompletionResult {
     err: Error,
 }
 
-/// Check that given JS and Rust input / output layout are the same, i.e. check
-/// that each input / output have the same number of channels
-///
-/// Note that we don't check the number of inputs / outputs as they is defined
-/// at construction and cannot change
-fn check_same_io_layout(js_io: &JsObject, rs_io: &'static [&'static [&'static [f32]]]) -> bool {
-    for (i, io) in rs_io.iter().enumerate() {
-        if io.len()
-            != js_io
-                .get_element::<JsObject>(i as u32)
-                .unwrap()
-                .get_array_length_unchecked()
-                .unwrap() as usize
-        {
-            return false;
-        }
+/// JS side of a ProcessorArena, created on the first `process` call and kept
+/// until the processor is dropped
+struct ProcessorViews {
+    processor: Ref<()>,
+    // ArrayBuffer mapping the arena memory
+    buffer: Ref<()>,
+    // frozen arrays of Float32Array views and params object given to `process`
+    inputs: Ref<()>,
+    outputs: Ref<()>,
+    params: Ref<()>,
+    // layout of the arena the views have been built for
+    layout_versions: Option<LayoutVersions>,
+    // [[callable process]] flag of the processor
+    callable: bool,
+}
+
+impl ProcessorViews {
+    fn unref(mut self, env: Env) -> Result<()> {
+        self.processor.unref(env)?;
+        self.buffer.unref(env)?;
+        self.inputs.unref(env)?;
+        self.outputs.unref(env)?;
+        self.params.unref(env)?;
+
+        Ok(())
     }
+}
 
-    true
+thread_local! {
+    /// Views of the processors running in this Worker, by processor ID
+    static PROCESSOR_VIEWS: RefCell<HashMap<u32, ProcessorViews>> = RefCell::new(HashMap::new());
 }
 
-/// Recreate the whole JS inputs and output data structure. It is required to start
-/// from scratch because Array are frozen with prevents us to add, remove or modify items.
-fn rebuild_io_layout(
+/// Map the arena memory into the Worker, the ArrayBuffer keeps the arena alive
+/// until it is garbage collected
+fn create_arena_buffer(
     env: &Env,
-    js_io: JsObject,
-    rs_io: &'static [&'static [&'static [f32]]],
-) -> Result<JsObject> {
-    let mut new_js_io = env.create_array(rs_io.len() as u32).unwrap();
-
-    let global = env.get_global()?;
-
-    let k_worklet_get_buffer = env.symbol_for("node-web-audio-api:worklet-get-buffer")?;
-    let get_buffer = global.get_property::<JsSymbol, JsFunction>(k_worklet_get_buffer)?;
+    arena: &Arc<Mutex<ProcessorArena>>,
+    memory: &mut ArenaMemory,
+) -> Result<JsArrayBuffer> {
+    let byte_length = memory.len * std::mem::size_of::<f32>();
+    let hint = Arc::clone(arena);
+    // SAFETY: the memory is never reallocated and outlives the ArrayBuffer
+    let buffer = unsafe {
+        env.create_arraybuffer_with_borrowed_data(
+            memory.ptr as *mut u8,
+            byte_length,
+            hint,
+            |arena, _env| drop(arena),
+        )?
+    };
 
-    let k_worklet_recycle_buffer = env.symbol_for("node-web-audio-api:worklet-recycle-buffer")?;
-    let recycle_buffer = global.get_property::<JsSymbol, JsFunction>(k_worklet_recycle_buffer)?;
+    mark_as_untransferable(env, buffer.into_raw())
+}
 
+/// Prevent the given object to be transferred or detached by user code, which
+/// would leave the render thread writing in memory it does not own anymore
+pub(crate) fn mark_as_untransferable<T: NapiRaw + NapiValue>(env: &Env, obj: T) -> Result<T> {
+    let global = env.get_global()?;
     let k_worklet_mark_as_untransferable =
         env.symbol_for("node-web-audio-api:worklet-mark-as-untransferable")?;
     let mark_as_untransferable =
         global.get_property::<JsSymbol, JsFunction>(k_worklet_mark_as_untransferable)?;
 
-    for (i, io) in rs_io.iter().enumerate() {
-        let mut channels = env.create_array(rs_io[i].len() as u32).unwrap();
-        let old_channels = js_io.get_element::<JsObject>(i as u32).unwrap();
-        // recycle old channels
-        for j in 0..old_channels.get_array_length_unchecked()? {
-            let channel = old_channels.get_element::<JsTypedArray>(j).unwrap();
-            let _ = recycle_buffer.call1::<JsTypedArray, JsUndefined>(channel);
-        }
-        // populate channels
-        for j in 0..io.len() {
-            let channel = get_buffer.call0::<JsTypedArray>()?;
-            let _ = channels.set(j as u32, channel);
-        }
+    mark_as_untransferable.call1::<T, T>(obj)
+}
+
+/// Create the frozen array of channels of each input or output
+fn create_bus_views(
+    env: &Env,
+    buffer: &Ref<()>,
+    number_of_channels: &[usize],
+    offset: impl Fn(usize, usize) -> usize,
+) -> Result<JsObject> {
+    let mut js_buses = env.create_array_with_length(number_of_channels.len())?;
 
-        let channels = mark_as_untransferable.call1::<Array, Array>(channels)?;
-        let mut channels = channels.coerce_to_object().unwrap();
-        let _ = channels.freeze();
+    for (bus_number, &channels) in number_of_channels.iter().enumerate() {
+        let mut js_channels = env.create_array_with_length(channels)?;
 
-        new_js_io.set(i as u32, channels).unwrap();
+        for channel_number in 0..channels {
+            let byte_offset = offset(bus_number, channel_number) * std::mem::size_of::<f32>();
+            let js_channel = env
+                .get_reference_value::<JsArrayBuffer>(buffer)?
+                .into_typedarray(TypedArrayType::Float32, RENDER_QUANTUM_SIZE, byte_offset)?;
+            js_channels.set_element(channel_number as u32, js_channel)?;
+        }
+
+        let mut js_channels = mark_as_untransferable(env, js_channels)?;
+        js_channels.freeze()?;
+        js_buses.set_element(bus_number as u32, js_channels)?;
     }
 
-    let new_js_io = mark_as_untransferable.call1::<Array, Array>(new_js_io)?;
-    let mut new_js_io = new_js_io.coerce_to_object().unwrap();
-    let _ = new_js_io.freeze();
+    let mut js_buses = mark_as_untransferable(env, js_buses)?;
+    js_buses.freeze()?;
 
-    Ok(new_js_io)
+    Ok(js_buses)
 }
 
-/// Recycle all processor buffers on Drop
-fn recycle_processor(env: &Env, processor: JsObject) -> Result<()> {
-    let global = env.get_global()?;
+/// Create the params object, with a view of 1 or 128 values for each param
+fn create_params_views(env: &Env, buffer: &Ref<()>, arena: &ProcessorArena) -> Result<JsObject> {
+    let mut js_params = env.create_object()?;
+
+    for (param_number, name) in arena.param_names.iter().enumerate() {
+        let byte_offset = arena.param_offset(param_number) * std::mem::size_of::<f32>();
+        let js_values = env
+            .get_reference_value::<JsArrayBuffer>(buffer)?
+            .into_typedarray(
+                TypedArrayType::Float32,
+                arena.param_lengths[param_number],
+                byte_offset,
+            )?;
+        js_params.set_named_property(name, js_values)?;
+    }
 
-    let k_worklet_recycle_buffer = env.symbol_for("node-web-audio-api:worklet-recycle-buffer")?;
-    let recycle_buffer = global.get_property::<JsSymbol, JsFunction>(k_worklet_recycle_buffer)?;
+    Ok(js_params)
+}
 
-    let k_worklet_recycle_buffer_1 =
-        env.symbol_for("node-web-audio-api:worklet-recycle-buffer-1")?;
-    let recycle_buffer_1 =
-        global.get_property::<JsSymbol, JsFunction>(k_worklet_recycle_buffer_1)?;
+/// Rebuild the views of the inputs, outputs or params given to `process` after
+/// their number of channels or values has changed
+///
+/// It is required to start from scratch because arrays are frozen which
+/// prevents us to add, remove or modify items.
+fn rebuild_views(env: &Env, views: &mut ProcessorViews, arena: &ProcessorArena) -> Result<()> {
+    let built = views.layout_versions;
+    let current = arena.layout_versions;
+
+    if built.is_none_or(|built| built.inputs != current.inputs) {
+        let inputs = create_bus_views(env, &views.buffer, &arena.input_channels, |i, c| {
+            arena.input_offset(i, c)
+        })?;
+        std::mem::replace(&mut views.inputs, env.create_reference(inputs)?).unref(*env)?;
+    }
 
-    let k_worklet_inputs = env.symbol_for("node-web-audio-api:worklet-inputs")?;
-    let js_inputs = processor.get_property::<JsSymbol, JsObject>(k_worklet_inputs)?;
+    if built.is_none_or(|built| built.outputs != current.outputs) {
+        let outputs = create_bus_views(env, &views.buffer, &arena.output_channels, |o, c| {
+            arena.output_offset(o, c)
+        })?;
+        std::mem::replace(&mut views.outputs, env.create_reference(outputs)?).unref(*env)?;
+    }
 
-    for i in 0..js_inputs.get_array_length_unchecked()? {
-        let input = js_inputs.get_element::<JsObject>(i)?;
-        for j in 0..input.get_array_length_unchecked()? {
-            let channel = input.get_element::<JsTypedArray>(j)?;
-            let _ = recycle_buffer.call1::<JsTypedArray, JsUndefined>(channel)?;
-        }
+    if built.is_none_or(|built| built.params != current.params) {
+        let params = create_params_views(env, &views.buffer, arena)?;
+        std::mem::replace(&mut views.params, env.create_reference(params)?).unref(*env)?;
     }
 
-    let k_worklet_outputs = env.symbol_for("node-web-audio-api:worklet-outputs")?;
-    let js_outputs = processor.get_property::<JsSymbol, JsObject>(k_worklet_outputs)?;
+    views.layout_versions = Some(current);
 
-    for i in 0..js_outputs.get_array_length_unchecked()? {
-        let output = js_outputs.get_element::<JsObject>(i)?;
-        for j in 0..output.get_array_length_unchecked()? {
-            let channel = output.get_element::<JsTypedArray>(j)?;
-            let _ = recycle_buffer.call1::<JsTypedArray, JsUndefined>(channel)?;
-        }
-    }
+    Ok(())
+}
 
-    let k_worklet_params_cache = env.symbol_for("node-web-audio-api:worklet-params-cache")?;
-    let js_params_cache = processor.get_property::<JsSymbol, JsObject>(k_worklet_params_cache)?;
+/// Create the views of a processor on its first `process` call
+fn create_views(
+    env: &Env,
+    processor: JsObject,
+    arena: &Arc<Mutex<ProcessorArena>>,
+    memory: &mut ArenaMemory,
+) -> Result<ProcessorViews> {
+    let buffer = create_arena_buffer(env, arena, memory)?;
+    // placeholders, replaced by `rebuild_views` before the first call
+    let empty = env.create_array_with_length(0)?;
+
+    Ok(ProcessorViews {
+        processor: env.create_reference(processor)?,
+        buffer: env.create_reference(buffer)?,
+        inputs: env.create_reference(&empty)?,
+        outputs: env.create_reference(&empty)?,
+        params: env.create_reference(empty)?,
+        layout_versions: None,
+        callable: true,
+    })
+}
 
-    let param_cache_128 = js_params_cache.get_element::<JsTypedArray>(0)?;
-    let _ = recycle_buffer.call1::<JsTypedArray, JsUndefined>(param_cache_128)?;
+/// Drop the views of a processor and the processor itself
+fn drop_processor(env: &Env, processors: &mut JsObject, id: u32) -> Result<()> {
+    if let Some(views) = PROCESSOR_VIEWS.with_borrow_mut(|views| views.remove(&id)) {
+        views.unref(*env)?;
+    }
 
-    let param_cache_1 = js_params_cache.get_element::<JsTypedArray>(1)?;
-    let _ = recycle_buffer_1.call1::<JsTypedArray, JsUndefined>(param_cache_1)?;
+    processors.delete_named_property(&id.to_string())?;
 
     Ok(())
 }
@@ -257,207 +758,206 @@ fn recycle_processor(env: &Env, processor: JsObject) -> Result<()> {
 fn process_audio_worklet(env: &Env, processors: &JsObject, args: ProcessorArguments) -> Result<()> {
     let ProcessorArguments {
         id,
-        inputs,
-        outputs,
-        param_values,
+        arena,
         current_time,
         current_frame,
         tail_time_sender,
+        sent_at,
+        stats,
     } = args;
 
-    let processor = processors.get_named_property::<JsUnknown>(&id.to_string())?;
+    let mut guard = arena.lock().unwrap();
+
+    let (result, number_of_quanta) = match guard.batch.take() {
+        // the batch is empty once the node is not batched anymore
+        Some(mut batch) if batch.len > 0 => {
+            let mut result = Ok(true);
+
+            for quantum in batch.quanta.iter_mut().take(batch.len) {
+                guard.load_quantum(quantum);
+                result = process_audio_worklet_arena(
+                    env,
+                    processors,
+                    id,
+                    &arena,
+                    &mut guard,
+                    quantum.current_time,
+                    quantum.current_frame,
+                );
+                guard.store_outputs(quantum);
+
+                if result.is_err() {
+                    break;
+                }
+            }
 
-    // Make sure the processor exists, might run into race conditions
-    // between Rust Audio thread and JS Worker thread
-    if processor.get_type()? == ValueType::Undefined {
-        let _ = tail_time_sender.send(true); // make sure we will be called
-        return Ok(());
-    }
+            let number_of_quanta = std::mem::replace(&mut batch.len, 0);
+            guard.batch = Some(batch);
 
-    // fill AudioWorkletGlobalScope
-    let mut global = env.get_global()?;
-    global.set_named_property("currentTime", current_time)?;
-    global.set_named_property("currentFrame", current_frame)?;
+            (result, number_of_quanta)
+        }
+        batch => {
+            guard.batch = batch;
+
+            let result = process_audio_worklet_arena(
+                env,
+                processors,
+                id,
+                &arena,
+                &mut guard,
+                current_time,
+                current_frame,
+            );
+
+            (result, 1)
+        }
+    };
 
-    let mut processor = processor.coerce_to_object()?;
+    // release the arena before answering so that the render thread can read
+    // the outputs right away
+    drop(guard);
 
-    let k_worklet_callable_process =
-        env.symbol_for("node-web-audio-api:worklet-callable-process")?;
-    // return early if worklet has been tagged as not callable,
-    // @note - maybe this could be guaranteed on rust side
-    let callable_process = processor
-        .get_property::<JsSymbol, JsBoolean>(k_worklet_callable_process)?
-        .get_value()?;
+    // the duration of a batch is shared among its render quanta
+    let elapsed = sent_at.elapsed() / number_of_quanta.max(1) as u32;
+    (0..number_of_quanta).for_each(|_| stats.record(elapsed));
+
+    // always answer, the render thread would otherwise wait for the deadline
+    let tail_time = *result.as_ref().unwrap_or(&false);
+    let _ = tail_time_sender.send(tail_time); // allowed to fail
+
+    result.map(|_| ())
+}
+
+/// Run the `process` method of the JS processor on the views of its arena,
+/// and return its tail time
+fn process_audio_worklet_arena(
+    env: &Env,
+    processors: &JsObject,
+    id: u32,
+    arena: &Arc<Mutex<ProcessorArena>>,
+    guard: &mut ProcessorArena,
+    current_time: f64,
+    current_frame: u64,
+) -> Result<bool> {
+    if !PROCESSOR_VIEWS.with_borrow(|views| views.contains_key(&id)) {
+        let processor = processors.get_named_property::<JsUnknown>(&id.to_string())?;
+
+        // Make sure the processor exists, might run into race conditions
+        // between Rust Audio thread and JS Worker thread
+        if processor.get_type()? == ValueType::Undefined {
+            return Ok(true); // make sure we will be called
+        }
 
-    if !callable_process {
-        let _ = tail_time_sender.send(false);
-        return Ok(());
+        let processor = processor.coerce_to_object()?;
+        let views = create_views(env, processor, arena, &mut guard.memory)?;
+        PROCESSOR_VIEWS.with_borrow_mut(|processor_views| processor_views.insert(id, views));
     }
 
-    // This value become Some if "process" do not exist or throw an error at execution
-    let mut completion: Option<WorkletAbruptCompletionResult> = None;
+    let views = PROCESSOR_VIEWS.with_borrow_mut(|views| {
+        let views = views.get_mut(&id).unwrap();
 
-    match processor.get_named_property::<JsFunction>("process") {
-        Ok(process_method) => {
-            let k_worklet_inputs = env.symbol_for("node-web-audio-api:worklet-inputs")?;
-            let k_worklet_outputs = env.symbol_for("node-web-audio-api:worklet-outputs")?;
-            let k_worklet_params = env.symbol_for("node-web-audio-api:worklet-params")?;
-            let k_worklet_params_cache =
-                env.symbol_for("node-web-audio-api:worklet-params-cache")?;
-            let render_quantum_size = global
-                .get_named_property::<JsNumber>("renderQuantumSize")?
-                .get_double()? as usize;
-            let mut js_inputs = processor.get_property::<JsSymbol, JsObject>(k_worklet_inputs)?;
-            let mut js_outputs = processor.get_property::<JsSymbol, JsObject>(k_worklet_outputs)?;
-            let mut js_params = processor.get_property::<JsSymbol, JsObject>(k_worklet_params)?;
-            let js_params_cache =
-                processor.get_property::<JsSymbol, JsObject>(k_worklet_params_cache)?;
-
-            // Check JS input and output, and rebuild JS object if layout changed
-            if !check_same_io_layout(&js_inputs, inputs) {
-                let new_js_inputs = rebuild_io_layout(env, js_inputs, inputs)?;
-                // Store new layout in processor
-                processor.set_property(k_worklet_inputs, new_js_inputs)?;
-                // Override js_inputs with new reference
-                js_inputs = processor.get_property::<JsSymbol, JsObject>(k_worklet_inputs)?;
-            }
+        // return early if worklet has been tagged as not callable,
+        // @note - maybe this could be guaranteed on rust side
+        if !views.callable {
+            return Ok(None);
+        }
 
-            if !check_same_io_layout(&js_outputs, outputs) {
-                let new_js_outputs = rebuild_io_layout(env, js_outputs, outputs)?;
-                // Store new layout in processor
-                processor.set_property(k_worklet_outputs, new_js_outputs)?;
-                // Override js_outputs with new reference
-                js_outputs = processor.get_property::<JsSymbol, JsObject>(k_worklet_outputs)?;
-            }
+        if views.layout_versions != Some(guard.layout_versions) {
+            rebuild_views(env, views, guard)?;
+        }
 
-            // Copy inputs into JS inputs buffers
-            for (input_number, input) in inputs.iter().enumerate() {
-                let js_input = js_inputs.get_element::<JsObject>(input_number as u32)?;
+        Ok::<_, Error>(Some((
+            env.get_reference_value::<JsObject>(&views.processor)?,
+            env.get_reference_value::<JsObject>(&views.inputs)?,
+            env.get_reference_value::<JsObject>(&views.outputs)?,
+            env.get_reference_value::<JsObject>(&views.params)?,
+        )))
+    })?;
 
-                for (channel_number, channel) in input.iter().enumerate() {
-                    let js_channel = js_input.get_element::<JsTypedArray>(channel_number as u32)?;
-                    let mut js_channel_value = js_channel.into_value()?;
-                    let js_channel_buffer: &mut [f32] = js_channel_value.as_mut();
-                    js_channel_buffer.copy_from_slice(channel);
-                }
-            }
+    let Some((processor, js_inputs, js_outputs, js_params)) = views else {
+        return Ok(false);
+    };
 
-            // Copy params values into JS params buffers
-            //
-            // @perf - We could rely on the fact that ParameterDescriptors
-            // are ordered maps to avoid sending param names in `param_values`
-            for (name, data) in param_values.iter() {
-                let float32_arr_cache = js_params_cache.get_named_property::<JsObject>(name)?;
-                // retrieve right Float32Array according to actual param size, i.e. 128 or 1
-                let cache_index = if data.len() == 1 { 1 } else { 0 };
-                let float32_arr = float32_arr_cache.get_element::<JsTypedArray>(cache_index)?;
-                // copy data into underlying ArrayBuffer
-                let mut float32_arr_value = float32_arr.into_value()?;
-                let buffer: &mut [f32] = float32_arr_value.as_mut();
-                buffer.copy_from_slice(data);
-                // get new owned value, as `float32_arr` as been consumed by `into_value` call
-                let float32_arr = float32_arr_cache.get_element::<JsTypedArray>(cache_index)?;
-                js_params.set_named_property(name, float32_arr)?;
-            }
+    // fill AudioWorkletGlobalScope
+    let mut global = env.get_global()?;
+    global.set_named_property("currentTime", current_time)?;
+    global.set_named_property("currentFrame", current_frame)?;
 
+    // We only get past this match if "process" do not exist or throw an error at execution
+    let completion = match processor.get_named_property::<JsFunction>("process") {
+        Ok(process_method) => {
             let res: Result<JsUnknown> =
                 process_method.apply3(processor, js_inputs, js_outputs, js_params);
 
             match res {
-                Ok(js_ret) => {
-                    // Grab back new owned value processor and js_ouputs, has been
-                    // consumed by `apply` call
-                    let processor = processors.get_named_property::<JsObject>(&id.to_string())?;
-                    let js_outputs =
-                        processor.get_property::<JsSymbol, JsObject>(k_worklet_outputs)?;
-
-                    // copy JS output buffers back into outputs
-                    for (output_number, output) in outputs.iter().enumerate() {
-                        let js_output = js_outputs.get_element::<JsObject>(output_number as u32)?;
-
-                        for (channel_number, channel) in output.iter().enumerate() {
-                            let js_channel =
-                                js_output.get_element::<JsTypedArray>(channel_number as u32)?;
-                            let js_channel_value = js_channel.into_value()?;
-                            let js_channel_buffer: &[f32] = js_channel_value.as_ref();
-
-                            let src = js_channel_buffer.as_ptr();
-                            let dst = channel.as_ptr() as *mut f32;
-
-                            unsafe {
-                                std::ptr::copy_nonoverlapping(src, dst, render_quantum_size);
-                            }
-                        }
-                    }
-
-                    let ret = js_ret.coerce_to_bool()?.get_value()?;
-                    let _ = tail_time_sender.send(ret); // allowed to fail
-                }
-                Err(err) => {
-                    completion = Some(WorkletAbruptCompletionResult {
-                        cmd: "node-web-audio-api:worklet:process-error".to_string(),
-                        err,
-                    });
-                }
+                // outputs have been written in place by the processor
+                Ok(js_ret) => return js_ret.coerce_to_bool()?.get_value(),
+                Err(err) => WorkletAbruptCompletionResult {
+                    cmd: "node-web-audio-api:worklet:process-error".to_string(),
+                    err,
+                },
             }
         }
-        Err(err) => {
-            completion = Some(WorkletAbruptCompletionResult {
-                cmd: "node-web-audio-api:worklet:process-invalid".to_string(),
-                err,
-            });
-        }
-    }
-
-    // Handle eventual errors
-    if let Some(value) = completion {
-        let WorkletAbruptCompletionResult { cmd, err } = value;
-        // Grab back our process which may have been consumed by the process apply
-        let mut processor = processors.get_named_property::<JsObject>(&id.to_string())?;
-        let k_worklet_queue_task = env.symbol_for("node-web-audio-api:worklet-queue-task")?;
-        // @todo - would be usefull to propagate to rust side too so that the
-        // processor can be removed from graph (?)
-        let value = env.get_boolean(false)?;
-        processor.set_property(k_worklet_callable_process, value)?;
-        // set active source flag to false, same semantic as tail time
-        // https://webaudio.github.io/web-audio-api/#active-source
-        let _ = tail_time_sender.send(false);
-        // Dispatch processorerror event on main thread
-        let queue_task = processor.get_property::<JsSymbol, JsFunction>(k_worklet_queue_task)?;
-        let js_cmd = env.create_string(&cmd)?;
-        let js_err = env.create_error(err)?;
-        let _: Result<JsUnknown> = queue_task.apply2(processor, js_cmd, js_err);
-    }
+        Err(err) => WorkletAbruptCompletionResult {
+            cmd: "node-web-audio-api:worklet:process-invalid".to_string(),
+            err,
+        },
+    };
 
-    Ok(())
+    // Handle errors
+    let WorkletAbruptCompletionResult { cmd, err } = completion;
+    // @todo - would be usefull to propagate to rust side too so that the
+    // processor can be removed from graph (?)
+    let processor = PROCESSOR_VIEWS.with_borrow_mut(|views| {
+        let views = views.get_mut(&id).unwrap();
+        views.callable = false;
+        env.get_reference_value::<JsObject>(&views.processor)
+    })?;
+    // Dispatch processorerror event on main thread
+    let k_worklet_queue_task = env.symbol_for("node-web-audio-api:worklet-queue-task")?;
+    let queue_task = processor.get_property::<JsSymbol, JsFunction>(k_worklet_queue_task)?;
+    let js_cmd = env.create_string(&cmd)?;
+    let js_err = env.create_error(err)?;
+    let _: Result<JsUnknown> = queue_task.apply2(processor, js_cmd, js_err);
+
+    // set active source flag to false, same semantic as tail time
+    // https://webaudio.github.io/web-audio-api/#active-source
+    Ok(false)
 }
 
 /// The entry point into Rust from the Worker
 #[js_function(2)]
 pub(crate) fn run_audio_worklet_global_scope(ctx: CallContext) -> Result<JsUndefined> {
-    // Set thread priority to highest, if not done already
+    // Obtain the unique worker ID
+    let worklet_id = ctx.get::<JsNumber>(0)?.get_int64()? as u64;
+
+    // The context has been closed, the Worker is about to exit
+    let Some(receiver) = process_call_receiver(worklet_id) else {
+        return ctx.env.get_undefined();
+    };
+
+    // Set thread priority, if not done already
     if !HAS_THREAD_PRIO.replace(true) {
-        // allowed to fail
-        let _ = thread_priority::set_current_thread_priority(thread_priority::ThreadPriority::Max);
-    }
+        let scheduled = process_call_scheduling(worklet_id)
+            .is_some_and(|scheduling| scheduling.apply_to_worklet_thread());
 
-    // Obtain the unique worker ID
-    let worklet_id = ctx.get::<JsNumber>(0)?.get_uint32()? as usize;
+        if !scheduled {
+            // best effort default, allowed to fail
+            let _ =
+                thread_priority::set_current_thread_priority(thread_priority::ThreadPriority::Max);
+        }
+    }
     // List of registered processors
     let processors = ctx.get::<JsObject>(1)?;
 
     // Poll for incoming commands and yield back to the event loop if there are none.
     // recv_timeout is not an option due to realtime safety, see discussion of
     // https://github.com/ircam-ismm/node-web-audio-api/pull/124#pullrequestreview-2053515583
-    while let Ok(msg) = process_call_receiver(worklet_id).try_recv() {
+    while let Ok(msg) = receiver.try_recv() {
         match msg {
             WorkletCommand::Drop(id) => {
                 let mut processors = ctx.get::<JsObject>(1)?;
-                // recycle all processor buffers
-                let processor = processors.get_named_property::<JsObject>(&id.to_string())?;
-                recycle_processor(ctx.env, processor)?;
-
-                processors.delete_named_property(&id.to_string())?;
+                drop_processor(ctx.env, &mut processors, id)?;
             }
             WorkletCommand::Process(args) => {
                 process_audio_worklet(ctx.env, &processors, args)?;
@@ -471,22 +971,68 @@ pub(crate) fn run_audio_worklet_global_scope(ctx: CallContext) -> Result<JsUndef
 #[js_function(1)]
 pub(crate) fn exit_audio_worklet_global_scope(ctx: CallContext) -> Result<JsUndefined> {
     // Obtain the unique worker ID
-    let worklet_id = ctx.get::<JsNumber>(0)?.get_uint32()? as usize;
+    let worklet_id = ctx.get::<JsNumber>(0)?.get_int64()? as u64;
     // Flag message channel as exited to prevent any other render call
     process_call_exited(worklet_id).store(true, Ordering::SeqCst);
     // Handle any pending message from audio thread
-    if let Ok(WorkletCommand::Process(args)) = process_call_receiver(worklet_id).try_recv() {
-        let _ = args.tail_time_sender.send(false);
+    if let Some(receiver) = process_call_receiver(worklet_id) {
+        if let Ok(WorkletCommand::Process(args)) = receiver.try_recv() {
+            let _ = args.tail_time_sender.send(false);
+        }
+    }
+    // Release the views, the arenas are freed once garbage collected
+    for (_, views) in PROCESSOR_VIEWS.take() {
+        views.unref(*ctx.env)?;
+    }
+
+    ctx.env.get_undefined()
+}
+
+/// Swap the JS processor of given ID for a new instance, i.e. when its module
+/// has been reloaded, the views of its arena being kept
+#[js_function(2)]
+pub(crate) fn replace_audio_worklet_processor(ctx: CallContext) -> Result<JsUndefined> {
+    let id = ctx.get::<JsNumber>(0)?.get_uint32()?;
+    let processor = ctx.get::<JsObject>(1)?;
+
+    // nothing to do if the processor has not been called yet
+    let previous = PROCESSOR_VIEWS.with_borrow_mut(|views| {
+        views.get_mut(&id).map(|views| {
+            // the new code is given a chance to run
+            views.callable = true;
+            ctx.env
+                .create_reference(processor)
+                .map(|processor| std::mem::replace(&mut views.processor, processor))
+        })
+    });
+
+    if let Some(previous) = previous {
+        previous?.unref(*ctx.env)?;
     }
 
     ctx.env.get_undefined()
 }
 
-pub(crate) struct NapiAudioWorkletNode(AudioWorkletNode);
+/// Events sent from the render thread to the JS AudioWorkletNode
+pub(crate) enum ProcessorEvent {
+    /// Bytes posted on the port by a WebAssembly processor
+    Message(Vec<u8>),
+    /// The processor is not called anymore
+    Error(String),
+}
+
+/// The node, the durations of the `process` calls of its processor, and
+/// whether its render quanta are not batched anymore, cf. `batchQuanta`
+pub(crate) struct NapiAudioWorkletNode(AudioWorkletNode, Arc<ProcessorStats>, Arc<AtomicBool>);
 
 impl NapiAudioWorkletNode {
     pub fn create_js_class(env: &Env) -> Result<JsFunction> {
-        let interface = audio_node_interface![];
+        let interface = audio_node_interface![
+            Property::new("missedQuanta")?.with_getter(get_missed_quanta),
+            Property::new("getStats")?.with_method(get_stats),
+            Property::new("resetStats")?.with_method(reset_stats),
+            Property::new("disableBatching")?.with_method(disable_batching)
+        ];
 
         env.define_class("AudioWorkletNode", constructor, &interface)
     }
@@ -494,9 +1040,63 @@ impl NapiAudioWorkletNode {
     pub fn unwrap(&self) -> &AudioWorkletNode {
         &self.0
     }
+
+    /// Wrap a node created from Rust code, e.g. the probes of the node
+    /// profiler, so that other nodes can be connected to it
+    pub fn wrap_native(env: &Env, node: AudioWorkletNode) -> Result<JsObject> {
+        let mut js_obj = env.create_object()?;
+
+        js_obj.define_properties(&[Property::new("Symbol.toStringTag")?
+            .with_value(&env.create_string("AudioWorkletNode")?)
+            .with_property_attributes(PropertyAttributes::Static)])?;
+
+        let stats = Arc::new(ProcessorStats::new(Duration::ZERO));
+        let napi_node = NapiAudioWorkletNode(node, stats, Arc::default());
+        env.wrap(&mut js_obj, napi_node)?;
+
+        Ok(js_obj)
+    }
+}
+
+fn with_processor_options<T>(
+    options: AudioWorkletNodeOptions<()>,
+    processor_options: T,
+) -> AudioWorkletNodeOptions<T> {
+    AudioWorkletNodeOptions {
+        number_of_inputs: options.number_of_inputs,
+        number_of_outputs: options.number_of_outputs,
+        output_channel_count: options.output_channel_count,
+        parameter_data: options.parameter_data,
+        audio_node_options: options.audio_node_options,
+        processor_options,
+    }
+}
+
+/// Create the native node running the processor `P` in the given context
+fn create_native_node<P: AudioWorkletProcessor + 'static>(
+    env: &Env,
+    js_audio_context: &JsObject,
+    audio_context_str: &str,
+    options: AudioWorkletNodeOptions<P::ProcessorOptions>,
+) -> Result<AudioWorkletNode> {
+    let native_node = match audio_context_str {
+        "AudioContext" => {
+            let napi_audio_context = env.unwrap::<NapiAudioContext>(js_audio_context)?;
+            let audio_context = napi_audio_context.unwrap();
+            AudioWorkletNode::new::<P>(audio_context, options)
+        }
+        "OfflineAudioContext" => {
+            let napi_audio_context = env.unwrap::<NapiOfflineAudioContext>(js_audio_context)?;
+            let audio_context = napi_audio_context.unwrap();
+            AudioWorkletNode::new::<P>(audio_context, options)
+        }
+        &_ => unreachable!(),
+    };
+
+    Ok(native_node)
 }
 
-#[js_function(4)]
+#[js_function(5)]
 fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     let mut js_this = ctx.this_unchecked::<JsObject>();
 
@@ -549,6 +1149,25 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
         parameter_data.insert(key, value);
     }
 
+    // non spec compliant, watchdog of the `process` calls of JS processors
+    let process_deadline = options_js.get_named_property::<JsUnknown>("processDeadline")?;
+    let process_deadline = match process_deadline.get_type()? {
+        // default of realtime contexts, bound below to the budget of the context
+        ValueType::Null => None,
+        _ => Some(process_deadline.coerce_to_number()?.get_double()?),
+    };
+
+    let max_missed_quanta = options_js
+        .get_named_property::<JsNumber>("maxMissedQuanta")?
+        .get_double()? as u64;
+
+    // non spec compliant, render quanta per round-trip with the Worker in
+    // offline contexts
+    let batch_quanta = options_js
+        .get_named_property::<JsNumber>("batchQuanta")?
+        .get_double()? as usize;
+    let batching_disabled = Arc::new(AtomicBool::new(false));
+
     // No `processorOptions` here, they are sent to JS processor
 
     // --------------------------------------------------------
@@ -653,31 +1272,56 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
         js_audio_context.get_named_property::<JsString>("Symbol.toStringTag")?;
     let audio_context_str = audio_context_name.into_utf8()?;
 
-    let worklet_id = match audio_context_str.as_str()? {
+    let (worklet_ids, budget, context_length) = match audio_context_str.as_str()? {
         "AudioContext" => {
             let napi_audio_context = ctx.env.unwrap::<NapiAudioContext>(&js_audio_context)?;
-            napi_audio_context.worklet_id()
+            (
+                napi_audio_context.worklet_ids(),
+                Some(napi_audio_context.process_budget()),
+                usize::MAX,
+            )
         }
         "OfflineAudioContext" => {
             let napi_audio_context = ctx
                 .env
                 .unwrap::<NapiOfflineAudioContext>(&js_audio_context)?;
-            napi_audio_context.worklet_id()
+            (
+                napi_audio_context.worklet_ids(),
+                None,
+                napi_audio_context.unwrap().length(),
+            )
         }
         &_ => panic!("not supported"),
     };
 
+    // non spec compliant, Worker running the JS processor
+    let worklet_thread_index = options_js
+        .get_named_property::<JsNumber>("workletThreadIndex")?
+        .get_uint32()? as usize;
+    let Some(&worklet_id) = worklet_ids.get(worklet_thread_index) else {
+        return Err(napi::Error::from_reason(format!(
+            "IndexSizeError - Failed to construct 'AudioWorkletNode': workletThreadIndex ({worklet_thread_index}) is outside the number of AudioWorklet threads ({})",
+            worklet_ids.len(),
+        )));
+    };
+    // non spec compliant, the processor runs concurrently with the render
+    // thread and the other Workers with a render quantum of latency
+    let pipelined = options_js
+        .get_named_property::<JsBoolean>("pipelined")?
+        .get_value()?;
+
+    let deadline = match (process_deadline, budget) {
+        (Some(deadline), _) if deadline.is_finite() => {
+            ProcessDeadline::Fixed(Duration::from_secs_f64(deadline))
+        }
+        (None, Some(budget)) => ProcessDeadline::Shared(budget),
+        _ => ProcessDeadline::None,
+    };
+
     // --------------------------------------------------------
     // Create AudioWorkletNodeOptions object
     // --------------------------------------------------------
     let id = INCREMENTING_ID.fetch_add(1, Ordering::Relaxed);
-    let processor_options = NapiAudioWorkletProcessor {
-        id,
-        send: process_call_sender(worklet_id),
-        exited: process_call_exited(worklet_id),
-        tail_time_channel: crossbeam_channel::bounded(1),
-        param_values: Vec::with_capacity(32),
-    };
 
     let options = AudioWorkletNodeOptions {
         number_of_inputs,
@@ -689,11 +1333,75 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
             channel_count_mode,
             channel_interpretation,
         },
-        processor_options,
+        processor_options: (),
     };
 
     // --------------------------------------------------------
-    // send parameterDescriptors so that NapiAudioWorkletProcessor can retrieve them
+    // Events of the processor, e.g. `processorerror` raised by the watchdog
+    // --------------------------------------------------------
+    let js_processor = ctx.get::<JsObject>(4)?;
+    let onevent_cb = js_processor.get_named_property::<JsFunction>("onevent")?;
+
+    let mut events = ctx.env.create_threadsafe_function(
+        &onevent_cb,
+        0,
+        |ctx: ThreadSafeCallContext<ProcessorEvent>| {
+            let mut event = ctx.env.create_object()?;
+
+            match ctx.value {
+                ProcessorEvent::Message(bytes) => {
+                    let data = ctx.env.create_arraybuffer_with_data(bytes)?;
+                    event.set_named_property("type", ctx.env.create_string("message")?)?;
+                    event.set_named_property("data", data.into_raw())?;
+                }
+                ProcessorEvent::Error(message) => {
+                    event.set_named_property("type", ctx.env.create_string("processorerror")?)?;
+                    event.set_named_property("message", ctx.env.create_string(&message)?)?;
+                }
+            }
+
+            Ok(vec![event])
+        },
+    )?;
+
+    // unref tsfn so they do not prevent the process to exit
+    let _ = events.unref(ctx.env);
+
+    // non spec compliant, processor implemented in WebAssembly and running
+    // directly on the render thread, cf. `AudioWorklet.addWasmModule`
+    let wasm_js = js_processor.get::<&str, JsObject>("wasm")?;
+
+    let wasm_processor = if let Some(wasm_js) = wasm_js {
+        // the processor is instantiated before the parameter descriptors are
+        // sent, so that an error does not leave them in the channel
+        let (processor, js_port) = WasmAudioWorkletProcessor::new(
+            ctx.env,
+            &wasm_js,
+            events.clone(),
+            number_of_inputs,
+            number_of_outputs,
+            &rs_params,
+        )?;
+        js_this.set_named_property("wasmPort", js_port)?;
+
+        Some(processor)
+    } else {
+        None
+    };
+
+    // non spec compliant, durations of the `process` calls, a call is late
+    // when it lasts longer than a render quantum
+    let sample_rate = js_audio_context
+        .get_named_property::<JsNumber>("sampleRate")?
+        .get_double()?;
+    let quantum_duration = Duration::from_secs_f64(RENDER_QUANTUM_SIZE as f64 / sample_rate);
+    let stats = Arc::new(ProcessorStats::new(quantum_duration));
+    let param_names: Vec<String> = rs_params.iter().map(|desc| desc.name.clone()).collect();
+    // the render quanta of a source processor do not depend on the graph
+    let batch_delayed = number_of_inputs > 0 || !param_names.is_empty();
+
+    // --------------------------------------------------------
+    // send parameterDescriptors so that the processor can retrieve them
     // --------------------------------------------------------
     let guard = audio_param_descriptor_channel().send.lock().unwrap();
     guard.send(rs_params).unwrap();
@@ -701,24 +1409,57 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     // --------------------------------------------------------
     // Create native AudioWorkletNode
     // --------------------------------------------------------
-    let native_node = match audio_context_str.as_str()? {
-        "AudioContext" => {
-            let napi_audio_context = ctx.env.unwrap::<NapiAudioContext>(&js_audio_context)?;
-            let audio_context = napi_audio_context.unwrap();
-            AudioWorkletNode::new::<NapiAudioWorkletProcessor>(audio_context, options)
-        }
-        "OfflineAudioContext" => {
-            let napi_audio_context = ctx
-                .env
-                .unwrap::<NapiOfflineAudioContext>(&js_audio_context)?;
-            let audio_context = napi_audio_context.unwrap();
-            AudioWorkletNode::new::<NapiAudioWorkletProcessor>(audio_context, options)
+    let audio_context_str = audio_context_str.as_str()?;
+
+    let native_node = match wasm_processor {
+        Some(processor) => create_native_node::<WasmAudioWorkletProcessor>(
+            ctx.env,
+            &js_audio_context,
+            audio_context_str,
+            with_processor_options(options, processor),
+        ),
+        None => {
+            let processor = NapiAudioWorkletProcessor {
+                id,
+                send: process_call_sender(worklet_id),
+                exited: process_call_exited(worklet_id),
+                tail_time_channel: crossbeam_channel::bounded(1),
+                arena: Arc::new(Mutex::new(ProcessorArena::new(
+                    number_of_inputs,
+                    number_of_outputs,
+                    param_names,
+                    batch_quanta,
+                ))),
+                deadline,
+                max_missed_quanta,
+                pipelined,
+                pending: false,
+                consecutive_missed_quanta: 0,
+                stats: Arc::clone(&stats),
+                batch_quanta,
+                batch_delayed,
+                batching_disabled: Arc::clone(&batching_disabled),
+                batch_len: 0,
+                batch_index: 0,
+                batch_tail_time: true,
+                context_length,
+                callable: true,
+                events,
+            };
+
+            create_native_node::<NapiAudioWorkletProcessor>(
+                ctx.env,
+                &js_audio_context,
+                audio_context_str,
+                with_processor_options(options, processor),
+            )
         }
-        &_ => unreachable!(),
     };
 
     drop(guard);
 
+    let native_node = native_node?;
+
     let mut js_parameters = ctx.env.create_object()?;
 
     for (name, native_param) in native_node.parameters().iter() {
@@ -750,7 +1491,7 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     ])?;
 
     // finalize instance creation
-    let napi_node = NapiAudioWorkletNode(native_node);
+    let napi_node = NapiAudioWorkletNode(native_node, stats, batching_disabled);
     ctx.env.wrap(&mut js_this, napi_node)?;
 
     ctx.env.get_undefined()
@@ -758,10 +1499,71 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
 
 audio_node_impl!(NapiAudioWorkletNode);
 
+#[js_function]
+fn get_missed_quanta(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_node = ctx.env.unwrap::<NapiAudioWorkletNode>(&js_this)?;
+
+    let missed_quanta = napi_node.1.missed_quanta.load(Ordering::Relaxed);
+
+    ctx.env.create_double(missed_quanta as f64)
+}
+
+#[js_function]
+fn get_stats(ctx: CallContext) -> Result<JsObject> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_node = ctx.env.unwrap::<NapiAudioWorkletNode>(&js_this)?;
+
+    let report = napi_node.1.report();
+
+    let mut js_stats = ctx.env.create_object()?;
+    js_stats.set_named_property("quanta", ctx.env.create_double(report.quanta as f64)?)?;
+    js_stats.set_named_property("averageTime", ctx.env.create_double(report.average_time)?)?;
+    js_stats.set_named_property("maxTime", ctx.env.create_double(report.max_time)?)?;
+    js_stats.set_named_property("p99Time", ctx.env.create_double(report.p99_time)?)?;
+    js_stats.set_named_property(
+        "lateQuanta",
+        ctx.env.create_double(report.late_quanta as f64)?,
+    )?;
+
+    Ok(js_stats)
+}
+
+#[js_function]
+fn reset_stats(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_node = ctx.env.unwrap::<NapiAudioWorkletNode>(&js_this)?;
+
+    napi_node.1.reset();
+
+    ctx.env.get_undefined()
+}
+
+#[js_function]
+fn disable_batching(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_node = ctx.env.unwrap::<NapiAudioWorkletNode>(&js_this)?;
+
+    // picked up by the processor at the next render quantum
+    napi_node.2.store(true, Ordering::Relaxed);
+
+    ctx.env.get_undefined()
+}
+
 // -------------------------------------------------
 // AudioWorkletNode Interface
 // -------------------------------------------------
 
+/// Max wait of the render thread for a `process` call
+enum ProcessDeadline {
+    /// `processDeadline` given to the node
+    Fixed(Duration),
+    /// Render quantum budget of the context, shared with the other processors
+    Shared(Arc<ProcessBudget>),
+    /// Wait forever, e.g. in offline contexts
+    None,
+}
+
 struct NapiAudioWorkletProcessor {
     /// Unique id to pair Napi Worklet and JS processor
     id: u32,
@@ -771,8 +1573,230 @@ struct NapiAudioWorkletProcessor {
     exited: Arc<AtomicBool>,
     /// tail_time result channel
     tail_time_channel: (Sender<bool>, Receiver<bool>),
-    /// Reusable Vec for AudioParam values
-    param_values: Vec<(&'static str, &'static [f32])>,
+    /// Memory shared with the Worker
+    arena: Arc<Mutex<ProcessorArena>>,
+    /// Max wait for a `process` call
+    deadline: ProcessDeadline,
+    /// Number of consecutive missed render quanta after which the processor
+    /// is not called anymore
+    max_missed_quanta: u64,
+    /// The result of a `process` call is collected at the next render quantum
+    pipelined: bool,
+    /// A `process` call has been sent and has not returned yet, i.e. it overran
+    /// its deadline or the processor is pipelined
+    pending: bool,
+    /// Number of consecutive missed render quanta
+    consecutive_missed_quanta: u64,
+    /// Durations of the calls and total number of missed render quanta,
+    /// shared with the node
+    stats: Arc<ProcessorStats>,
+    /// Number of render quanta processed per round-trip with the Worker in
+    /// offline contexts
+    batch_quanta: usize,
+    /// The render quanta depend on the inputs or params, a batch is processed
+    /// once all its render quanta are queued and its outputs are delayed
+    batch_delayed: bool,
+    /// Set when the node is connected in a cycle, shared with the node
+    batching_disabled: Arc<AtomicBool>,
+    /// Number of render quanta of the last processed batch
+    batch_len: usize,
+    /// Next render quantum of the last processed batch to deliver
+    batch_index: usize,
+    /// Tail time of the last processed batch
+    batch_tail_time: bool,
+    /// Length of the OfflineAudioContext in sample-frames, the last batch
+    /// stops at the end of the rendering
+    context_length: usize,
+    /// The watchdog gave up on the processor
+    callable: bool,
+    /// Dispatch `processorerror` on the node
+    events: ThreadsafeFunction<ProcessorEvent>,
+}
+
+impl NapiAudioWorkletProcessor {
+    /// Output silence in place of the `process` call, and give up on the
+    /// processor if too many consecutive render quanta have been missed
+    fn miss_render_quantum(&mut self, outputs: &mut [&mut [&mut [f32]]]) -> bool {
+        silence(outputs);
+
+        self.stats.missed_quanta.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_missed_quanta += 1;
+
+        if self.consecutive_missed_quanta >= self.max_missed_quanta {
+            self.callable = false;
+
+            let message = format!(
+                "process() missed its deadline for {} consecutive render quanta",
+                self.consecutive_missed_quanta
+            );
+            self.events.call(
+                Ok(ProcessorEvent::Error(message)),
+                ThreadsafeFunctionCallMode::NonBlocking,
+            );
+
+            return false;
+        }
+
+        // keep the processor alive while the Worker is late
+        true
+    }
+
+    /// Wait for the answer of the pending call until the deadline
+    fn await_tail_time(&self, current_frame: u64) -> Option<bool> {
+        let timeout = match &self.deadline {
+            ProcessDeadline::Fixed(deadline) => *deadline,
+            ProcessDeadline::Shared(budget) => budget.remaining(current_frame),
+            ProcessDeadline::None => return self.tail_time_channel.1.recv().ok(),
+        };
+
+        self.tail_time_channel.1.recv_timeout(timeout).ok()
+    }
+
+    /// Deliver the render quanta of the last processed batch, and process the
+    /// next batch once they have all been delivered. The processor has neither
+    /// inputs nor params, so that a batch is processed ahead of time from its
+    /// first render quantum, and its outputs are not delayed.
+    fn process_batched(
+        &mut self,
+        inputs: &[&[&[f32]]],
+        outputs: &mut [&mut [&mut [f32]]],
+        params: &AudioParamValues<'_>,
+        scope: &AudioWorkletGlobalScope,
+    ) -> bool {
+        if self.batch_index < self.batch_len {
+            // never contended, the Worker only holds the lock while we wait
+            self.arena
+                .lock()
+                .unwrap()
+                .read_batched(self.batch_index, outputs);
+            self.batch_index += 1;
+            // keep the processor alive until the whole batch is delivered
+            return self.batch_tail_time || self.batch_index < self.batch_len;
+        }
+
+        // the last batch stops at the end of the rendering
+        let remaining_frames = self
+            .context_length
+            .saturating_sub(scope.current_frame as usize);
+        let batch_len = self
+            .batch_quanta
+            .min(remaining_frames.div_ceil(RENDER_QUANTUM_SIZE))
+            .max(1);
+
+        let mut arena = self.arena.lock().unwrap();
+        arena.write(inputs, outputs, params);
+
+        for index in 0..batch_len {
+            let offset = (index * RENDER_QUANTUM_SIZE) as u64;
+            arena.queue_quantum(
+                scope.current_time + offset as f64 / f64::from(scope.sample_rate),
+                scope.current_frame + offset,
+            );
+        }
+
+        drop(arena);
+
+        let Some(tail_time) = self.send_batch(scope) else {
+            silence(outputs);
+            return false;
+        };
+
+        self.batch_tail_time = tail_time;
+        self.batch_len = batch_len;
+        self.batch_index = 1;
+        self.arena.lock().unwrap().read_batched(0, outputs);
+
+        tail_time || batch_len > 1
+    }
+
+    /// Queue the render quantum, and process the batch once its render quanta
+    /// have all been queued, or at the end of the rendering. The outputs are
+    /// those of the last processed batch, i.e. they are delayed by
+    /// `batch_quanta - 1` render quanta, and silent until the first batch.
+    fn process_batched_delayed(
+        &mut self,
+        inputs: &[&[&[f32]]],
+        outputs: &mut [&mut [&mut [f32]]],
+        params: &AudioParamValues<'_>,
+        scope: &AudioWorkletGlobalScope,
+    ) -> bool {
+        // never contended, the Worker only holds the lock while we wait
+        let mut arena = self.arena.lock().unwrap();
+        arena.write(inputs, outputs, params);
+        arena.queue_quantum(scope.current_time, scope.current_frame);
+        let queued = arena.queued_quanta();
+
+        // the render quantum of the last batch queued `batch_quanta - 1`
+        // render quanta ago, its slot has not been overwritten yet
+        let full = queued == self.batch_quanta;
+
+        if !full && queued < self.batch_len {
+            arena.read_batched(queued, outputs);
+        } else if !full {
+            silence(outputs);
+        }
+
+        drop(arena);
+
+        let last = scope.current_frame as usize + RENDER_QUANTUM_SIZE >= self.context_length;
+
+        if !full && !last {
+            // keep the processor alive until the whole batch is delivered
+            return self.batch_tail_time || queued + 1 < self.batch_len;
+        }
+
+        let Some(tail_time) = self.send_batch(scope) else {
+            silence(outputs);
+            return false;
+        };
+
+        self.batch_tail_time = tail_time;
+        self.batch_len = queued;
+
+        if full {
+            self.arena.lock().unwrap().read_batched(0, outputs);
+        }
+
+        tail_time || queued > 1
+    }
+
+    /// Process the queued render quanta in a single round-trip with the
+    /// Worker, and return the tail time of the last one
+    fn send_batch(&self, scope: &AudioWorkletGlobalScope) -> Option<bool> {
+        let item = ProcessorArguments {
+            id: self.id,
+            arena: Arc::clone(&self.arena),
+            current_time: scope.current_time,
+            current_frame: scope.current_frame,
+            tail_time_sender: self.tail_time_channel.0.clone(),
+            sent_at: Instant::now(),
+            stats: Arc::clone(&self.stats),
+        };
+
+        self.send.send(WorkletCommand::Process(item)).ok()?;
+
+        // offline contexts have no deadline
+        self.tail_time_channel.1.recv().ok()
+    }
+
+    /// Process the render quanta one at a time from now on, the queued render
+    /// quanta are still processed but their delayed outputs are dropped
+    fn stop_batching(&mut self, scope: &AudioWorkletGlobalScope) {
+        self.batch_quanta = 1;
+
+        // the tail time is given by the call of the current render quantum
+        if self.arena.lock().unwrap().queued_quanta() > 0 {
+            let _ = self.send_batch(scope);
+        }
+    }
+
+    /// Copy the outputs computed by the Worker
+    fn read_outputs(&self, outputs: &mut [&mut [&mut [f32]]]) {
+        match self.arena.try_lock() {
+            Ok(arena) => arena.read(outputs),
+            Err(_) => silence(outputs),
+        }
+    }
 }
 
 impl AudioWorkletProcessor for NapiAudioWorkletProcessor {
@@ -802,46 +1826,106 @@ impl AudioWorkletProcessor for NapiAudioWorkletProcessor {
             return false;
         }
 
-        // SAFETY:
-        // We are transmuting the a' and b' lifetimes to static in order to send them to the Worker
-        // thread. This should be safe as long as:
-        // - this function does not return before the Worker has finished using the slices
-        // - the Worker / JS-code doesn't keep a copy of these slices - fingers crossed on this one
+        if !self.callable {
+            silence(outputs);
+            return false;
+        }
+
+        if self.batch_quanta > 1 && self.batching_disabled.load(Ordering::Relaxed) {
+            self.stop_batching(scope);
+        }
+
+        if self.batch_quanta > 1 && self.batch_delayed {
+            return self.process_batched_delayed(inputs, outputs, &params, scope);
+        }
 
-        let inputs: &'static [&'static [&'static [f32]]] = unsafe { std::mem::transmute(inputs) };
-        let outputs: &'static [&'static [&'static [f32]]] = unsafe { std::mem::transmute(outputs) };
+        if self.batch_quanta > 1 {
+            return self.process_batched(inputs, outputs, &params, scope);
+        }
 
-        self.param_values.clear();
-        self.param_values.extend(params.keys().map(|k| {
-            let label: &'static str = unsafe { std::mem::transmute(k) };
-            let value: &'static [f32] = unsafe { std::mem::transmute(&params.get(k)[..]) };
-            (label, value)
-        }));
-        let param_values: &'static [_] = unsafe { std::mem::transmute(&self.param_values[..]) };
+        // Tail time of the last answered call
+        let mut tail_time = true;
+
+        // Do not queue another call until the Worker is done with the pending
+        // one. When not pipelined, the pending call overran its deadline and
+        // its result is discarded.
+        if self.pending {
+            let answer = if self.pipelined {
+                self.await_tail_time(scope.current_frame)
+            } else {
+                self.tail_time_channel.1.try_recv().ok()
+            };
+
+            let Some(answer) = answer else {
+                return self.miss_render_quantum(outputs);
+            };
+
+            self.pending = false;
+
+            if self.pipelined {
+                self.consecutive_missed_quanta = 0;
+                self.read_outputs(outputs);
+                tail_time = answer;
+            }
+        } else if self.pipelined {
+            // nothing has been computed yet
+            silence(outputs);
+        }
 
-        // end SAFETY comment
+        // never blocks as no call is pending
+        let Ok(mut arena) = self.arena.try_lock() else {
+            return self.miss_render_quantum(outputs);
+        };
+        arena.write(inputs, outputs, &params);
+        drop(arena);
 
         let item = ProcessorArguments {
             id: self.id,
-            inputs,
-            outputs,
-            param_values,
+            arena: Arc::clone(&self.arena),
             current_time: scope.current_time,
             current_frame: scope.current_frame,
             tail_time_sender: self.tail_time_channel.0.clone(),
+            sent_at: Instant::now(),
+            stats: Arc::clone(&self.stats),
+        };
+
+        // send command to Worker, fails if the context has been released
+        // in the meantime
+        if self.send.send(WorkletCommand::Process(item)).is_err() {
+            silence(outputs);
+            return false;
+        }
+
+        // the Worker runs concurrently with the render thread and the other
+        // Workers until the next render quantum
+        if self.pipelined {
+            self.pending = true;
+            return tail_time;
+        }
+
+        let Some(tail_time) = self.await_tail_time(scope.current_frame) else {
+            self.pending = true;
+            return self.miss_render_quantum(outputs);
         };
 
-        // send command to Worker
-        self.send.send(WorkletCommand::Process(item)).unwrap();
-        // await result
-        self.tail_time_channel.1.recv().unwrap()
+        self.consecutive_missed_quanta = 0;
+        self.read_outputs(outputs);
+
+        tail_time
     }
 }
 
+fn silence(outputs: &mut [&mut [&mut [f32]]]) {
+    outputs
+        .iter_mut()
+        .flat_map(|output| output.iter_mut())
+        .for_each(|channel| channel.fill(0.));
+}
+
 impl Drop for NapiAudioWorkletProcessor {
     fn drop(&mut self) {
         if !self.exited.load(Ordering::SeqCst) {
-            self.send.send(WorkletCommand::Drop(self.id)).unwrap();
+            let _ = self.send.send(WorkletCommand::Drop(self.id));
         }
     }
 }
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use napi::*;
+use napi_derive::js_function;
+use web_audio_api::AudioBuffer;
+
+use crate::audio_buffer::NapiAudioBuffer;
+
+static INCREMENTING_ID: AtomicU32 = AtomicU32::new(0);
+
+/// AudioBuffers given in the `processorOptions` of an AudioWorkletNode, from
+/// the moment the node is created on the main thread to the moment the
+/// Worker builds the options of the processor
+///
+/// The buffers are clones of the main thread buffers, i.e. they share the
+/// same channel data. The entries that are never taken are released with
+/// `release_shared_audio_buffer`.
+fn shared_audio_buffers() -> &'static Mutex<HashMap<u32, AudioBuffer>> {
+    static MAP: OnceLock<Mutex<HashMap<u32, AudioBuffer>>> = OnceLock::new();
+    MAP.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Register the AudioBuffer so that it can be taken by a Worker, returns the
+/// ID to be sent to the Worker
+#[js_function(1)]
+pub(crate) fn share_audio_buffer(ctx: CallContext) -> Result<JsNumber> {
+    let js_buffer = ctx.get::<JsObject>(0)?;
+    let napi_buffer = ctx.env.unwrap::<NapiAudioBuffer>(&js_buffer)?;
+    let audio_buffer = napi_buffer.unwrap().clone();
+
+    let id = INCREMENTING_ID.fetch_add(1, Ordering::Relaxed);
+    shared_audio_buffers()
+        .lock()
+        .unwrap()
+        .insert(id, audio_buffer);
+
+    ctx.env.create_uint32(id)
+}
+
+/// Release the AudioBuffer of given ID if it has not been taken by a Worker,
+/// e.g. if the processor could not be created or the context was closed before
+#[js_function(1)]
+pub(crate) fn release_shared_audio_buffer(ctx: CallContext) -> Result<JsUndefined> {
+    let id = ctx.get::<JsNumber>(0)?.get_uint32()?;
+    shared_audio_buffers().lock().unwrap().remove(&id);
+
+    ctx.env.get_undefined()
+}
+
+/// Take the AudioBuffer of given ID, returns an object wrapping the buffer
+/// with its sample rate, length and number of channels
+///
+/// The buffer shares the channel data of the main thread buffer, which can be
+/// read by the render thread at the same time, the Worker therefore only
+/// reads it through `copy_from_shared_audio_buffer`. The object keeps the
+/// channel data alive until it is garbage collected.
+#[js_function(1)]
+pub(crate) fn take_shared_audio_buffer(ctx: CallContext) -> Result<JsObject> {
+    let id = ctx.get::<JsNumber>(0)?.get_uint32()?;
+    let audio_buffer = shared_audio_buffers()
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| {
+            Error::from_reason(format!(
+                "InvalidStateError - Unknown shared AudioBuffer {id}"
+            ))
+        })?;
+
+    let mut js_result = ctx.env.create_object()?;
+    js_result.set_named_property(
+        "sampleRate",
+        ctx.env.create_double(audio_buffer.sample_rate() as f64)?,
+    )?;
+    js_result.set_named_property(
+        "length",
+        ctx.env.create_uint32(audio_buffer.length() as u32)?,
+    )?;
+    js_result.set_named_property(
+        "numberOfChannels",
+        ctx.env
+            .create_uint32(audio_buffer.number_of_channels() as u32)?,
+    )?;
+    ctx.env.wrap(&mut js_result, audio_buffer)?;
+
+    Ok(js_result)
+}
+
+/// Copy the samples of a channel of a shared AudioBuffer, i.e. an object
+/// returned by `take_shared_audio_buffer`, from the given offset into the
+/// destination Float32Array
+#[js_function(4)]
+pub(crate) fn copy_from_shared_audio_buffer(ctx: CallContext) -> Result<JsUndefined> {
+    let js_shared = ctx.get::<JsObject>(0)?;
+    let audio_buffer = ctx.env.unwrap::<AudioBuffer>(&js_shared)?;
+
+    let mut dest_js = ctx.get::<JsTypedArray>(1)?.into_value()?;
+    let dest: &mut [f32] = dest_js.as_mut();
+
+    let channel_number = ctx.get::<JsNumber>(2)?.get_double()? as usize;
+    let offset = ctx.get::<JsNumber>(3)?.get_double()? as usize;
+
+    audio_buffer.copy_from_channel_with_offset(dest, channel_number, offset);
+
+    ctx.env.get_undefined()
+}
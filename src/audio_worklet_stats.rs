@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Number of bits of the sub-buckets of each power of two of the histogram,
+/// i.e. the percentiles are known within 12.5%
+const SUB_BUCKET_BITS: u32 = 3;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+/// Enough buckets to hold any duration in nanoseconds
+const NUMBER_OF_BUCKETS: usize = (64 - SUB_BUCKET_BITS as usize + 1) * SUB_BUCKETS;
+
+/// Index of the histogram bucket of a duration in nanoseconds, the values
+/// below `SUB_BUCKETS` have their own bucket, the others are grouped by
+/// powers of two split in `SUB_BUCKETS` linear sub-buckets
+fn bucket_index(value: u64) -> usize {
+    if value < SUB_BUCKETS as u64 {
+        return value as usize;
+    }
+
+    let msb = 63 - value.leading_zeros();
+    let shift = msb - SUB_BUCKET_BITS;
+    let sub_bucket = (value >> shift) as usize & (SUB_BUCKETS - 1);
+
+    (shift as usize + 1) * SUB_BUCKETS + sub_bucket
+}
+
+/// Highest duration in nanoseconds held by a histogram bucket
+fn bucket_upper_bound(index: usize) -> u64 {
+    if index < SUB_BUCKETS {
+        return index as u64;
+    }
+
+    let shift = index / SUB_BUCKETS - 1;
+    let sub_bucket = (index % SUB_BUCKETS) as u64;
+
+    ((SUB_BUCKETS as u64 + sub_bucket + 1) << shift) - 1
+}
+
+/// Durations of the `process` calls of a JS processor, measured from the
+/// render thread sending the call to the Worker answering it, in nanoseconds
+///
+/// The durations are recorded by the Worker and read by the main thread, so
+/// that measuring never adds work to the render thread.
+pub(crate) struct ProcessorStats {
+    /// Duration of a render quantum, beyond which a call is late
+    late_threshold: u64,
+    quanta: AtomicU64,
+    late_quanta: AtomicU64,
+    total_time: AtomicU64,
+    max_time: AtomicU64,
+    histogram: [AtomicU64; NUMBER_OF_BUCKETS],
+    /// Render quanta for which the render thread gave up waiting for the
+    /// Worker, not affected by `reset`
+    pub missed_quanta: AtomicU64,
+}
+
+/// Snapshot of the stats, durations are in seconds
+pub(crate) struct ProcessorStatsReport {
+    pub quanta: u64,
+    pub late_quanta: u64,
+    pub average_time: f64,
+    pub max_time: f64,
+    pub p99_time: f64,
+}
+
+impl ProcessorStats {
+    pub fn new(quantum_duration: Duration) -> Self {
+        Self {
+            late_threshold: quantum_duration.as_nanos() as u64,
+            quanta: AtomicU64::new(0),
+            late_quanta: AtomicU64::new(0),
+            total_time: AtomicU64::new(0),
+            max_time: AtomicU64::new(0),
+            histogram: std::array::from_fn(|_| AtomicU64::new(0)),
+            missed_quanta: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, elapsed: Duration) {
+        let elapsed = elapsed.as_nanos() as u64;
+
+        self.quanta.fetch_add(1, Ordering::Relaxed);
+        self.total_time.fetch_add(elapsed, Ordering::Relaxed);
+        self.max_time.fetch_max(elapsed, Ordering::Relaxed);
+        self.histogram[bucket_index(elapsed)].fetch_add(1, Ordering::Relaxed);
+
+        if elapsed > self.late_threshold {
+            self.late_quanta.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn reset(&self) {
+        self.quanta.store(0, Ordering::Relaxed);
+        self.late_quanta.store(0, Ordering::Relaxed);
+        self.total_time.store(0, Ordering::Relaxed);
+        self.max_time.store(0, Ordering::Relaxed);
+        self.histogram
+            .iter()
+            .for_each(|bucket| bucket.store(0, Ordering::Relaxed));
+    }
+
+    /// The calls recorded while reporting may be partially accounted for
+    pub fn report(&self) -> ProcessorStatsReport {
+        let quanta = self.quanta.load(Ordering::Relaxed);
+        let total_time = self.total_time.load(Ordering::Relaxed);
+        let max_time = self.max_time.load(Ordering::Relaxed);
+
+        let counts: Vec<u64> = self
+            .histogram
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        let recorded: u64 = counts.iter().sum();
+        // rank of the 99th percentile, rounded up
+        let rank = recorded - recorded / 100;
+
+        let mut p99_time = 0;
+        let mut cumulated = 0;
+
+        for (index, count) in counts.iter().enumerate() {
+            cumulated += count;
+
+            if cumulated >= rank && *count > 0 {
+                p99_time = bucket_upper_bound(index).min(max_time);
+                break;
+            }
+        }
+
+        let average_time = if quanta > 0 {
+            total_time as f64 / quanta as f64
+        } else {
+            0.
+        };
+
+        ProcessorStatsReport {
+            quanta,
+            late_quanta: self.late_quanta.load(Ordering::Relaxed),
+            average_time: average_time / 1e9,
+            max_time: max_time as f64 / 1e9,
+            p99_time: p99_time as f64 / 1e9,
+        }
+    }
+}
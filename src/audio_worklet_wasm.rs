@@ -0,0 +1,588 @@
+use crate::utils::to_byte_slice;
+
+use crossbeam_channel::{self, Receiver, Sender};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::*;
+use napi_derive::js_function;
+use web_audio_api::worklet::{AudioParamValues, AudioWorkletGlobalScope, AudioWorkletProcessor};
+use web_audio_api::AudioParamDescriptor;
+
+use crate::audio_worklet_node::{audio_param_descriptor_channel, ProcessorEvent};
+
+use runtime::{Caller, Engine, Instance, Linker, Memory, Module, Store, TypedFunc};
+
+const RENDER_QUANTUM_SIZE: usize = 128;
+
+/// Number of channel buffers reserved for each input and output in the linear
+/// memory of the module, i.e. the implementation max number of channels
+const MAX_CHANNELS: usize = 32;
+
+/// Size of a `{ i32 length; float* data }` entry of the inputs, outputs and
+/// params tables handed over to `awp_process`
+const TABLE_ENTRY_SIZE: usize = 8;
+
+/// Max number of instructions executed by the calls made from the main thread,
+/// i.e. `awp_descriptors` and `awp_create`
+const SETUP_FUEL: u64 = 20_000_000;
+
+const CHANNEL_SIZE: usize = RENDER_QUANTUM_SIZE * std::mem::size_of::<f32>();
+const BUS_SIZE: usize = MAX_CHANNELS * CHANNEL_SIZE;
+
+struct HostState {
+    /// Not defined for the instance used to validate the module
+    events: Option<ThreadsafeFunction<ProcessorEvent>>,
+    /// Exported memory of the instance, once instantiated
+    memory: Option<Memory>,
+}
+
+/// Parts of the API that differ between the runtimes, wasmi mirrors the API
+/// of wasmtime otherwise
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+mod runtime {
+    pub(crate) use wasmtime::{
+        Caller, Config, Engine, Error, Instance, Linker, Memory, Module, Store, TypedFunc,
+    };
+
+    /// Upper bound of the number of instructions the compiled code of a module
+    /// runs per second, cf. `process_fuel`
+    pub(crate) const INSTRUCTIONS_PER_SECOND: f64 = 4e9;
+
+    pub(crate) fn metered_engine() -> Result<Engine, Error> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        // leave the signal handlers to Node.js, bounds are checked explicitly
+        config.signals_based_traps(false);
+        Engine::new(&config)
+    }
+
+    pub(crate) fn instantiate<T>(
+        linker: &Linker<T>,
+        store: &mut Store<T>,
+        module: &Module,
+    ) -> Result<Instance, Error> {
+        linker.instantiate(store, module)
+    }
+
+    pub(crate) fn is_out_of_fuel(err: &Error) -> bool {
+        err.downcast_ref::<wasmtime::Trap>() == Some(&wasmtime::Trap::OutOfFuel)
+    }
+}
+
+/// Interpreter used where Cranelift is not available, e.g. 32-bit ARM
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod runtime {
+    pub(crate) use wasmi::{
+        Caller, Config, Engine, Error, Instance, Linker, Memory, Module, Store, TypedFunc,
+    };
+
+    /// Upper bound of the number of instructions the interpreter runs per
+    /// second, cf. `process_fuel`
+    pub(crate) const INSTRUCTIONS_PER_SECOND: f64 = 4e8;
+
+    pub(crate) fn metered_engine() -> Result<Engine, Error> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        Ok(Engine::new(&config))
+    }
+
+    pub(crate) fn instantiate<T>(
+        linker: &Linker<T>,
+        store: &mut Store<T>,
+        module: &Module,
+    ) -> Result<Instance, Error> {
+        linker.instantiate(&mut *store, module)?.start(store)
+    }
+
+    pub(crate) fn is_out_of_fuel(err: &Error) -> bool {
+        err.as_trap_code() == Some(wasmi::core::TrapCode::OutOfFuel)
+    }
+}
+
+/// Max number of instructions executed by a call into the module from the
+/// render thread, so that a runaway processor can't hang it
+///
+/// This is the number of instructions a fast CPU runs in a render quantum,
+/// i.e. a call running out of fuel would have been late on any machine.
+fn process_fuel(sample_rate: f32) -> u64 {
+    let quantum_duration = RENDER_QUANTUM_SIZE as f64 / sample_rate as f64;
+    (quantum_duration * runtime::INSTRUCTIONS_PER_SECOND) as u64
+}
+
+// -------------------------------------------------
+// Module
+// -------------------------------------------------
+
+/// WebAssembly module compiled by `audioWorklet.addWasmModule`
+///
+/// The module is compiled once and instantiated for each AudioWorkletNode, so
+/// that every processor lives in its own linear memory.
+pub(crate) struct NapiAudioWorkletWasmModule {
+    engine: Engine,
+    module: Module,
+}
+
+impl NapiAudioWorkletWasmModule {
+    pub fn create_js_class(env: &Env) -> Result<JsFunction> {
+        env.define_class("AudioWorkletWasmModule", constructor, &[])
+    }
+}
+
+/// Reset the instruction budget before a call into the module
+fn refuel(store: &mut Store<HostState>, fuel: u64) {
+    // fuel metering is enabled on the engine
+    store.set_fuel(fuel).unwrap();
+}
+
+fn instantiate(
+    engine: &Engine,
+    module: &Module,
+    events: Option<ThreadsafeFunction<ProcessorEvent>>,
+) -> std::result::Result<(Store<HostState>, Instance), runtime::Error> {
+    let host_state = HostState {
+        events,
+        memory: None,
+    };
+    let mut store = Store::new(engine, host_state);
+    refuel(&mut store, SETUP_FUEL);
+    let mut linker = Linker::<HostState>::new(engine);
+
+    linker.func_wrap(
+        "env",
+        "awp_post_message",
+        |caller: Caller<'_, HostState>, ptr: i32, length: i32| {
+            let Some(events) = &caller.data().events else {
+                return;
+            };
+
+            let Some(memory) = caller.data().memory else {
+                return;
+            };
+
+            let start = ptr as u32 as usize;
+            let end = start.saturating_add(length as u32 as usize);
+
+            if let Some(bytes) = memory.data(&caller).get(start..end) {
+                let message = ProcessorEvent::Message(bytes.to_vec());
+                events.call(Ok(message), ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        },
+    )?;
+
+    let instance = runtime::instantiate(&linker, &mut store, module)?;
+    store.data_mut().memory = instance.get_memory(&mut store, "memory");
+
+    Ok((store, instance))
+}
+
+/// Read the JSON description of the processors, i.e. the NUL terminated
+/// string returned by `awp_descriptors`
+fn read_descriptors(
+    store: &mut Store<HostState>,
+    instance: &Instance,
+) -> std::result::Result<String, String> {
+    let memory = store
+        .data()
+        .memory
+        .ok_or("module does not export 'memory'")?;
+    let descriptors = instance
+        .get_typed_func::<(), i32>(&mut *store, "awp_descriptors")
+        .map_err(|err| format!("invalid 'awp_descriptors' export: {err}"))?;
+
+    // check the other mandatory exports while we are here
+    instance
+        .get_typed_func::<i32, i32>(&mut *store, "awp_alloc")
+        .map_err(|err| format!("invalid 'awp_alloc' export: {err}"))?;
+    instance
+        .get_typed_func::<(i32, f32), i32>(&mut *store, "awp_create")
+        .map_err(|err| format!("invalid 'awp_create' export: {err}"))?;
+    instance
+        .get_typed_func::<(i32, i32, i32, i32, i64), i32>(&mut *store, "awp_process")
+        .map_err(|err| format!("invalid 'awp_process' export: {err}"))?;
+
+    refuel(store, SETUP_FUEL);
+    let ptr = descriptors
+        .call(&mut *store, ())
+        .map_err(|err| format!("'awp_descriptors' failed: {err}"))?;
+
+    let bytes = memory
+        .data(&*store)
+        .get(ptr as u32 as usize..)
+        .ok_or("'awp_descriptors' returned an out of bounds pointer")?;
+    let length = bytes
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or("'awp_descriptors' returned a non NUL terminated string")?;
+
+    String::from_utf8(bytes[..length].to_vec())
+        .map_err(|_| "'awp_descriptors' returned an invalid UTF-8 string".to_string())
+}
+
+#[js_function(1)]
+fn constructor(ctx: CallContext) -> Result<JsUndefined> {
+    let mut js_this = ctx.this_unchecked::<JsObject>();
+
+    let js_buffer = ctx.get::<JsBuffer>(0)?.into_value()?;
+
+    let engine = runtime::metered_engine()
+        .map_err(|err| Error::from_reason(format!("InvalidStateError - {err}")))?;
+    let module = Module::new(&engine, &js_buffer[..])
+        .map_err(|err| Error::from_reason(format!("Invalid WebAssembly module: {err}")))?;
+
+    // instantiate the module once to retrieve the description of the
+    // processors and to report errors as soon as possible
+    let descriptors = instantiate(&engine, &module, None)
+        .map_err(|err| err.to_string())
+        .and_then(|(mut store, instance)| read_descriptors(&mut store, &instance))
+        .map_err(|err| Error::from_reason(format!("Invalid WebAssembly module: {err}")))?;
+
+    js_this.set_named_property("descriptors", ctx.env.create_string(&descriptors)?)?;
+
+    let napi_obj = NapiAudioWorkletWasmModule { engine, module };
+    ctx.env.wrap(&mut js_this, napi_obj)?;
+
+    ctx.env.get_undefined()
+}
+
+// -------------------------------------------------
+// Port
+// -------------------------------------------------
+
+/// Main thread side of the messages sent to a WebAssembly processor
+struct NapiWasmProcessorPort(Sender<Vec<u8>>);
+
+#[js_function(1)]
+fn post_message(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_obj = ctx.env.unwrap::<NapiWasmProcessorPort>(&js_this)?;
+
+    let js_buffer = ctx.get::<JsBuffer>(0)?.into_value()?;
+    // the receiver is dropped with the processor
+    let _ = napi_obj.0.send(js_buffer.to_vec());
+
+    ctx.env.get_undefined()
+}
+
+// -------------------------------------------------
+// Processor
+// -------------------------------------------------
+
+/// AudioWorkletProcessor running the `awp_process` function of a WebAssembly
+/// module directly on the render thread
+///
+/// The inputs, outputs and params are copied to and from a region of the
+/// linear memory reserved with `awp_alloc` when the processor is created.
+pub(crate) struct WasmAudioWorkletProcessor {
+    store: Store<HostState>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    process: TypedFunc<(i32, i32, i32, i32, i64), i32>,
+    message: Option<TypedFunc<(i32, i32, i32), ()>>,
+    /// Value returned by `awp_create`
+    handle: i32,
+    /// Offsets of the inputs, outputs and params tables in the linear memory
+    inputs: usize,
+    outputs: usize,
+    params: usize,
+    /// Offsets of the first channel buffer of the inputs, outputs and params,
+    /// kept here so that a misbehaving module cannot redirect them
+    inputs_data: usize,
+    outputs_data: usize,
+    params_data: usize,
+    /// Names of the params, in the order of the params table
+    param_names: Vec<String>,
+    /// Messages posted on the port of the node
+    messages: Receiver<Vec<u8>>,
+    /// Max number of instructions per call, cf. `process_fuel`
+    fuel: u64,
+    /// The processor trapped, it is not called anymore
+    failed: bool,
+}
+
+impl WasmAudioWorkletProcessor {
+    /// Instantiate the `index`-th processor of the module, return the
+    /// processor and the JS object to post messages to it
+    ///
+    /// `wasm_js` holds the compiled `module`, the `index` of the processor in
+    /// the module and the `sampleRate` of the context.
+    pub fn new(
+        env: &Env,
+        wasm_js: &JsObject,
+        events: ThreadsafeFunction<ProcessorEvent>,
+        number_of_inputs: usize,
+        number_of_outputs: usize,
+        parameter_descriptors: &[AudioParamDescriptor],
+    ) -> Result<(Self, JsObject)> {
+        let js_module = wasm_js.get_named_property::<JsObject>("module")?;
+        let napi_module = env.unwrap::<NapiAudioWorkletWasmModule>(&js_module)?;
+        let index = wasm_js
+            .get_named_property::<JsNumber>("index")?
+            .get_int32()?;
+        let sample_rate = wasm_js
+            .get_named_property::<JsNumber>("sampleRate")?
+            .get_double()? as f32;
+
+        let invalid_state =
+            |err: runtime::Error| Error::from_reason(format!("InvalidStateError - {err}"));
+
+        let (mut store, instance) =
+            instantiate(&napi_module.engine, &napi_module.module, Some(events))
+                .map_err(invalid_state)?;
+
+        // exports have been checked when the module has been compiled
+        let memory = store.data().memory.unwrap();
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "awp_alloc")
+            .map_err(invalid_state)?;
+        let create = instance
+            .get_typed_func::<(i32, f32), i32>(&mut store, "awp_create")
+            .map_err(invalid_state)?;
+        let process = instance
+            .get_typed_func::<(i32, i32, i32, i32, i64), i32>(&mut store, "awp_process")
+            .map_err(invalid_state)?;
+        let message = instance
+            .get_typed_func::<(i32, i32, i32), ()>(&mut store, "awp_message")
+            .ok();
+
+        refuel(&mut store, SETUP_FUEL);
+        let handle = create
+            .call(&mut store, (index, sample_rate))
+            .map_err(invalid_state)?;
+
+        // Layout of the reserved region:
+        // | inputs table | outputs table | params table | inputs | outputs | params |
+        let number_of_params = parameter_descriptors.len();
+        let tables_size =
+            (number_of_inputs + number_of_outputs + number_of_params) * TABLE_ENTRY_SIZE;
+        let data_size =
+            (number_of_inputs + number_of_outputs) * BUS_SIZE + number_of_params * CHANNEL_SIZE;
+
+        refuel(&mut store, SETUP_FUEL);
+        let region = alloc
+            .call(&mut store, (tables_size + data_size) as i32)
+            .map_err(invalid_state)? as u32 as usize;
+
+        let inputs = region;
+        let outputs = inputs + number_of_inputs * TABLE_ENTRY_SIZE;
+        let params = outputs + number_of_outputs * TABLE_ENTRY_SIZE;
+
+        let mem = memory.data_mut(&mut store);
+
+        if region + tables_size + data_size > mem.len() {
+            return Err(Error::from_reason(
+                "InvalidStateError - 'awp_alloc' returned an out of bounds pointer",
+            ));
+        }
+
+        let inputs_data = region + tables_size;
+        let outputs_data = inputs_data + number_of_inputs * BUS_SIZE;
+        let params_data = outputs_data + number_of_outputs * BUS_SIZE;
+
+        // the data pointers of the tables never change, only the lengths are
+        // updated on each render quantum
+        for i in 0..number_of_inputs {
+            let data = inputs_data + i * BUS_SIZE;
+            write_i32(mem, inputs + i * TABLE_ENTRY_SIZE + 4, data as i32);
+        }
+
+        for i in 0..number_of_outputs {
+            let data = outputs_data + i * BUS_SIZE;
+            write_i32(mem, outputs + i * TABLE_ENTRY_SIZE + 4, data as i32);
+        }
+
+        for i in 0..number_of_params {
+            let data = params_data + i * CHANNEL_SIZE;
+            write_i32(mem, params + i * TABLE_ENTRY_SIZE + 4, data as i32);
+        }
+
+        let (send, messages) = crossbeam_channel::unbounded();
+
+        let mut js_port = env.create_object()?;
+        js_port.define_properties(&[Property::new("postMessage")?.with_method(post_message)])?;
+        env.wrap(&mut js_port, NapiWasmProcessorPort(send))?;
+
+        let processor = Self {
+            store,
+            memory,
+            alloc,
+            process,
+            message,
+            handle,
+            inputs,
+            outputs,
+            params,
+            inputs_data,
+            outputs_data,
+            params_data,
+            param_names: parameter_descriptors
+                .iter()
+                .map(|desc| desc.name.clone())
+                .collect(),
+            messages,
+            fuel: process_fuel(sample_rate),
+            failed: false,
+        };
+
+        Ok((processor, js_port))
+    }
+
+    /// Copy the message in the linear memory and hand it over to `awp_message`,
+    /// the processor owns the memory allocated for the message
+    fn deliver_message(&mut self, bytes: Vec<u8>) -> std::result::Result<(), runtime::Error> {
+        let Some(message) = &self.message else {
+            return Ok(());
+        };
+
+        refuel(&mut self.store, self.fuel);
+        let ptr = self.alloc.call(&mut self.store, bytes.len() as i32)?;
+        let start = ptr as u32 as usize;
+
+        match self
+            .memory
+            .data_mut(&mut self.store)
+            .get_mut(start..start + bytes.len())
+        {
+            Some(dst) => dst.copy_from_slice(&bytes),
+            None => return Ok(()),
+        }
+
+        refuel(&mut self.store, self.fuel);
+        message.call(&mut self.store, (self.handle, ptr, bytes.len() as i32))
+    }
+
+    fn report_error(&mut self, err: runtime::Error) {
+        self.failed = true;
+
+        let message = if runtime::is_out_of_fuel(&err) {
+            format!(
+                "processor exceeded the limit of {} instructions per call, i.e. per render quantum",
+                self.fuel
+            )
+        } else {
+            err.to_string()
+        };
+
+        if let Some(events) = &self.store.data().events {
+            let event = ProcessorEvent::Error(message);
+            events.call(Ok(event), ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    }
+}
+
+fn silence(outputs: &mut [&mut [&mut [f32]]]) {
+    outputs
+        .iter_mut()
+        .flat_map(|output| output.iter_mut())
+        .for_each(|channel| channel.fill(0.));
+}
+
+fn write_i32(mem: &mut [u8], offset: usize, value: i32) {
+    mem[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+impl AudioWorkletProcessor for WasmAudioWorkletProcessor {
+    type ProcessorOptions = WasmAudioWorkletProcessor;
+
+    fn constructor(opts: Self::ProcessorOptions) -> Self {
+        opts // the opts contain the full processor
+    }
+
+    fn parameter_descriptors() -> Vec<AudioParamDescriptor>
+    where
+        Self: Sized,
+    {
+        // Get the values out of thin air, see `audio_param_descriptor_channel()` for details
+        audio_param_descriptor_channel().recv.recv().unwrap()
+    }
+
+    fn process<'a, 'b>(
+        &mut self,
+        inputs: &'b [&'a [&'a [f32]]],
+        outputs: &'b mut [&'a mut [&'a mut [f32]]],
+        params: AudioParamValues<'b>,
+        scope: &'b AudioWorkletGlobalScope,
+    ) -> bool {
+        if self.failed {
+            silence(outputs);
+            return false;
+        }
+
+        while let Ok(bytes) = self.messages.try_recv() {
+            if let Err(err) = self.deliver_message(bytes) {
+                self.report_error(err);
+                silence(outputs);
+                return false;
+            }
+        }
+
+        let mem = self.memory.data_mut(&mut self.store);
+
+        for (i, input) in inputs.iter().enumerate() {
+            let entry = self.inputs + i * TABLE_ENTRY_SIZE;
+            let number_of_channels = input.len().min(MAX_CHANNELS);
+            write_i32(mem, entry, number_of_channels as i32);
+
+            let data = self.inputs_data + i * BUS_SIZE;
+            for (c, channel) in input.iter().take(number_of_channels).enumerate() {
+                let start = data + c * CHANNEL_SIZE;
+                mem[start..start + channel.len() * 4].copy_from_slice(to_byte_slice(channel));
+            }
+        }
+
+        for (i, output) in outputs.iter().enumerate() {
+            let entry = self.outputs + i * TABLE_ENTRY_SIZE;
+            let number_of_channels = output.len().min(MAX_CHANNELS);
+            write_i32(mem, entry, number_of_channels as i32);
+
+            let data = self.outputs_data + i * BUS_SIZE;
+            mem[data..data + number_of_channels * CHANNEL_SIZE].fill(0);
+        }
+
+        for (i, name) in self.param_names.iter().enumerate() {
+            let entry = self.params + i * TABLE_ENTRY_SIZE;
+            let values = params.get(name);
+            write_i32(mem, entry, values.len() as i32);
+
+            let data = self.params_data + i * CHANNEL_SIZE;
+            mem[data..data + values.len() * 4].copy_from_slice(to_byte_slice(&values));
+        }
+
+        refuel(&mut self.store, self.fuel);
+        let result = self.process.call(
+            &mut self.store,
+            (
+                self.handle,
+                self.inputs as i32,
+                self.outputs as i32,
+                self.params as i32,
+                scope.current_frame as i64,
+            ),
+        );
+
+        let tail_time = match result {
+            Ok(tail_time) => tail_time != 0,
+            Err(err) => {
+                self.report_error(err);
+                silence(outputs);
+                return false;
+            }
+        };
+
+        // the memory may have grown during the call
+        let mem = self.memory.data(&self.store);
+
+        for (i, output) in outputs.iter_mut().enumerate() {
+            let data = self.outputs_data + i * BUS_SIZE;
+
+            for (c, channel) in output.iter_mut().take(MAX_CHANNELS).enumerate() {
+                let start = data + c * CHANNEL_SIZE;
+                let bytes = &mem[start..start + channel.len() * 4];
+
+                for (sample, bytes) in channel.iter_mut().zip(bytes.chunks_exact(4)) {
+                    *sample = f32::from_le_bytes(bytes.try_into().unwrap());
+                }
+            }
+        }
+
+        tail_time
+    }
+}
@@ -0,0 +1,15 @@
+use napi::{CallContext, JsObject, Result};
+use napi_derive::js_function;
+
+#[js_function(0)]
+pub(crate) fn napi_enumerate_backends(ctx: CallContext) -> Result<JsObject> {
+    let list = crate::sinks::available_backends();
+
+    let mut napi_list = ctx.env.create_array(0)?;
+
+    for backend in list {
+        napi_list.insert(ctx.env.create_string(&backend)?)?;
+    }
+
+    napi_list.coerce_to_object()
+}
@@ -1,4 +1,5 @@
 use crate::media_streams::NapiMediaStream;
+use crate::sinks::{jack_input_track, JackOptions};
 
 use napi::{CallContext, Either, JsFunction, JsNumber, JsObject, JsString, Result};
 use napi_derive::js_function;
@@ -6,6 +7,7 @@ use napi_derive::js_function;
 use web_audio_api::media_devices::{
     get_user_media_sync, MediaStreamConstraints, MediaTrackConstraints,
 };
+use web_audio_api::media_streams::MediaStream;
 
 // @note: this factory pattern could be used for params as well
 // so we could expose the AudioParam ctor (for the web test suite)
@@ -54,6 +56,18 @@ pub(crate) fn napi_get_user_media(ctx: CallContext) -> Result<JsObject> {
                     constraints.channel_count = Some(channel_count);
                 }
 
+                // non standard, expose the input as the ports of a JACK client
+                if let Ok(Some(js_backend)) = js_constraints.get::<&str, JsString>("backend") {
+                    if js_backend.into_utf8()?.as_str()? == "jack" {
+                        let js_jack_options =
+                            js_constraints.get_named_property::<JsObject>("jack")?;
+                        let jack_options = JackOptions::from_js(&js_jack_options)?;
+                        let track = jack_input_track(jack_options)?;
+
+                        return wrap_stream(&ctx, MediaStream::from_tracks(vec![track]));
+                    }
+                }
+
                 MediaStreamConstraints::AudioWithConstraints(constraints)
             } else {
                 return Err(napi::Error::from_reason(
@@ -70,6 +84,10 @@ pub(crate) fn napi_get_user_media(ctx: CallContext) -> Result<JsObject> {
 
     // create rust stream
     let stream = get_user_media_sync(options);
+    wrap_stream(&ctx, stream)
+}
+
+fn wrap_stream(ctx: &CallContext, stream: MediaStream) -> Result<JsObject> {
     let napi_stream = NapiMediaStream::new(stream);
     // retrieve the JS ctor and create a new instance
     let store_ref: &mut napi::Ref<()> = ctx.env.get_instance_data()?.unwrap();
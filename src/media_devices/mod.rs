@@ -3,3 +3,6 @@ pub(crate) use enumerate_devices::napi_enumerate_devices;
 
 mod get_user_media;
 pub(crate) use get_user_media::napi_get_user_media;
+
+mod enumerate_backends;
+pub(crate) use enumerate_backends::napi_enumerate_backends;
@@ -1,4 +1,5 @@
 use std::io::Cursor;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use napi::threadsafe_function::{ThreadSafeCallContext, ThreadsafeFunctionCallMode};
@@ -7,11 +8,17 @@ use napi_derive::js_function;
 use web_audio_api::context::*;
 use web_audio_api::{Event, OfflineAudioCompletionEvent};
 
+use crate::audio_worklet_node::{allocate_process_call_channel, ProcessCallChannelGuard};
 use crate::*;
 
-/// Napi object wrapping the native OfflineAudioContext and the AudioWorklet ID
+/// Napi object wrapping the native OfflineAudioContext, the AudioWorklet channels
+/// and the number of times the render loop has been resumed after a suspension
 #[derive(Clone)]
-pub(crate) struct NapiOfflineAudioContext(Arc<OfflineAudioContext>, usize);
+pub(crate) struct NapiOfflineAudioContext(
+    Arc<OfflineAudioContext>,
+    Arc<Vec<ProcessCallChannelGuard>>,
+    Arc<AtomicU64>,
+);
 
 // // for debug purpose
 // impl Drop for NapiOfflineAudioContext {
@@ -36,8 +43,13 @@ impl NapiOfflineAudioContext {
         &self.0
     }
 
-    pub fn worklet_id(&self) -> usize {
-        self.1
+    /// IDs of the channels of the AudioWorklet threads
+    pub fn worklet_ids(&self) -> Vec<u64> {
+        self.1.iter().map(|channel| channel.worklet_id()).collect()
+    }
+
+    pub fn resume_count(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.2)
     }
 }
 
@@ -53,12 +65,19 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     let sample_rate = ctx.get::<JsNumber>(2)?.get_double()? as f32;
 
     let audio_context = OfflineAudioContext::new(number_of_channels, length, sample_rate);
-    let worklet_id = crate::audio_worklet_node::allocate_process_call_channel();
+    // processors are not pipelined when rendering offline, a single Worker
+    // runs them all
+    let worklet_channel = allocate_process_call_channel(None);
+    let worklet_id = worklet_channel.worklet_id();
 
     // -------------------------------------------------
     // Wrap context
     // -------------------------------------------------
-    let napi_audio_context = NapiOfflineAudioContext(Arc::new(audio_context), worklet_id);
+    let napi_audio_context = NapiOfflineAudioContext(
+        Arc::new(audio_context),
+        Arc::new(vec![worklet_channel]),
+        Arc::new(AtomicU64::new(0)),
+    );
     ctx.env.wrap(&mut js_this, napi_audio_context)?;
 
     js_this.define_properties(&[
@@ -69,7 +88,7 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     ])?;
 
     // -------------------------------------------------
-    // Bind AudioDestination - requires Symbol.toStringTag
+    // Bind AudioDestination and AudioRenderCapacity - requires Symbol.toStringTag
     // -------------------------------------------------
     let store_ref: &mut napi::Ref<()> = ctx.env.get_instance_data()?.unwrap();
     let store: JsObject = ctx.env.get_reference_value(store_ref)?;
@@ -77,8 +96,14 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     let js_obj = ctor.new_instance(&[&js_this])?;
     js_this.set_named_property("destination", &js_obj)?;
 
-    // internal id to retrieve worklet message channel
-    js_this.set_named_property("workletId", ctx.env.create_uint32(worklet_id as u32)?)?;
+    let ctor: JsFunction = store.get_named_property("AudioRenderCapacity")?;
+    let js_obj = ctor.new_instance(&[&js_this])?;
+    js_this.set_named_property("renderCapacity", &js_obj)?;
+
+    // internal ids to retrieve worklet message channels
+    let mut js_worklet_ids = ctx.env.create_array_with_length(1)?;
+    js_worklet_ids.set_element(0, ctx.env.create_int64(worklet_id as i64)?)?;
+    js_this.set_named_property("workletIds", js_worklet_ids)?;
 
     ctx.env.get_undefined()
 }
@@ -163,10 +188,16 @@ fn start_rendering(ctx: CallContext) -> Result<JsObject> {
 
     // everything is setup, do "real" rendering job
     let context_clone = Arc::clone(&napi_context.0);
+    let worklet_channels_clone = Arc::clone(&napi_context.1);
 
     ctx.env.execute_tokio_future(
         async move {
             let audio_buffer = context_clone.start_rendering().await;
+            // the context can't render anymore, its channel can be reused by
+            // another context
+            worklet_channels_clone
+                .iter()
+                .for_each(|channel| channel.release());
             Ok(audio_buffer)
         },
         |&mut env, audio_buffer| {
@@ -187,6 +218,9 @@ fn resume(ctx: CallContext) -> Result<JsObject> {
     let napi_context = ctx.env.unwrap::<NapiOfflineAudioContext>(&js_this)?;
     let context_clone = Arc::clone(&napi_context.0);
 
+    // the time spent suspended must not be accounted as render time
+    napi_context.2.fetch_add(1, Ordering::Relaxed);
+
     ctx.env.execute_tokio_future(
         async move {
             context_clone.resume().await;
@@ -0,0 +1,219 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use web_audio_api::context::BaseAudioContext;
+use web_audio_api::node::{AudioNode, AudioNodeOptions, ChannelCountMode, ChannelInterpretation};
+use web_audio_api::worklet::{
+    AudioParamValues, AudioWorkletGlobalScope, AudioWorkletNode, AudioWorkletNodeOptions,
+    AudioWorkletProcessor,
+};
+use web_audio_api::AudioRenderCapacityOptions;
+
+use crate::audio_render_capacity::RenderCapacityUpdate;
+
+use crate::offline_audio_context::NapiOfflineAudioContext;
+
+const RENDER_QUANTUM_SIZE: usize = 128;
+
+type UpdateCallback = Box<dyn FnMut(RenderCapacityUpdate) + Send>;
+
+/// State shared between an `OfflineRenderCapacity` and its probe
+#[derive(Default)]
+struct CapacityState {
+    /// Number of render quanta per update, 0 when stopped
+    update_quanta: AtomicU64,
+    /// Set by `start` so that the probe drops the values already collected
+    restart: AtomicBool,
+    onupdate: Mutex<Option<UpdateCallback>>,
+}
+
+/// Render thread side of an `OfflineRenderCapacity`
+///
+/// As the offline render loop never waits for an audio device, the wall time
+/// between two calls of the probe is the time spent to render one quantum.
+struct CapacityProbe {
+    state: Arc<CapacityState>,
+    /// Incremented by the context when the render loop is resumed after a
+    /// suspension, in which case the next measure must be discarded
+    resume_count: Arc<AtomicU64>,
+    last_resume_count: u64,
+    /// Length of the context in sample-frames
+    length: u64,
+    last_call: Option<Instant>,
+    timestamp: f64,
+    count: u64,
+    load_sum: f64,
+    peak_load: f64,
+    overruns: u64,
+}
+
+impl CapacityProbe {
+    fn reset(&mut self) {
+        self.count = 0;
+        self.load_sum = 0.;
+        self.peak_load = 0.;
+        self.overruns = 0;
+    }
+
+    fn dispatch(&mut self) {
+        let count = self.count as f64;
+
+        // same precision as the realtime AudioRenderCapacity of the upstream crate
+        let event = RenderCapacityUpdate {
+            timestamp: self.timestamp,
+            average_load: (self.load_sum / count * 100.).round() / 100.,
+            peak_load: (self.peak_load * 100.).round() / 100.,
+            underrun_ratio: (self.overruns as f64 / count * 100.).ceil() / 100.,
+        };
+
+        if let Some(callback) = self.state.onupdate.lock().unwrap().as_mut() {
+            callback(event);
+        }
+
+        self.reset();
+    }
+}
+
+impl AudioWorkletProcessor for CapacityProbe {
+    type ProcessorOptions = CapacityProbe;
+
+    fn constructor(opts: Self::ProcessorOptions) -> Self {
+        opts // the opts contain the full processor
+    }
+
+    fn process<'a, 'b>(
+        &mut self,
+        _inputs: &'b [&'a [&'a [f32]]],
+        _outputs: &'b mut [&'a mut [&'a mut [f32]]],
+        _params: AudioParamValues<'b>,
+        scope: &'b AudioWorkletGlobalScope,
+    ) -> bool {
+        let now = Instant::now();
+        let last_call = self.last_call.replace(now);
+
+        if self.state.restart.swap(false, Ordering::Relaxed) {
+            self.reset();
+        }
+
+        let resume_count = self.resume_count.load(Ordering::Relaxed);
+        let discontinuity = resume_count != self.last_resume_count;
+        self.last_resume_count = resume_count;
+        let update_quanta = self.state.update_quanta.load(Ordering::Relaxed);
+
+        let last_call = match last_call {
+            Some(last_call) if !discontinuity && update_quanta > 0 => last_call,
+            // keep the processor alive even if nothing is measured
+            _ => return true,
+        };
+
+        let quantum_duration = RENDER_QUANTUM_SIZE as f64 / scope.sample_rate as f64;
+        let load = now.duration_since(last_call).as_secs_f64() / quantum_duration;
+
+        if self.count == 0 {
+            self.timestamp = scope.current_time;
+        }
+
+        self.count += 1;
+        self.load_sum += load;
+        self.peak_load = self.peak_load.max(load);
+
+        // this quantum could not have been rendered in time by a realtime context
+        if load > 1. {
+            self.overruns += 1;
+        }
+
+        let finished = scope.current_frame + RENDER_QUANTUM_SIZE as u64 >= self.length;
+
+        if self.count >= update_quanta || finished {
+            self.dispatch();
+        }
+
+        // release the JS handler as the context can't render anymore
+        if finished {
+            self.state.onupdate.lock().unwrap().take();
+        }
+
+        true
+    }
+}
+
+/// Render capacity of an OfflineAudioContext
+///
+/// Mirrors the API of the upstream `AudioRenderCapacity`, which only exists
+/// for realtime contexts. The load of a quantum is its rendering wall time
+/// divided by its duration in audio time, and the update interval is expressed
+/// in audio time too, i.e. in terms of the `currentTime` of the context.
+pub(crate) struct OfflineRenderCapacity {
+    context: NapiOfflineAudioContext,
+    state: Arc<CapacityState>,
+    probe: Option<AudioWorkletNode>,
+}
+
+impl OfflineRenderCapacity {
+    pub fn new(context: NapiOfflineAudioContext) -> Self {
+        Self {
+            context,
+            state: Arc::new(CapacityState::default()),
+            probe: None,
+        }
+    }
+
+    pub fn start(&mut self, options: AudioRenderCapacityOptions) {
+        let context = self.context.unwrap();
+
+        // the probe is created lazily so that it costs nothing unless used
+        if self.probe.is_none() {
+            let processor_options = CapacityProbe {
+                state: Arc::clone(&self.state),
+                resume_count: self.context.resume_count(),
+                last_resume_count: 0,
+                length: context.length() as u64,
+                last_call: None,
+                timestamp: 0.,
+                count: 0,
+                load_sum: 0.,
+                peak_load: 0.,
+                overruns: 0,
+            };
+
+            let options = AudioWorkletNodeOptions {
+                number_of_inputs: 1,
+                number_of_outputs: 0,
+                output_channel_count: vec![],
+                parameter_data: Default::default(),
+                audio_node_options: AudioNodeOptions {
+                    channel_count: 1,
+                    channel_count_mode: ChannelCountMode::Explicit,
+                    channel_interpretation: ChannelInterpretation::Discrete,
+                },
+                processor_options,
+            };
+
+            let probe = AudioWorkletNode::new::<CapacityProbe>(context, options);
+            context.destination().connect(&probe);
+            self.probe = Some(probe);
+        }
+
+        let quanta =
+            options.update_interval * context.sample_rate() as f64 / RENDER_QUANTUM_SIZE as f64;
+
+        self.state
+            .update_quanta
+            .store(quanta.round().max(1.) as u64, Ordering::Relaxed);
+        self.state.restart.store(true, Ordering::Relaxed);
+    }
+
+    pub fn stop(&mut self) {
+        self.state.update_quanta.store(0, Ordering::Relaxed);
+        self.state.onupdate.lock().unwrap().take();
+    }
+
+    pub fn set_onupdate<F: FnMut(RenderCapacityUpdate) + Send + 'static>(&self, callback: F) {
+        self.state
+            .onupdate
+            .lock()
+            .unwrap()
+            .replace(Box::new(callback));
+    }
+}
@@ -1,3 +1,6 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
 use napi::*;
 use napi_derive::js_function;
 use web_audio_api::node::*;
@@ -10,13 +13,36 @@ use crate::utils::{
 };
 use crate::*;
 
-pub(crate) struct NapiScriptProcessorNode(ScriptProcessorNode);
+/// Max number of `audioprocess` events waiting for the JS thread when events
+/// are dropped on overflow, i.e. one event can be queued while the handler
+/// processes the previous one
+const MAX_PENDING_EVENTS: usize = 1;
+
+/// What to do with the `audioprocess` events when the JS thread lags behind
+#[derive(Clone, Copy, PartialEq)]
+enum OverflowPolicy {
+    /// Queue all the events, the latency grows with the backlog
+    Block,
+    /// Drop the events that cannot be queued, their output buffer is silent
+    Drop,
+}
+
+pub(crate) struct NapiScriptProcessorNode(
+    ScriptProcessorNode,
+    OverflowPolicy,
+    // number of dropped events
+    Arc<AtomicU64>,
+    Option<ThreadsafeFunctionPatched<AudioProcessingEvent>>,
+);
 
 impl NapiScriptProcessorNode {
     pub fn create_js_class(env: &Env) -> Result<JsFunction> {
         let interface = audio_node_interface![
             Property::new("bufferSize")?.with_getter(get_buffer_size),
-            Property::new("listen_to_events")?.with_method(listen_to_events)
+            Property::new("droppedEvents")?.with_getter(get_dropped_events),
+            Property::new("listen_to_events")?.with_method(listen_to_events),
+            Property::new("ref")?.with_method(refer),
+            Property::new("unref")?.with_method(unref)
         ];
 
         env.define_class("ScriptProcessorNode", constructor, &interface)
@@ -54,6 +80,13 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
         .unwrap()
         .get_double()? as usize;
 
+    let overflow_js = js_options.get::<&str, JsString>("overflow")?.unwrap();
+    let overflow = match &overflow_js.into_utf8()?.into_owned()?[..] {
+        "block" => OverflowPolicy::Block,
+        "drop" => OverflowPolicy::Drop,
+        _ => unreachable!(),
+    };
+
     // --------------------------------------------------------
     // Create AudioBufferSourceOptions object
     // --------------------------------------------------------
@@ -101,7 +134,7 @@ fn constructor(ctx: CallContext) -> Result<JsUndefined> {
     ])?;
 
     // finalize instance creation
-    let napi_node = NapiScriptProcessorNode(native_node);
+    let napi_node = NapiScriptProcessorNode(native_node, overflow, Arc::default(), None);
     ctx.env.wrap(&mut js_this, napi_node)?;
 
     ctx.env.get_undefined()
@@ -124,20 +157,37 @@ fn get_buffer_size(ctx: CallContext) -> Result<JsNumber> {
     ctx.env.create_double(buffer_size)
 }
 
+#[js_function]
+fn get_dropped_events(ctx: CallContext) -> Result<JsNumber> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_node = ctx.env.unwrap::<NapiScriptProcessorNode>(&js_this)?;
+
+    let dropped_events = napi_node.2.load(Ordering::Relaxed) as f64;
+
+    ctx.env.create_double(dropped_events)
+}
+
 #[js_function]
 fn listen_to_events(ctx: CallContext) -> Result<JsUndefined> {
     let js_this = ctx.this_unchecked::<JsObject>();
     let napi_node = ctx.env.unwrap::<NapiScriptProcessorNode>(&js_this)?;
     let node = napi_node.unwrap();
+    let overflow = napi_node.1;
+    let dropped_events = Arc::clone(&napi_node.2);
+
+    let max_queue_size = match overflow {
+        OverflowPolicy::Block => 0,
+        OverflowPolicy::Drop => MAX_PENDING_EVENTS,
+    };
 
     let k_onaudioprocess =
         crate::utils::get_symbol_for(ctx.env, "node-web-audio-api:onaudioprocess");
     let audioprocess_cb: JsFunction = js_this.get_property(k_onaudioprocess).unwrap();
 
-    let audioprocess_tsfn = ThreadsafeFunctionPatched::create(
+    let mut audioprocess_tsfn = ThreadsafeFunctionPatched::create(
         ctx.env.raw(),
         unsafe { audioprocess_cb.raw() },
-        0,
+        max_queue_size,
         move |ctx: ThreadSafeCallContextPatched<AudioProcessingEvent>| {
             let mut event = ctx.value;
 
@@ -187,12 +237,52 @@ fn listen_to_events(ctx: CallContext) -> Result<JsUndefined> {
         },
     )?;
 
-    // not implemented in threadsafe patched version
-    // let _ = audioprocess_tsfn.unref(ctx.env);
+    // unref tsfn so they do not prevent the process to exit
+    let _ = audioprocess_tsfn.unref(ctx.env);
 
-    node.set_onaudioprocess(move |e| {
-        audioprocess_tsfn.call(e, ThreadsafeFunctionCallModePatched::Blocking);
+    let tsfn = audioprocess_tsfn.clone();
+
+    node.set_onaudioprocess(move |e| match overflow {
+        OverflowPolicy::Block => {
+            tsfn.call(e, ThreadsafeFunctionCallModePatched::Blocking);
+        }
+        OverflowPolicy::Drop => {
+            // the event is dropped if it cannot be queued, which ships its
+            // silent output buffer back to the render thread
+            let status = tsfn.call(e, ThreadsafeFunctionCallModePatched::NonBlocking);
+
+            if status == Status::QueueFull {
+                dropped_events.fetch_add(1, Ordering::Relaxed);
+            }
+        }
     });
 
+    napi_node.3 = Some(audioprocess_tsfn);
+
+    ctx.env.get_undefined()
+}
+
+// Keep the process alive as long as the node exists
+#[js_function]
+fn refer(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_node = ctx.env.unwrap::<NapiScriptProcessorNode>(&js_this)?;
+
+    if let Some(audioprocess_tsfn) = napi_node.3.as_mut() {
+        audioprocess_tsfn.refer(ctx.env)?;
+    }
+
+    ctx.env.get_undefined()
+}
+
+#[js_function]
+fn unref(ctx: CallContext) -> Result<JsUndefined> {
+    let js_this = ctx.this_unchecked::<JsObject>();
+    let napi_node = ctx.env.unwrap::<NapiScriptProcessorNode>(&js_this)?;
+
+    if let Some(audioprocess_tsfn) = napi_node.3.as_mut() {
+        audioprocess_tsfn.unref(ctx.env)?;
+    }
+
     ctx.env.get_undefined()
 }
@@ -0,0 +1,85 @@
+use std::sync::atomic::Ordering;
+
+use napi::threadsafe_function::{ThreadSafeCallContext, ThreadsafeFunctionCallMode};
+use napi::{Env, JsFunction, JsObject, Result, Status, TypedArrayType};
+
+use crate::sinks::{Tap, TapPump};
+
+/// Max number of blocks waiting to be pulled by the JS callback, further
+/// blocks are dropped
+const MAX_PENDING_BLOCKS: usize = 32;
+
+/// Split interleaved samples into one `Vec` per channel
+pub(crate) fn deinterleave(samples: &[f32], number_of_channels: usize) -> Vec<Vec<f32>> {
+    (0..number_of_channels)
+        .map(|c| {
+            samples
+                .iter()
+                .skip(c)
+                .step_by(number_of_channels)
+                .copied()
+                .collect()
+        })
+        .collect()
+}
+
+/// Convert planar samples into an array of Float32Array
+pub(crate) fn planar_to_js(env: &Env, channels: &[Vec<f32>]) -> Result<JsObject> {
+    let mut js_channels = env.create_array_with_length(channels.len())?;
+
+    for (index, channel) in channels.iter().enumerate() {
+        let length = channel.len();
+        let arr_u8 = crate::utils::to_byte_slice(channel);
+        let js_channel = env
+            .create_arraybuffer_with_data(arr_u8.to_vec())?
+            .into_raw()
+            .into_typedarray(TypedArrayType::Float32, length, 0)?;
+
+        js_channels.set_element(index as u32, js_channel)?;
+    }
+
+    Ok(js_channels)
+}
+
+/// Sink that hands planar blocks of rendered frames to a user defined JS callback
+pub(crate) struct CustomSink;
+
+impl CustomSink {
+    pub fn listen(
+        &self,
+        env: &Env,
+        js_context: &JsObject,
+        tap: &mut Tap,
+        block_size: usize,
+    ) -> Result<TapPump> {
+        let k_onsinkdata = crate::utils::get_symbol_for(env, "node-web-audio-api:onsinkdata");
+        let sinkdata_cb: JsFunction = js_context.get_property(k_onsinkdata)?;
+        let mut sinkdata_tsfn = env.create_threadsafe_function(
+            &sinkdata_cb,
+            MAX_PENDING_BLOCKS,
+            |ctx: ThreadSafeCallContext<Vec<Vec<f32>>>| {
+                Ok(vec![planar_to_js(&ctx.env, &ctx.value)?])
+            },
+        )?;
+
+        // unref tsfn so they do not prevent the process to exit
+        let _ = sinkdata_tsfn.unref(env);
+
+        let number_of_channels = tap.number_of_channels();
+        let dropped_frames = tap.dropped_frames();
+
+        let pump = tap.pump(block_size, move |samples| {
+            let number_of_frames = samples.len() / number_of_channels;
+            let channels = deinterleave(samples, number_of_channels);
+
+            let status = sinkdata_tsfn.call(Ok(channels), ThreadsafeFunctionCallMode::NonBlocking);
+
+            // JS is not pulling the blocks fast enough
+            if status == Status::QueueFull {
+                dropped_frames.fetch_add(number_of_frames as u64, Ordering::Relaxed);
+            }
+        });
+
+        Ok(pump)
+    }
+}
@@ -0,0 +1,362 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use napi::Result;
+
+use crate::sinks::{
+    is_jack_available, DeviceError, DriftResampler, PlayoutStats, Tap, TapPump, UnderrunTracker,
+};
+
+/// Name of the ALSA device routing to PulseAudio (or to PipeWire through its
+/// PulseAudio compatibility layer)
+const PULSE_ALSA_DEVICE: &str = "pulse";
+
+/// Number of render quanta kept buffered between the render thread and the
+/// device, which absorbs the jitter of the clock of the render thread
+const PREBUFFER_QUANTA: usize = 4;
+
+/// Name of all the audio backends that can be used by an AudioContext, the
+/// `default` backend uses the audio device selected by the upstream crate
+pub(crate) fn available_backends() -> Vec<String> {
+    let mut backends = vec![String::from("default")];
+
+    // JACK is handled by its own client, cf. sinks/jack.rs
+    if is_jack_available() {
+        backends.push(String::from("jack"));
+    }
+
+    for host_id in cpal::available_hosts() {
+        backends.push(host_id.name().to_lowercase());
+
+        if host_id.name() == "ALSA" && find_alsa_pulse_device().is_some() {
+            backends.push(String::from("pulse"));
+        }
+    }
+
+    backends
+}
+
+fn find_alsa_pulse_device() -> Option<cpal::Device> {
+    let host = cpal::host_from_id(find_host_id("alsa")?).ok()?;
+    let mut devices = host.output_devices().ok()?;
+    devices.find(|d| d.name().map(|n| n == PULSE_ALSA_DEVICE).unwrap_or(false))
+}
+
+pub(crate) fn find_host_id(backend: &str) -> Option<cpal::HostId> {
+    cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name().to_lowercase() == backend)
+}
+
+pub(crate) fn not_supported(msg: String) -> napi::Error {
+    napi::Error::from_reason(format!("NotSupportedError - {msg}"))
+}
+
+/// Resolve the output device from the backend name and the sinkId, an empty
+/// sinkId selects the default output device of the backend
+fn find_output_device(backend: &str, sink_id: &str) -> Result<cpal::Device> {
+    if backend == "pulse" {
+        return find_alsa_pulse_device()
+            .ok_or_else(|| not_supported(String::from("Audio backend 'pulse' is not available")));
+    }
+
+    let host_id = find_host_id(backend).ok_or_else(|| {
+        not_supported(format!(
+            "Audio backend '{backend}' is not available, available backends are: {}",
+            available_backends().join(", ")
+        ))
+    })?;
+
+    let host = cpal::host_from_id(host_id)
+        .map_err(|err| not_supported(format!("Audio backend '{backend}': {err}")))?;
+
+    let device = if sink_id.is_empty() {
+        host.default_output_device()
+    } else {
+        host.output_devices()
+            .map_err(|err| not_supported(format!("Audio backend '{backend}': {err}")))?
+            .find(|d| d.name().map(|n| n == sink_id).unwrap_or(false))
+    };
+
+    device.ok_or_else(|| {
+        let msg = if sink_id.is_empty() {
+            format!("No default output device found for audio backend '{backend}'")
+        } else {
+            format!("No output device '{sink_id}' found for audio backend '{backend}'")
+        };
+
+        napi::Error::from_reason(format!("NotFoundError - {msg}"))
+    })
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut consumer: rtrb::Consumer<f32>,
+    device_error: DeviceError,
+    playout_stats: Arc<PlayoutStats>,
+    mut resampler: DriftResampler,
+) -> std::result::Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let number_of_channels = config.channels as usize;
+    let sample_rate = config.sample_rate.0 as f64;
+    let mut underruns = UnderrunTracker::default();
+
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], info: &cpal::OutputCallbackInfo| {
+            let number_of_frames = data.len() / number_of_channels;
+
+            // frames waiting in the ring buffer plus the latency of the device
+            let buffered = consumer.slots() / number_of_channels;
+            let timestamp = info.timestamp();
+            let device_latency = timestamp
+                .playback
+                .duration_since(&timestamp.callback)
+                .unwrap_or_default();
+
+            // the render thread runs on the clock of the "none" sink, which
+            // drifts from the clock of the device
+            let missing = resampler.process(&mut consumer, data);
+            underruns.record(&playout_stats, number_of_frames, missing);
+
+            if missing < number_of_frames {
+                playout_stats.record_latency(
+                    Duration::from_secs_f64(buffered as f64 / sample_rate) + device_latency,
+                );
+            }
+        },
+        move |err| match err {
+            cpal::StreamError::DeviceNotAvailable => device_error.report(err.to_string()),
+            _ => device_error.notify(format!("Output stream error: {err}")),
+        },
+        None,
+    )
+}
+
+/// Sink that plays the rendered frames on an audio device of the given backend
+///
+/// The render thread runs on the clock of the `"none"` sink, the frames are
+/// resampled to follow the clock of the device rather than relying on both
+/// clocks running at the same pace.
+///
+/// The device is entirely handled in a dedicated thread as `cpal` streams are
+/// not `Send` on all platforms.
+pub(crate) struct DeviceSink {
+    backend: String,
+    sink_id: String,
+    sample_rate: f32,
+    number_of_channels: usize,
+    consumer_send: Option<mpsc::Sender<(rtrb::Consumer<f32>, Arc<PlayoutStats>)>>,
+    running: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+    device_error: DeviceError,
+}
+
+impl DeviceSink {
+    /// Open the output device, called before the context is created as it
+    /// defines the sample rate and the number of channels of the sink
+    pub fn open(backend: String, sink_id: String, sample_rate: Option<f32>) -> Result<Self> {
+        Self::spawn(backend, sink_id, sample_rate, None)
+    }
+
+    /// Open an output device that mirrors the output of a running context
+    ///
+    /// The device keeps its own sample rate and clock, the frames rendered at
+    /// `context_sample_rate` are resampled with a drift compensating resampler
+    /// that keeps `latency` seconds of audio buffered.
+    pub fn open_mirror(
+        backend: String,
+        sink_id: String,
+        context_sample_rate: f32,
+        latency: f64,
+    ) -> Result<Self> {
+        Self::spawn(backend, sink_id, None, Some((context_sample_rate, latency)))
+    }
+
+    /// Open an output device for a running context, e.g. once its previous
+    /// device has been lost
+    ///
+    /// As with mirrors, the device keeps its own sample rate and the frames
+    /// rendered at `context_sample_rate` are resampled.
+    pub fn reopen(backend: String, sink_id: String, context_sample_rate: f32) -> Result<Self> {
+        let latency = (PREBUFFER_QUANTA * 128) as f64 / context_sample_rate as f64;
+        Self::open_mirror(backend, sink_id, context_sample_rate, latency)
+    }
+
+    fn spawn(
+        backend: String,
+        sink_id: String,
+        sample_rate: Option<f32>,
+        mirror: Option<(f32, f64)>,
+    ) -> Result<Self> {
+        let (config_send, config_recv) = mpsc::channel();
+        let (consumer_send, consumer_recv) =
+            mpsc::channel::<(rtrb::Consumer<f32>, Arc<PlayoutStats>)>();
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = Arc::clone(&running);
+        let device_error = DeviceError::default();
+        let device_error_clone = device_error.clone();
+        let (backend_clone, sink_id_clone) = (backend.clone(), sink_id.clone());
+
+        let handle = thread::spawn(move || {
+            let res = find_output_device(&backend, &sink_id).and_then(|device| {
+                let supported = device
+                    .default_output_config()
+                    .map_err(|err| not_supported(format!("Audio backend '{backend}': {err}")))?;
+
+                let mut config = supported.config();
+                // the "none" render thread handles at most 32 channels
+                config.channels = config.channels.min(32);
+
+                if let Some(sample_rate) = sample_rate {
+                    config.sample_rate = cpal::SampleRate(sample_rate as u32);
+                }
+
+                Ok((device, config, supported.sample_format()))
+            });
+
+            let (device, config, sample_format) = match res {
+                Ok(value) => value,
+                Err(err) => {
+                    let _ = config_send.send(Err(err));
+                    return;
+                }
+            };
+
+            let _ = config_send.send(Ok((config.sample_rate.0 as f32, config.channels as usize)));
+
+            // wait for the tap of the context
+            let (consumer, playout_stats) = match consumer_recv.recv() {
+                Ok(value) => value,
+                Err(_) => return,
+            };
+
+            // a sink renders at the sample rate of the device, a mirror at the
+            // sample rate of the context it mirrors
+            let (input_sample_rate, target_fill) = match mirror {
+                Some((context_sample_rate, latency)) => (
+                    context_sample_rate,
+                    (latency * context_sample_rate as f64) as usize,
+                ),
+                None => (config.sample_rate.0 as f32, PREBUFFER_QUANTA * 128),
+            };
+
+            let resampler = DriftResampler::new(
+                config.channels as usize,
+                input_sample_rate,
+                config.sample_rate.0 as f32,
+                target_fill,
+            );
+
+            let stream = match sample_format {
+                cpal::SampleFormat::F32 => build_stream::<f32>(
+                    &device,
+                    &config,
+                    consumer,
+                    device_error_clone.clone(),
+                    playout_stats,
+                    resampler,
+                ),
+                cpal::SampleFormat::I16 => build_stream::<i16>(
+                    &device,
+                    &config,
+                    consumer,
+                    device_error_clone.clone(),
+                    playout_stats,
+                    resampler,
+                ),
+                cpal::SampleFormat::U16 => build_stream::<u16>(
+                    &device,
+                    &config,
+                    consumer,
+                    device_error_clone.clone(),
+                    playout_stats,
+                    resampler,
+                ),
+                cpal::SampleFormat::I32 => build_stream::<i32>(
+                    &device,
+                    &config,
+                    consumer,
+                    device_error_clone.clone(),
+                    playout_stats,
+                    resampler,
+                ),
+                format => {
+                    device_error_clone.report(format!("Unsupported sample format: {format}"));
+                    return;
+                }
+            };
+
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    device_error_clone.report(format!("Failed to build output stream: {err}"));
+                    return;
+                }
+            };
+
+            if let Err(err) = stream.play() {
+                device_error_clone.report(format!("Failed to start output stream: {err}"));
+                return;
+            }
+
+            while running_clone.load(Ordering::Acquire) {
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            drop(stream);
+        });
+
+        match config_recv.recv() {
+            Ok(Ok((sample_rate, number_of_channels))) => Ok(Self {
+                backend: backend_clone,
+                sink_id: sink_id_clone,
+                sample_rate,
+                number_of_channels,
+                consumer_send: Some(consumer_send),
+                running,
+                handle: Some(handle),
+                device_error,
+            }),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(not_supported(String::from("Failed to open output device"))),
+        }
+    }
+
+    pub fn backend(&self) -> &str {
+        &self.backend
+    }
+
+    pub fn sink_id(&self) -> &str {
+        &self.sink_id
+    }
+
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    pub fn number_of_channels(&self) -> usize {
+        self.number_of_channels
+    }
+
+    pub fn device_error(&self) -> DeviceError {
+        self.device_error.clone()
+    }
+
+    /// Start the output stream fed by the given tap
+    pub fn listen(&mut self, tap: &mut Tap, playout_stats: Arc<PlayoutStats>) -> TapPump {
+        if let Some(consumer_send) = self.consumer_send.take() {
+            let _ = consumer_send.send((tap.take_consumer(), playout_stats));
+        }
+
+        TapPump::new(Arc::clone(&self.running), self.handle.take())
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs::File;
+use std::io::BufWriter;
+
+use napi::Result;
+
+use crate::sinks::{f32_to_i16, DeviceError, FlacWriter, SampleFormat, Tap, TapPump};
+
+/// Update the file header every few seconds so that long recordings remain
+/// readable if the process is killed before the context is closed
+const HEADER_UPDATE_INTERVAL_SECS: usize = 5;
+
+/// Container of the file sink
+#[derive(Clone, Copy)]
+pub(crate) enum FileFormat {
+    Wav,
+    Flac,
+}
+
+impl FileFormat {
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "wav" => FileFormat::Wav,
+            "flac" => FileFormat::Flac,
+            _ => unreachable!(),
+        }
+    }
+}
+
+enum FileWriter {
+    Wav(hound::WavWriter<BufWriter<File>>, SampleFormat),
+    Flac(FlacWriter),
+}
+
+impl FileWriter {
+    fn write(&mut self, samples: &[f32]) -> std::result::Result<(), String> {
+        match self {
+            FileWriter::Wav(writer, SampleFormat::F32) => samples
+                .iter()
+                .try_for_each(|s| writer.write_sample(*s))
+                .map_err(|err| err.to_string()),
+            FileWriter::Wav(writer, SampleFormat::S16) => samples
+                .iter()
+                .try_for_each(|s| writer.write_sample(f32_to_i16(*s)))
+                .map_err(|err| err.to_string()),
+            FileWriter::Flac(writer) => {
+                writer.write_samples(samples).map_err(|err| err.to_string())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::result::Result<(), String> {
+        match self {
+            FileWriter::Wav(writer, _) => writer.flush().map_err(|err| err.to_string()),
+            FileWriter::Flac(writer) => writer.flush().map_err(|err| err.to_string()),
+        }
+    }
+
+    fn finalize(self) -> std::result::Result<(), String> {
+        match self {
+            FileWriter::Wav(writer, _) => writer.finalize().map_err(|err| err.to_string()),
+            FileWriter::Flac(writer) => writer.finalize().map_err(|err| err.to_string()),
+        }
+    }
+}
+
+/// Owned by the pump, finalizes the file once all pending frames are written
+struct FileRecorder {
+    writer: Option<FileWriter>,
+    error: DeviceError,
+}
+
+impl FileRecorder {
+    fn fail(&mut self, err: String) {
+        self.writer = None;
+        self.error
+            .report(format!("Failed to write into file sink: {err}"));
+    }
+}
+
+impl Drop for FileRecorder {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            if let Err(err) = writer.finalize() {
+                self.fail(err);
+            }
+        }
+    }
+}
+
+/// Sink that records everything reaching the destination into a WAV or a
+/// FLAC file
+///
+/// Write errors are reported as a device error, i.e. the context dispatches
+/// an `error` event.
+pub(crate) struct FileSink {
+    pub path: String,
+    pub format: FileFormat,
+    pub sample_format: SampleFormat,
+    writer: Option<FileWriter>,
+    error: DeviceError,
+}
+
+impl FileSink {
+    pub fn new(path: String, format: FileFormat, sample_format: SampleFormat) -> Self {
+        Self {
+            path,
+            format,
+            sample_format,
+            writer: None,
+            error: DeviceError::default(),
+        }
+    }
+
+    pub fn device_error(&self) -> DeviceError {
+        self.error.clone()
+    }
+
+    /// Create the file, called at context instantiation so that errors are
+    /// reported synchronously
+    pub fn open(&mut self, sample_rate: f32, number_of_channels: usize) -> Result<()> {
+        let writer = match self.format {
+            FileFormat::Wav => {
+                let spec = match self.sample_format {
+                    SampleFormat::F32 => hound::WavSpec {
+                        channels: number_of_channels as u16,
+                        sample_rate: sample_rate as u32,
+                        bits_per_sample: 32,
+                        sample_format: hound::SampleFormat::Float,
+                    },
+                    SampleFormat::S16 => hound::WavSpec {
+                        channels: number_of_channels as u16,
+                        sample_rate: sample_rate as u32,
+                        bits_per_sample: 16,
+                        sample_format: hound::SampleFormat::Int,
+                    },
+                };
+
+                hound::WavWriter::create(&self.path, spec)
+                    .map(|writer| FileWriter::Wav(writer, self.sample_format))
+                    .map_err(|err| err.to_string())
+            }
+            FileFormat::Flac => {
+                // FLAC has no floating point samples
+                let bits_per_sample = match self.sample_format {
+                    SampleFormat::F32 => 24,
+                    SampleFormat::S16 => 16,
+                };
+
+                FlacWriter::create(
+                    &self.path,
+                    sample_rate as u32,
+                    number_of_channels,
+                    bits_per_sample,
+                )
+                .map(FileWriter::Flac)
+                .map_err(|err| err.to_string())
+            }
+        };
+
+        match writer {
+            Ok(writer) => {
+                self.writer = Some(writer);
+                Ok(())
+            }
+            Err(err) => Err(napi::Error::from_reason(format!(
+                "NotFoundError - Failed to create file '{}': {}",
+                self.path, err
+            ))),
+        }
+    }
+
+    pub fn listen(&mut self, tap: &mut Tap, block_size: usize) -> TapPump {
+        let mut recorder = FileRecorder {
+            writer: self.writer.take(),
+            error: self.error.clone(),
+        };
+        let number_of_channels = tap.number_of_channels();
+        let header_update_interval =
+            tap.sample_rate() as usize * HEADER_UPDATE_INTERVAL_SECS * number_of_channels;
+        let mut samples_since_update = 0;
+
+        // The recorder is moved into the pump and dropped with it once all
+        // pending frames are flushed, which finalizes the file header
+        tap.pump(block_size, move |samples| {
+            let writer = match recorder.writer.as_mut() {
+                Some(writer) => writer,
+                None => return,
+            };
+
+            if let Err(err) = writer.write(samples) {
+                recorder.fail(err);
+                return;
+            }
+
+            samples_since_update += samples.len();
+
+            if samples_since_update >= header_update_interval {
+                samples_since_update = 0;
+
+                if let Err(err) = writer.flush() {
+                    recorder.fail(err);
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,428 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+
+/// Number of frames per channel of each FLAC frame
+const BLOCK_SIZE: usize = 4096;
+/// Highest order of the fixed predictors
+const MAX_FIXED_ORDER: usize = 4;
+/// Highest number of partitions of the residual is `1 << MAX_PARTITION_ORDER`
+const MAX_PARTITION_ORDER: u32 = 6;
+/// Offset in the file of the 8 bytes holding the sample rate, the number of
+/// channels, the bits per sample and the total number of frames
+const STREAMINFO_TOTAL_OFFSET: u64 = 18;
+
+/// MSB first bit writer
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    len: u32,
+}
+
+impl BitWriter {
+    /// Write the `bits` (at most 32) lowest bits of `value`
+    fn write(&mut self, value: u64, bits: u32) {
+        debug_assert!(bits <= 32);
+
+        self.acc = (self.acc << bits) | (value & ((1 << bits) - 1));
+        self.len += bits;
+
+        while self.len >= 8 {
+            self.len -= 8;
+            self.bytes.push((self.acc >> self.len) as u8);
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write(value as u64, bits);
+    }
+
+    fn write_unary(&mut self, mut value: u64) {
+        while value >= 32 {
+            self.write(0, 32);
+            value -= 32;
+        }
+
+        self.write(1, value as u32 + 1);
+    }
+
+    /// Frame numbers are coded like UTF-8 characters, extended to 36 bits
+    fn write_utf8(&mut self, value: u64) {
+        if value < 0x80 {
+            self.write(value, 8);
+            return;
+        }
+
+        let number_of_bytes = match value {
+            0..=0x7ff => 2,
+            0x800..=0xffff => 3,
+            0x1_0000..=0x1f_ffff => 4,
+            0x20_0000..=0x3ff_ffff => 5,
+            0x400_0000..=0x7fff_ffff => 6,
+            _ => 7,
+        };
+
+        let prefix = (0xff00 >> number_of_bytes) & 0xff;
+        self.write(prefix | (value >> (6 * (number_of_bytes - 1))), 8);
+
+        for i in (0..number_of_bytes - 1).rev() {
+            self.write(0x80 | ((value >> (6 * i)) & 0x3f), 8);
+        }
+    }
+
+    fn align(&mut self) {
+        if self.len > 0 {
+            self.write(0, 8 - self.len);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.bytes.clear();
+        self.acc = 0;
+        self.len = 0;
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Residual of the fixed predictor of given order, mapped to unsigned values
+fn fixed_residual(samples: &[i32], order: usize, residual: &mut Vec<u64>) {
+    residual.clear();
+    residual.extend((order..samples.len()).map(|i| {
+        let x = |delay: usize| samples[i - delay] as i64;
+        let error = match order {
+            0 => x(0),
+            1 => x(0) - x(1),
+            2 => x(0) - 2 * x(1) + x(2),
+            3 => x(0) - 3 * x(1) + 3 * x(2) - x(3),
+            _ => x(0) - 4 * x(1) + 6 * x(2) - 4 * x(3) + x(4),
+        };
+
+        ((error << 1) ^ (error >> 63)) as u64
+    }));
+}
+
+/// Best Rice parameter of a partition and the number of bits of its samples
+fn rice_parameter(partition: &[u64], max_parameter: u32) -> (u32, u64) {
+    (0..=max_parameter)
+        .map(|k| {
+            let bits = partition.iter().map(|u| (u >> k) + 1 + k as u64).sum();
+            (k, bits)
+        })
+        .min_by_key(|(_, bits)| *bits)
+        .unwrap()
+}
+
+/// Partitioning of the residual of a block of `block_size` frames
+struct Partitioning {
+    order: u32,
+    parameters: Vec<u32>,
+    bits: u64,
+}
+
+fn partition_residual(
+    residual: &[u64],
+    block_size: usize,
+    predictor_order: usize,
+    max_parameter: u32,
+    parameter_bits: u32,
+) -> Partitioning {
+    let mut best: Option<Partitioning> = None;
+
+    for order in 0..=MAX_PARTITION_ORDER {
+        let partition_size = block_size >> order;
+
+        if order > 0
+            && (!block_size.is_multiple_of(1 << order) || partition_size <= predictor_order)
+        {
+            break;
+        }
+
+        let mut parameters = Vec::with_capacity(1 << order);
+        let mut bits = 0;
+        let mut start = 0;
+
+        for index in 0..(1 << order) {
+            // the warm-up samples of the predictor have no residual
+            let len = if index == 0 {
+                partition_size - predictor_order
+            } else {
+                partition_size
+            };
+
+            let (parameter, partition_bits) =
+                rice_parameter(&residual[start..start + len], max_parameter);
+            parameters.push(parameter);
+            bits += partition_bits + parameter_bits as u64;
+            start += len;
+        }
+
+        if best.as_ref().is_none_or(|best| bits < best.bits) {
+            best = Some(Partitioning {
+                order,
+                parameters,
+                bits,
+            });
+        }
+    }
+
+    best.unwrap()
+}
+
+/// Streaming FLAC encoder, using the fixed predictors with independent channels
+///
+/// The number of frames in the STREAMINFO header is updated on `flush` and
+/// `finalize`, a file which is not finalized remains decodable.
+pub(crate) struct FlacWriter {
+    writer: BufWriter<File>,
+    sample_rate: u32,
+    number_of_channels: usize,
+    bits_per_sample: u32,
+    channels: Vec<Vec<i32>>,
+    residual: Vec<u64>,
+    bits: BitWriter,
+    frame_number: u64,
+    total_frames: u64,
+}
+
+impl FlacWriter {
+    /// `number_of_channels` must be in the range [1, 8] and `bits_per_sample`
+    /// either 16 or 24
+    pub fn create(
+        path: &str,
+        sample_rate: u32,
+        number_of_channels: usize,
+        bits_per_sample: u32,
+    ) -> io::Result<Self> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        let mut flac_writer = Self {
+            writer,
+            sample_rate,
+            number_of_channels,
+            bits_per_sample,
+            channels: vec![Vec::with_capacity(BLOCK_SIZE); number_of_channels],
+            residual: Vec::with_capacity(BLOCK_SIZE),
+            bits: BitWriter::default(),
+            frame_number: 0,
+            total_frames: 0,
+        };
+
+        flac_writer.write_stream_info()?;
+
+        Ok(flac_writer)
+    }
+
+    fn write_stream_info(&mut self) -> io::Result<()> {
+        let bits = &mut self.bits;
+        bits.clear();
+
+        // last metadata block, of type STREAMINFO, 34 bytes long
+        bits.write(0x80, 8);
+        bits.write(34, 24);
+        // min and max block sizes, unknown min and max frame sizes
+        bits.write(BLOCK_SIZE as u64, 16);
+        bits.write(BLOCK_SIZE as u64, 16);
+        bits.write(0, 24);
+        bits.write(0, 24);
+        bits.write(self.sample_rate as u64, 20);
+        bits.write(self.number_of_channels as u64 - 1, 3);
+        bits.write(self.bits_per_sample as u64 - 1, 5);
+        bits.write(self.total_frames >> 32, 4);
+        bits.write(self.total_frames, 32);
+        // unknown MD5 signature
+        (0..4).for_each(|_| bits.write(0, 32));
+
+        self.writer.write_all(b"fLaC")?;
+        self.writer.write_all(&self.bits.bytes)
+    }
+
+    /// Encode interleaved samples
+    pub fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        let scale = ((1 << (self.bits_per_sample - 1)) - 1) as f32;
+
+        for frame in samples.chunks_exact(self.number_of_channels) {
+            for (channel, sample) in self.channels.iter_mut().zip(frame) {
+                channel.push((sample.clamp(-1., 1.) * scale) as i32);
+            }
+
+            if self.channels[0].len() == BLOCK_SIZE {
+                self.write_frame()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_frame(&mut self) -> io::Result<()> {
+        let block_size = self.channels[0].len();
+
+        if block_size == 0 {
+            return Ok(());
+        }
+
+        self.bits.clear();
+        // sync code and fixed block size strategy
+        self.bits.write(0xfff8, 16);
+        // block size on 16 bits at the end of the header, sample rate and
+        // bits per sample from STREAMINFO, independent channels
+        self.bits.write(0b0111, 4);
+        self.bits.write(0b0000, 4);
+        self.bits.write(self.number_of_channels as u64 - 1, 4);
+        self.bits.write(0b0000, 4);
+        self.bits.write_utf8(self.frame_number);
+        self.bits.write(block_size as u64 - 1, 16);
+        let crc = crc8(&self.bits.bytes);
+        self.bits.write(crc as u64, 8);
+
+        for channel in 0..self.number_of_channels {
+            self.write_subframe(channel);
+        }
+
+        self.bits.align();
+        let crc = crc16(&self.bits.bytes);
+        self.bits.write(crc as u64, 16);
+
+        self.writer.write_all(&self.bits.bytes)?;
+
+        self.frame_number += 1;
+        self.total_frames += block_size as u64;
+        self.channels.iter_mut().for_each(|channel| channel.clear());
+
+        Ok(())
+    }
+
+    fn write_subframe(&mut self, channel: usize) {
+        let samples = &self.channels[channel];
+        let bits_per_sample = self.bits_per_sample;
+        let block_size = samples.len();
+
+        // constant
+        if samples.iter().all(|sample| *sample == samples[0]) {
+            self.bits.write(0b0000_0000, 8);
+            self.bits.write_signed(samples[0] as i64, bits_per_sample);
+            return;
+        }
+
+        // the 4 bits Rice parameters are enough for 16 bits samples
+        let (method, max_parameter, parameter_bits) = if bits_per_sample <= 16 {
+            (0b00, 14, 4)
+        } else {
+            (0b01, 30, 5)
+        };
+
+        let mut best: Option<(usize, Partitioning)> = None;
+
+        for order in 0..=MAX_FIXED_ORDER.min(block_size - 1) {
+            fixed_residual(samples, order, &mut self.residual);
+
+            let partitioning = partition_residual(
+                &self.residual,
+                block_size,
+                order,
+                max_parameter,
+                parameter_bits,
+            );
+            let bits = partitioning.bits + (order as u64) * bits_per_sample as u64;
+
+            if best.as_ref().is_none_or(|(best_order, best)| {
+                bits < best.bits + (*best_order as u64) * bits_per_sample as u64
+            }) {
+                best = Some((order, partitioning));
+            }
+        }
+
+        let (order, partitioning) = best.unwrap();
+        let fixed_bits = partitioning.bits + (order as u64) * bits_per_sample as u64;
+
+        // verbatim
+        if fixed_bits >= (block_size as u64) * bits_per_sample as u64 {
+            self.bits.write(0b0000_0010, 8);
+            samples
+                .iter()
+                .for_each(|sample| self.bits.write_signed(*sample as i64, bits_per_sample));
+            return;
+        }
+
+        // fixed predictor
+        self.bits.write(0b0001_0000 | ((order as u64) << 1), 8);
+        samples[..order]
+            .iter()
+            .for_each(|sample| self.bits.write_signed(*sample as i64, bits_per_sample));
+
+        fixed_residual(samples, order, &mut self.residual);
+
+        self.bits.write(method, 2);
+        self.bits.write(partitioning.order as u64, 4);
+
+        let partition_size = block_size >> partitioning.order;
+        let mut start = 0;
+
+        for (index, parameter) in partitioning.parameters.iter().enumerate() {
+            let len = if index == 0 {
+                partition_size - order
+            } else {
+                partition_size
+            };
+
+            self.bits.write(*parameter as u64, parameter_bits);
+
+            for u in &self.residual[start..start + len] {
+                self.bits.write_unary(u >> parameter);
+                self.bits.write(*u, *parameter);
+            }
+
+            start += len;
+        }
+    }
+
+    /// Write the number of frames encoded so far in the header
+    fn update_stream_info(&mut self) -> io::Result<()> {
+        let total_frames = self.total_frames;
+        let mut bits = BitWriter::default();
+        bits.write(self.sample_rate as u64, 20);
+        bits.write(self.number_of_channels as u64 - 1, 3);
+        bits.write(self.bits_per_sample as u64 - 1, 5);
+        bits.write(total_frames >> 32, 4);
+        bits.write(total_frames, 32);
+
+        self.writer.seek(SeekFrom::Start(STREAMINFO_TOTAL_OFFSET))?;
+        self.writer.write_all(&bits.bytes)?;
+        self.writer.seek(SeekFrom::End(0))?;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.update_stream_info()?;
+        self.writer.flush()
+    }
+
+    /// Encode the remaining samples as a last, shorter, frame
+    pub fn finalize(mut self) -> io::Result<()> {
+        self.write_frame()?;
+        self.flush()
+    }
+}
@@ -0,0 +1,432 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use napi::{Either, JsNull, JsNumber, JsObject, JsString, Result};
+use web_audio_api::{AudioBuffer, AudioBufferOptions};
+
+use crate::sinks::{DeviceError, DriftResampler, PlayoutStats, Tap, TapPump, UnderrunTracker};
+
+const RENDER_QUANTUM_SIZE: usize = 128;
+
+/// Number of render quanta kept buffered on top of a JACK period between the
+/// JACK process callback and the render thread
+const PREBUFFER_QUANTA: usize = 4;
+
+/// Number of frames kept buffered between the two clocks, JACK reads or
+/// writes a whole period at once while the render thread handles a render
+/// quantum at a time
+fn target_fill(period: usize) -> usize {
+    PREBUFFER_QUANTA * RENDER_QUANTUM_SIZE + period
+}
+
+/// Name of the JACK client and of its ports, and auto-connect rule
+///
+/// By construction all fields are populated on the JS side.
+#[derive(Clone)]
+pub(crate) struct JackOptions {
+    client_name: String,
+    port_prefix: String,
+    number_of_channels: usize,
+    /// Glob pattern of the ports to connect to, e.g. "system:playback_*", no
+    /// connection is made if `None`
+    connect: Option<String>,
+}
+
+impl JackOptions {
+    pub fn from_js(js_options: &JsObject) -> Result<Self> {
+        let client_name = js_options
+            .get_named_property::<JsString>("clientName")?
+            .into_utf8()?
+            .into_owned()?;
+
+        let port_prefix = js_options
+            .get_named_property::<JsString>("portPrefix")?
+            .into_utf8()?
+            .into_owned()?;
+
+        let number_of_channels = js_options
+            .get_named_property::<JsNumber>("numberOfChannels")?
+            .get_double()? as usize;
+
+        let connect = match js_options.get_named_property::<Either<JsString, JsNull>>("connect")? {
+            Either::A(js_string) => Some(js_string.into_utf8()?.into_owned()?),
+            Either::B(_) => None,
+        };
+
+        Ok(Self {
+            client_name,
+            port_prefix,
+            number_of_channels,
+            connect,
+        })
+    }
+}
+
+/// Whether the JACK library can be loaded
+pub(crate) fn is_jack_available() -> bool {
+    jack::jack_sys::library().is_ok()
+}
+
+fn not_supported(msg: String) -> napi::Error {
+    napi::Error::from_reason(format!("NotSupportedError - {msg}"))
+}
+
+fn open_client(client_name: &str) -> Result<jack::Client> {
+    match jack::Client::new(client_name, jack::ClientOptions::NO_START_SERVER) {
+        Ok((client, _status)) => Ok(client),
+        Err(jack::Error::LibraryError(err)) => Err(not_supported(format!(
+            "Audio backend 'jack' is not available: {err}"
+        ))),
+        Err(err) => Err(not_supported(format!(
+            "Failed to open JACK client '{client_name}', make sure the JACK server is running: {err}"
+        ))),
+    }
+}
+
+/// Convert a glob pattern, e.g. "system:playback_*", to the regular expression
+/// expected by `jack_get_ports`
+fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '.' | '+' | '(' | ')' | '|' | '^' | '$' | '[' | ']' | '{' | '}' | '\\' => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            _ => regex.push(c),
+        }
+    }
+
+    regex.push('$');
+    regex
+}
+
+/// Connect our ports, in order, to the ports matching the pattern, returns
+/// the connections that failed
+fn auto_connect(
+    client: &jack::Client,
+    own_ports: &[String],
+    pattern: &str,
+    is_output: bool,
+) -> Vec<String> {
+    let flags = if is_output {
+        jack::PortFlags::IS_INPUT
+    } else {
+        jack::PortFlags::IS_OUTPUT
+    };
+
+    let regex = glob_to_regex(pattern);
+    let others = client.ports(Some(&regex), Some(jack::jack_sys::FLOAT_MONO_AUDIO), flags);
+
+    own_ports
+        .iter()
+        .zip(others.iter())
+        .filter_map(|(own, other)| {
+            let res = if is_output {
+                client.connect_ports_by_name(own, other)
+            } else {
+                client.connect_ports_by_name(other, own)
+            };
+
+            res.err()
+                .map(|err| format!("Failed to connect JACK port '{own}' to '{other}': {err}"))
+        })
+        .collect()
+}
+
+struct JackOutputProcessor {
+    ports: Vec<jack::Port<jack::AudioOut>>,
+    consumer: rtrb::Consumer<f32>,
+    resampler: DriftResampler,
+    /// interleaved frames of the current period
+    interleaved: Vec<f32>,
+    playout_stats: Arc<PlayoutStats>,
+    underruns: UnderrunTracker,
+}
+
+impl jack::ProcessHandler for JackOutputProcessor {
+    fn process(&mut self, client: &jack::Client, ps: &jack::ProcessScope) -> jack::Control {
+        let number_of_frames = ps.n_frames() as usize;
+        let number_of_channels = self.ports.len();
+        let required = number_of_frames * number_of_channels;
+
+        // should have been resized by the `buffer_size` callback
+        if self.interleaved.len() < required {
+            self.interleaved.resize(required, 0.);
+        }
+
+        // frames waiting in the ring buffer plus the current period, which is
+        // played during the next cycle
+        let buffered = self.consumer.slots() / number_of_channels + number_of_frames;
+
+        // the render thread runs on the clock of the "none" sink, which
+        // drifts from the clock of the JACK server
+        let interleaved = &mut self.interleaved[..required];
+        let missing = self.resampler.process(&mut self.consumer, interleaved);
+
+        for (c, port) in self.ports.iter_mut().enumerate() {
+            port.as_mut_slice(ps)
+                .iter_mut()
+                .zip(interleaved.iter().skip(c).step_by(number_of_channels))
+                .for_each(|(o, i)| *o = *i);
+        }
+
+        self.underruns
+            .record(&self.playout_stats, number_of_frames, missing);
+
+        if missing < number_of_frames {
+            self.playout_stats.record_latency(Duration::from_secs_f64(
+                buffered as f64 / client.sample_rate() as f64,
+            ));
+        }
+
+        jack::Control::Continue
+    }
+
+    fn buffer_size(&mut self, _client: &jack::Client, size: jack::Frames) -> jack::Control {
+        self.interleaved
+            .resize(size as usize * self.ports.len(), 0.);
+        jack::Control::Continue
+    }
+}
+
+/// Report the shutdown of the JACK server
+struct JackNotifications {
+    device_error: DeviceError,
+}
+
+impl jack::NotificationHandler for JackNotifications {
+    fn shutdown(&mut self, _status: jack::ClientStatus, reason: &str) {
+        self.device_error
+            .report(format!("JACK server has been shut down: {reason}"));
+    }
+}
+
+/// Sink that exposes the rendered frames as the output ports of a JACK client
+pub(crate) struct JackSink {
+    client: Option<jack::Client>,
+    ports: Vec<jack::Port<jack::AudioOut>>,
+    options: JackOptions,
+    device_error: DeviceError,
+}
+
+impl JackSink {
+    /// Open the JACK client, called before the context is created as it
+    /// defines the sample rate of the context
+    pub fn open(options: JackOptions) -> Result<Self> {
+        let client = open_client(&options.client_name)?;
+
+        let ports = (0..options.number_of_channels)
+            .map(|i| {
+                let name = format!("{}{}", options.port_prefix, i + 1);
+                client.register_port(&name, jack::AudioOut).map_err(|err| {
+                    not_supported(format!("Failed to register JACK port '{name}': {err}"))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            client: Some(client),
+            ports,
+            options,
+            device_error: DeviceError::default(),
+        })
+    }
+
+    pub fn sample_rate(&self) -> f32 {
+        self.client.as_ref().unwrap().sample_rate() as f32
+    }
+
+    pub fn number_of_channels(&self) -> usize {
+        self.options.number_of_channels
+    }
+
+    pub fn options(&self) -> &JackOptions {
+        &self.options
+    }
+
+    pub fn device_error(&self) -> DeviceError {
+        self.device_error.clone()
+    }
+
+    /// Activate the client and connect its ports
+    ///
+    /// The frames are rendered at the sample rate of the tap, which differs
+    /// from the sample rate of the server if the client has been reopened
+    /// after a restart of the server.
+    pub fn listen(&mut self, tap: &mut Tap, playout_stats: Arc<PlayoutStats>) -> Result<TapPump> {
+        let client = self.client.take().unwrap();
+        let ports = std::mem::take(&mut self.ports);
+        let port_names: Vec<String> = ports.iter().filter_map(|p| p.name().ok()).collect();
+
+        let sample_rate = client.sample_rate() as f32;
+        let period = client.buffer_size() as usize;
+
+        let processor = JackOutputProcessor {
+            resampler: DriftResampler::new(
+                ports.len(),
+                tap.sample_rate(),
+                sample_rate,
+                target_fill(period),
+            ),
+            interleaved: vec![0.; period * ports.len()],
+            ports,
+            consumer: tap.take_consumer(),
+            playout_stats,
+            underruns: UnderrunTracker::default(),
+        };
+
+        let notifications = JackNotifications {
+            device_error: self.device_error.clone(),
+        };
+
+        let active_client = client
+            .activate_async(notifications, processor)
+            .map_err(|err| not_supported(format!("Failed to activate JACK client: {err}")))?;
+
+        if let Some(pattern) = &self.options.connect {
+            // the client keeps running, the ports can still be connected by
+            // other JACK clients
+            auto_connect(active_client.as_client(), &port_names, pattern, true)
+                .into_iter()
+                .for_each(|message| self.device_error.notify(message));
+        }
+
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = Arc::clone(&running);
+
+        let handle = thread::spawn(move || {
+            while running_clone.load(Ordering::Acquire) {
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            let _ = active_client.deactivate();
+        });
+
+        Ok(TapPump::new(running, Some(handle)))
+    }
+}
+
+struct JackInputProcessor {
+    ports: Vec<jack::Port<jack::AudioIn>>,
+    producer: rtrb::Producer<f32>,
+}
+
+impl jack::ProcessHandler for JackInputProcessor {
+    fn process(&mut self, _client: &jack::Client, ps: &jack::ProcessScope) -> jack::Control {
+        let number_of_frames = ps.n_frames() as usize;
+        let number_of_channels = self.ports.len();
+
+        // drop the frames if the render thread does not consume them
+        if let Ok(chunk) = self
+            .producer
+            .write_chunk_uninit(number_of_frames * number_of_channels)
+        {
+            let ports = &self.ports;
+            let samples = (0..number_of_frames)
+                .flat_map(|i| ports.iter().map(move |port| port.as_slice(ps)[i]));
+
+            chunk.fill_from_iter(samples);
+        }
+
+        jack::Control::Continue
+    }
+}
+
+/// Iterator of the `MediaStreamTrack` returned by `getUserMedia` for JACK inputs
+struct JackInputIter {
+    consumer: Mutex<rtrb::Consumer<f32>>,
+    /// the render thread pulls the frames on its own clock, which drifts
+    /// from the clock of the JACK server
+    resampler: DriftResampler,
+    interleaved: Vec<f32>,
+    number_of_channels: usize,
+    sample_rate: f32,
+    // keep the client alive as long as the track
+    _client: jack::AsyncClient<(), JackInputProcessor>,
+}
+
+impl Iterator for JackInputIter {
+    type Item = std::result::Result<AudioBuffer, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let options = AudioBufferOptions {
+            number_of_channels: self.number_of_channels,
+            length: RENDER_QUANTUM_SIZE,
+            sample_rate: self.sample_rate,
+        };
+
+        let mut buffer = AudioBuffer::new(options);
+        let consumer = self.consumer.get_mut().unwrap();
+
+        // frames not received in time are replaced with silence
+        self.resampler.process(consumer, &mut self.interleaved);
+
+        for c in 0..self.number_of_channels {
+            buffer
+                .get_channel_data_mut(c)
+                .iter_mut()
+                .zip(
+                    self.interleaved
+                        .iter()
+                        .skip(c)
+                        .step_by(self.number_of_channels),
+                )
+                .for_each(|(o, i)| *o = *i);
+        }
+
+        Some(Ok(buffer))
+    }
+}
+
+/// Create a `MediaStreamTrack` fed by the input ports of a new JACK client
+pub(crate) fn jack_input_track(
+    options: JackOptions,
+) -> Result<web_audio_api::media_streams::MediaStreamTrack> {
+    let client = open_client(&options.client_name)?;
+    let sample_rate = client.sample_rate() as f32;
+    let number_of_channels = options.number_of_channels;
+
+    let ports = (0..number_of_channels)
+        .map(|i| {
+            let name = format!("{}{}", options.port_prefix, i + 1);
+            client.register_port(&name, jack::AudioIn).map_err(|err| {
+                not_supported(format!("Failed to register JACK port '{name}': {err}"))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let port_names: Vec<String> = ports.iter().filter_map(|p| p.name().ok()).collect();
+    let target_fill = target_fill(client.buffer_size() as usize);
+    let (producer, consumer) = rtrb::RingBuffer::new(target_fill * 4 * number_of_channels);
+
+    let active_client = client
+        .activate_async((), JackInputProcessor { ports, producer })
+        .map_err(|err| not_supported(format!("Failed to activate JACK client: {err}")))?;
+
+    if let Some(pattern) = &options.connect {
+        let errors = auto_connect(active_client.as_client(), &port_names, pattern, false);
+
+        if !errors.is_empty() {
+            return Err(not_supported(errors.join(", ")));
+        }
+    }
+
+    let iter = JackInputIter {
+        consumer: Mutex::new(consumer),
+        resampler: DriftResampler::new(number_of_channels, sample_rate, sample_rate, target_fill),
+        interleaved: vec![0.; RENDER_QUANTUM_SIZE * number_of_channels],
+        number_of_channels,
+        sample_rate,
+        _client: active_client,
+    };
+
+    Ok(web_audio_api::media_streams::MediaStreamTrack::from_iter(
+        iter,
+    ))
+}
@@ -0,0 +1,62 @@
+//! Stand-in of the `jack` backend when the crate is built without the `jack`
+//! feature, the backend is then never listed nor opened
+
+use std::sync::Arc;
+
+use napi::{JsObject, Result};
+
+use crate::sinks::{DeviceError, PlayoutStats, Tap, TapPump};
+
+fn not_built() -> napi::Error {
+    napi::Error::from_reason(String::from(
+        "NotSupportedError - Audio backend 'jack' is not available, node-web-audio-api has been built without the 'jack' feature",
+    ))
+}
+
+#[derive(Clone)]
+pub(crate) struct JackOptions;
+
+impl JackOptions {
+    pub fn from_js(_js_options: &JsObject) -> Result<Self> {
+        Ok(Self)
+    }
+}
+
+pub(crate) fn is_jack_available() -> bool {
+    false
+}
+
+/// Can't be instantiated
+pub(crate) enum JackSink {}
+
+impl JackSink {
+    pub fn open(_options: JackOptions) -> Result<Self> {
+        Err(not_built())
+    }
+
+    pub fn sample_rate(&self) -> f32 {
+        match *self {}
+    }
+
+    pub fn number_of_channels(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn options(&self) -> &JackOptions {
+        match *self {}
+    }
+
+    pub fn device_error(&self) -> DeviceError {
+        match *self {}
+    }
+
+    pub fn listen(&mut self, _tap: &mut Tap, _playout_stats: Arc<PlayoutStats>) -> Result<TapPump> {
+        match *self {}
+    }
+}
+
+pub(crate) fn jack_input_track(
+    _options: JackOptions,
+) -> Result<web_audio_api::media_streams::MediaStreamTrack> {
+    Err(not_built())
+}
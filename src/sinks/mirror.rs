@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use napi::Result;
+use web_audio_api::context::{AudioContext, BaseAudioContext};
+use web_audio_api::media_devices::{enumerate_devices_sync, MediaDeviceInfoKind};
+use web_audio_api::node::AudioNode;
+
+use crate::sinks::{not_supported, DeviceSink, PlayoutStats, Tap, TapPump, RING_BUFFER_BLOCKS};
+
+/// Size of the blocks buffered between the render thread and a mirror
+const MIRROR_BLOCK_SIZE: usize = 1024;
+
+/// Resolve the `default` backend, i.e. the cpal host used by the upstream
+/// crate, and the name of the device from the id given by `enumerateDevices`
+fn resolve_default_backend(sink_id: &str) -> Result<(String, String)> {
+    let backend = cpal::default_host().id().name().to_lowercase();
+
+    if sink_id.is_empty() {
+        return Ok((backend, String::new()));
+    }
+
+    let device_name = enumerate_devices_sync()
+        .into_iter()
+        .find(|d| d.kind() == MediaDeviceInfoKind::AudioOutput && d.device_id() == sink_id)
+        .map(|d| d.label().to_string())
+        .ok_or_else(|| {
+            napi::Error::from_reason(format!(
+                "NotFoundError - No output device '{sink_id}' found"
+            ))
+        })?;
+
+    Ok((backend, device_name))
+}
+
+/// Audio device playing the same frames as the destination of a context
+///
+/// The frames are rendered once and copied by a dedicated `Tap`, each mirror
+/// then resamples them to the clock of its own device.
+pub(crate) struct MirrorSink {
+    sink_id: String,
+    tap: Tap,
+    pump: TapPump,
+}
+
+impl MirrorSink {
+    pub fn open(
+        context: &AudioContext,
+        backend: &str,
+        sink_id: String,
+        latency: f64,
+    ) -> Result<Self> {
+        let (backend, device_name) = match backend {
+            "default" => resolve_default_backend(&sink_id)?,
+            "jack" => {
+                return Err(not_supported(String::from(
+                    "Audio backend 'jack' cannot be used to mirror the output of a context",
+                )))
+            }
+            backend => (backend.to_string(), sink_id.clone()),
+        };
+
+        let sample_rate = context.sample_rate();
+        let mut device = DeviceSink::open_mirror(backend, device_name, sample_rate, latency)?;
+
+        // the ring buffer must be large enough to hold the requested latency
+        let latency_frames = (latency * sample_rate as f64) as usize;
+        let capacity = (MIRROR_BLOCK_SIZE * RING_BUFFER_BLOCKS).max(latency_frames * 2);
+
+        let mut tap = Tap::new(context, device.number_of_channels(), capacity);
+        context.destination().connect(tap.node());
+
+        // the mirrors are not accounted in the playout stats of the context
+        let pump = device.listen(&mut tap, Arc::new(PlayoutStats::default()));
+
+        Ok(Self { sink_id, tap, pump })
+    }
+
+    pub fn sink_id(&self) -> &str {
+        &self.sink_id
+    }
+
+    /// Stop the device and disconnect the tap from the destination
+    pub fn close(&mut self, context: &AudioContext) {
+        context.destination().disconnect_dest(self.tap.node());
+        self.pump.stop();
+    }
+}
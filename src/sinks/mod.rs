@@ -0,0 +1,362 @@
+//! Outputs of realtime AudioContext that are not handled by the upstream crate,
+//! i.e. non-device sinks and audio devices of a non default backend
+//!
+//! All these sinks rely on the `"none"` sink of the upstream crate, i.e. the
+//! render thread runs on its own clock without any audio device, while a `Tap`
+//! connected to the destination copies the rendered frames to the sink.
+
+use std::sync::Arc;
+
+use napi::{Env, JsNumber, JsObject, JsString, Result};
+use web_audio_api::context::{AudioContext, BaseAudioContext};
+use web_audio_api::node::AudioNode;
+
+mod tap;
+pub(crate) use tap::*;
+
+mod stream;
+pub(crate) use stream::*;
+
+mod file;
+pub(crate) use file::*;
+
+mod flac;
+pub(crate) use flac::*;
+
+mod custom;
+pub(crate) use custom::*;
+
+mod device;
+pub(crate) use device::*;
+
+#[cfg(feature = "jack")]
+mod jack;
+#[cfg(not(feature = "jack"))]
+#[path = "jack_disabled.rs"]
+mod jack;
+pub(crate) use self::jack::*;
+
+mod watchdog;
+pub(crate) use watchdog::*;
+
+mod playout_stats;
+pub(crate) use playout_stats::*;
+
+mod resampler;
+pub(crate) use resampler::*;
+
+mod mirror;
+pub(crate) use mirror::*;
+
+/// Number of blocks that can be buffered between the render thread and the sink
+pub(crate) const RING_BUFFER_BLOCKS: usize = 8;
+
+/// Encoding of the samples delivered to the sinks
+#[derive(Clone, Copy)]
+pub(crate) enum SampleFormat {
+    F32,
+    S16,
+}
+
+impl SampleFormat {
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "f32" => SampleFormat::F32,
+            "s16" => SampleFormat::S16,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Encode interleaved samples as little-endian bytes
+    pub fn encode(&self, samples: &[f32]) -> Vec<u8> {
+        match self {
+            SampleFormat::F32 => samples.iter().flat_map(|s| s.to_le_bytes()).collect(),
+            SampleFormat::S16 => samples
+                .iter()
+                .flat_map(|s| f32_to_i16(*s).to_le_bytes())
+                .collect(),
+        }
+    }
+}
+
+pub(crate) fn f32_to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1., 1.) * i16::MAX as f32) as i16
+}
+
+pub(crate) enum AudioSinkKind {
+    Stream(StreamSink),
+    File(FileSink),
+    Custom(CustomSink),
+    Device(DeviceSink),
+    Jack(JackSink),
+}
+
+/// Options of the sink, by construction all fields are populated on the JS side
+pub(crate) struct AudioSinkOptions {
+    kind: AudioSinkKind,
+    number_of_channels: usize,
+    buffer_size: usize,
+}
+
+impl AudioSinkOptions {
+    /// Options of a sink playing on an audio device of a non default backend
+    pub fn from_device(device: DeviceSink) -> Self {
+        Self {
+            number_of_channels: device.number_of_channels(),
+            buffer_size: 1024,
+            kind: AudioSinkKind::Device(device),
+        }
+    }
+
+    /// Options of a sink exposed as the output ports of a JACK client
+    pub fn from_jack(sink: JackSink) -> Self {
+        Self {
+            number_of_channels: sink.number_of_channels(),
+            buffer_size: 1024,
+            kind: AudioSinkKind::Jack(sink),
+        }
+    }
+
+    /// Parse the `sink` field of the AudioContext options, `None` means the
+    /// context renders to an audio device (or to nothing)
+    pub fn from_js(js_options: &JsObject) -> Result<Option<Self>> {
+        let js_sink = match js_options.get::<&str, JsObject>("sink")? {
+            Some(js_sink) => js_sink,
+            None => return Ok(None),
+        };
+
+        let number_of_channels = js_sink
+            .get_named_property::<JsNumber>("numberOfChannels")?
+            .get_double()? as usize;
+
+        let buffer_size = js_sink
+            .get_named_property::<JsNumber>("bufferSize")?
+            .get_double()? as usize;
+
+        let sink_type = js_sink
+            .get_named_property::<JsString>("type")?
+            .into_utf8()?
+            .into_owned()?;
+
+        let kind = match sink_type.as_str() {
+            "stream" => {
+                let format = js_sink
+                    .get_named_property::<JsString>("format")?
+                    .into_utf8()?
+                    .into_owned()?;
+
+                AudioSinkKind::Stream(StreamSink {
+                    format: SampleFormat::from_str(&format),
+                })
+            }
+            "file" => {
+                let path = js_sink
+                    .get_named_property::<JsString>("path")?
+                    .into_utf8()?
+                    .into_owned()?;
+
+                let format = js_sink
+                    .get_named_property::<JsString>("format")?
+                    .into_utf8()?
+                    .into_owned()?;
+
+                let sample_format = js_sink
+                    .get_named_property::<JsString>("sampleFormat")?
+                    .into_utf8()?
+                    .into_owned()?;
+
+                AudioSinkKind::File(FileSink::new(
+                    path,
+                    FileFormat::from_str(&format),
+                    SampleFormat::from_str(&sample_format),
+                ))
+            }
+            "custom" => AudioSinkKind::Custom(CustomSink),
+            _ => unreachable!(),
+        };
+
+        Ok(Some(Self {
+            kind,
+            number_of_channels,
+            buffer_size,
+        }))
+    }
+}
+
+/// Connect a Tap to the destination of the context and hand over its content
+/// to the sink
+pub(crate) struct AudioSink {
+    kind: AudioSinkKind,
+    tap: Tap,
+    buffer_size: usize,
+    pump: Option<TapPump>,
+    playout_stats: Option<Arc<PlayoutStats>>,
+}
+
+impl AudioSink {
+    pub fn new(context: &AudioContext, options: AudioSinkOptions) -> Result<Self> {
+        let AudioSinkOptions {
+            mut kind,
+            number_of_channels,
+            buffer_size,
+        } = options;
+
+        if let AudioSinkKind::File(sink) = &mut kind {
+            sink.open(context.sample_rate(), number_of_channels)?;
+        }
+
+        let tap = Tap::new(
+            context,
+            number_of_channels,
+            buffer_size * RING_BUFFER_BLOCKS,
+        );
+        context.destination().connect(tap.node());
+
+        Ok(Self {
+            kind,
+            tap,
+            buffer_size,
+            pump: None,
+            playout_stats: None,
+        })
+    }
+
+    /// Error reported by the backend if the sink plays on an audio device, or
+    /// by the file sink if it fails to write
+    pub fn device_error(&self) -> Option<DeviceError> {
+        match &self.kind {
+            AudioSinkKind::File(sink) => Some(sink.device_error()),
+            AudioSinkKind::Device(sink) => Some(sink.device_error()),
+            AudioSinkKind::Jack(sink) => Some(sink.device_error()),
+            _ => None,
+        }
+    }
+
+    /// Start delivering frames to the sink
+    ///
+    /// This must be called from `listen_to_events` as the JS callbacks are
+    /// added to the Napi object after its instantiation. Sinks playing on an
+    /// audio device update the playout stats of the context.
+    pub fn listen(
+        &mut self,
+        env: &Env,
+        js_context: &JsObject,
+        playout_stats: &Arc<PlayoutStats>,
+    ) -> Result<()> {
+        let pump = match &mut self.kind {
+            AudioSinkKind::Stream(sink) => {
+                sink.listen(env, js_context, &mut self.tap, self.buffer_size)?
+            }
+            AudioSinkKind::File(sink) => sink.listen(&mut self.tap, self.buffer_size),
+            AudioSinkKind::Custom(sink) => {
+                sink.listen(env, js_context, &mut self.tap, self.buffer_size)?
+            }
+            AudioSinkKind::Device(sink) => sink.listen(&mut self.tap, Arc::clone(playout_stats)),
+            AudioSinkKind::Jack(sink) => sink.listen(&mut self.tap, Arc::clone(playout_stats))?,
+        };
+
+        self.pump = Some(pump);
+        self.playout_stats = Some(Arc::clone(playout_stats));
+
+        Ok(())
+    }
+
+    /// Whether the sink plays on an audio device, which measures the playout
+    /// in its audio callback and can be opened again once it has been lost
+    pub fn plays_on_device(&self) -> bool {
+        matches!(self.kind, AudioSinkKind::Device(_) | AudioSinkKind::Jack(_))
+    }
+
+    /// Open the audio device again after it has been lost, or the default
+    /// output device of the backend if `fallback` is set (of the default
+    /// backend for JACK)
+    ///
+    /// The previous device is released first, a new tap then feeds the new
+    /// device. Returns the new sink id if it changed.
+    pub fn reopen(&mut self, context: &AudioContext, fallback: bool) -> Result<Option<String>> {
+        let playout_stats = match &self.playout_stats {
+            Some(playout_stats) => Arc::clone(playout_stats),
+            None => return Ok(None),
+        };
+
+        // the lost device may not be opened again while the stream is alive
+        if let Some(mut pump) = self.pump.take() {
+            pump.stop();
+        }
+
+        let sample_rate = context.sample_rate();
+
+        let (mut kind, sink_id) = match &self.kind {
+            AudioSinkKind::Device(sink) if fallback && !sink.sink_id().is_empty() => {
+                let device =
+                    DeviceSink::reopen(sink.backend().to_string(), String::new(), sample_rate)?;
+                (AudioSinkKind::Device(device), Some(String::new()))
+            }
+            AudioSinkKind::Device(sink) => {
+                let device = DeviceSink::reopen(
+                    sink.backend().to_string(),
+                    sink.sink_id().to_string(),
+                    sample_rate,
+                )?;
+                (AudioSinkKind::Device(device), None)
+            }
+            AudioSinkKind::Jack(_) if fallback => {
+                let backend = cpal::default_host().id().name().to_lowercase();
+                let device = DeviceSink::reopen(backend, String::new(), sample_rate)?;
+                (AudioSinkKind::Device(device), Some(String::new()))
+            }
+            AudioSinkKind::Jack(sink) => {
+                let jack = JackSink::open(sink.options().clone())?;
+                (AudioSinkKind::Jack(jack), None)
+            }
+            _ => return Ok(None),
+        };
+
+        let number_of_channels = match &kind {
+            AudioSinkKind::Device(sink) => sink.number_of_channels(),
+            AudioSinkKind::Jack(sink) => sink.number_of_channels(),
+            _ => unreachable!(),
+        };
+
+        let mut tap = Tap::new(
+            context,
+            number_of_channels,
+            self.buffer_size * RING_BUFFER_BLOCKS,
+        );
+
+        let pump = match &mut kind {
+            AudioSinkKind::Device(sink) => sink.listen(&mut tap, playout_stats),
+            AudioSinkKind::Jack(sink) => sink.listen(&mut tap, playout_stats)?,
+            _ => unreachable!(),
+        };
+
+        context.destination().disconnect_dest(self.tap.node());
+        context.destination().connect(tap.node());
+
+        self.kind = kind;
+        self.tap = tap;
+        self.pump = Some(pump);
+
+        Ok(sink_id)
+    }
+
+    /// Flush pending frames and release the sink, called once the context is closed
+    ///
+    /// Returns the errors raised while flushing, e.g. when the file sink fails
+    /// to finalize its file, as the context does not dispatch events anymore.
+    pub fn close(&mut self) -> Result<()> {
+        let device_error = self.device_error();
+        let previous_error = device_error.as_ref().and_then(|error| error.get());
+
+        if let Some(mut pump) = self.pump.take() {
+            pump.stop();
+        }
+
+        match device_error.and_then(|error| error.get()) {
+            Some(message) if previous_error.is_none() => Err(napi::Error::from_reason(format!(
+                "InvalidStateError - {message}"
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
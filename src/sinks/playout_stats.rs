@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Cumulative playout counters of an AudioContext
+///
+/// Updated from the audio callback for audio devices handled by this crate,
+/// and sampled by the device watchdog otherwise. All fields are atomics so
+/// that the audio callback never blocks. Latencies are stored in nanoseconds.
+///
+/// Underruns and latencies are unknown when playing on an audio device of the
+/// upstream backend, as its audio callback is not exposed.
+pub(crate) struct PlayoutStats {
+    available: AtomicBool,
+    total_frames: AtomicU64,
+    underrun_frames: AtomicU64,
+    underrun_events: AtomicU64,
+    min_latency: AtomicU64,
+    max_latency: AtomicU64,
+    latency_sum: AtomicU64,
+    latency_count: AtomicU64,
+}
+
+impl Default for PlayoutStats {
+    fn default() -> Self {
+        Self {
+            available: AtomicBool::new(true),
+            total_frames: AtomicU64::new(0),
+            underrun_frames: AtomicU64::new(0),
+            underrun_events: AtomicU64::new(0),
+            min_latency: AtomicU64::new(u64::MAX),
+            max_latency: AtomicU64::new(0),
+            latency_sum: AtomicU64::new(0),
+            latency_count: AtomicU64::new(0),
+        }
+    }
+}
+
+impl PlayoutStats {
+    /// Whether underruns and latencies are measured by the output
+    pub fn set_available(&self, available: bool) {
+        self.available.store(available, Ordering::Relaxed);
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.available.load(Ordering::Relaxed)
+    }
+
+    /// Record frames handed to the output, `underrun_frames` of them being
+    /// silence emitted because the render thread was late
+    pub fn record_frames(&self, frames: u64, underrun_frames: u64) {
+        self.total_frames.fetch_add(frames, Ordering::Relaxed);
+        self.underrun_frames
+            .fetch_add(underrun_frames, Ordering::Relaxed);
+    }
+
+    /// Record the start of a new underrun
+    pub fn record_underrun_event(&self) {
+        self.underrun_events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the latency between the render thread and the output
+    pub fn record_latency(&self, latency: Duration) {
+        let nanos = latency.as_nanos() as u64;
+
+        self.min_latency.fetch_min(nanos, Ordering::Relaxed);
+        self.max_latency.fetch_max(nanos, Ordering::Relaxed);
+        self.latency_sum.fetch_add(nanos, Ordering::Relaxed);
+        self.latency_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Restart the latency measurements, frame counters are left untouched
+    pub fn reset_latency(&self) {
+        self.min_latency.store(u64::MAX, Ordering::Relaxed);
+        self.max_latency.store(0, Ordering::Relaxed);
+        self.latency_sum.store(0, Ordering::Relaxed);
+        self.latency_count.store(0, Ordering::Relaxed);
+    }
+
+    pub fn total_frames(&self) -> u64 {
+        self.total_frames.load(Ordering::Relaxed)
+    }
+
+    pub fn underrun_frames(&self) -> u64 {
+        self.underrun_frames.load(Ordering::Relaxed)
+    }
+
+    pub fn underrun_events(&self) -> u64 {
+        self.underrun_events.load(Ordering::Relaxed)
+    }
+
+    /// Minimum latency in seconds, 0 if no latency has been measured
+    pub fn minimum_latency(&self) -> f64 {
+        match self.min_latency.load(Ordering::Relaxed) {
+            u64::MAX => 0.,
+            nanos => nanos as f64 / 1e9,
+        }
+    }
+
+    /// Maximum latency in seconds, 0 if no latency has been measured
+    pub fn maximum_latency(&self) -> f64 {
+        self.max_latency.load(Ordering::Relaxed) as f64 / 1e9
+    }
+
+    /// Average latency in seconds, 0 if no latency has been measured
+    pub fn average_latency(&self) -> f64 {
+        let count = self.latency_count.load(Ordering::Relaxed);
+
+        if count == 0 {
+            return 0.;
+        }
+
+        self.latency_sum.load(Ordering::Relaxed) as f64 / count as f64 / 1e9
+    }
+}
+
+/// Track underruns of an audio callback so that consecutive silent callbacks
+/// are counted as a single underrun event
+#[derive(Default)]
+pub(crate) struct UnderrunTracker {
+    started: bool,
+    in_underrun: bool,
+}
+
+impl UnderrunTracker {
+    /// Record a callback of `frames` frames of which `missing` were not
+    /// available. Silence emitted before the first rendered frame, while the
+    /// output is priming, is not counted as an underrun.
+    pub fn record(&mut self, stats: &PlayoutStats, frames: usize, missing: usize) {
+        if missing < frames {
+            self.started = true;
+        }
+
+        if !self.started {
+            return;
+        }
+
+        stats.record_frames(frames as u64, missing as u64);
+
+        if missing > 0 && !self.in_underrun {
+            stats.record_underrun_event();
+        }
+
+        self.in_underrun = missing > 0;
+    }
+}
@@ -0,0 +1,129 @@
+use cpal::{FromSample, Sample};
+
+/// Time constant of the low-pass filter applied to the ring buffer fill, in
+/// number of device callbacks
+const FILL_SMOOTHING: f64 = 0.01;
+
+/// Proportional gain of the ratio controller, per relative fill error
+const KP: f64 = 1e-3;
+
+/// Integral gain of the ratio controller, per relative fill error and second
+const KI: f64 = 1e-4;
+
+/// Max deviation of the ratio from its nominal value, i.e. 2000 ppm
+const MAX_ADJUST: f64 = 2e-3;
+
+/// Adaptive resampler playing the frames rendered by a context on an audio
+/// device that runs on its own clock
+///
+/// The conversion ratio is continuously adjusted so that the ring buffer stays
+/// around `target_fill` frames, which compensates both for a different
+/// nominal sample rate and for the drift between the two clocks.
+pub(crate) struct DriftResampler {
+    number_of_channels: usize,
+    /// nominal number of input frames per output frame
+    nominal_ratio: f64,
+    output_sample_rate: f64,
+    target_fill: f64,
+    smoothed_fill: f64,
+    integral: f64,
+    /// position between the `previous` and the `next` input frames
+    position: f64,
+    previous: Vec<f32>,
+    next: Vec<f32>,
+    primed: bool,
+}
+
+impl DriftResampler {
+    pub fn new(
+        number_of_channels: usize,
+        input_sample_rate: f32,
+        output_sample_rate: f32,
+        target_fill: usize,
+    ) -> Self {
+        Self {
+            number_of_channels,
+            nominal_ratio: input_sample_rate as f64 / output_sample_rate as f64,
+            output_sample_rate: output_sample_rate as f64,
+            target_fill: target_fill as f64,
+            smoothed_fill: target_fill as f64,
+            integral: 0.,
+            position: 0.,
+            previous: vec![0.; number_of_channels],
+            next: vec![0.; number_of_channels],
+            primed: false,
+        }
+    }
+
+    /// Fill `data` with interleaved frames resampled from the consumer, and
+    /// return the number of frames that could not be produced
+    pub fn process<T>(&mut self, consumer: &mut rtrb::Consumer<f32>, data: &mut [T]) -> usize
+    where
+        T: Sample + FromSample<f32>,
+    {
+        let number_of_channels = self.number_of_channels;
+        let number_of_frames = data.len() / number_of_channels;
+        let fill = (consumer.slots() / number_of_channels) as f64;
+
+        if !self.primed {
+            let needed = self.target_fill + number_of_frames as f64 * self.nominal_ratio;
+
+            if fill < needed {
+                data.fill(T::EQUILIBRIUM);
+                return number_of_frames;
+            }
+
+            // drop the frames accumulated meanwhile so that the latency
+            // starts at its target value
+            let excess = (fill - self.target_fill) as usize * number_of_channels;
+            consumer.read_chunk(excess).unwrap().commit_all();
+
+            self.smoothed_fill = self.target_fill;
+            self.integral = 0.;
+            self.position = 1.;
+            self.primed = true;
+        } else {
+            self.smoothed_fill += (fill - self.smoothed_fill) * FILL_SMOOTHING;
+        }
+
+        let error = (self.smoothed_fill - self.target_fill) / self.target_fill;
+        let duration = number_of_frames as f64 / self.output_sample_rate;
+        self.integral = (self.integral + error * duration).clamp(-MAX_ADJUST / KI, MAX_ADJUST / KI);
+        let adjust = (KP * error + KI * self.integral).clamp(-MAX_ADJUST, MAX_ADJUST);
+        // consume faster when the ring buffer fills up
+        let ratio = self.nominal_ratio * (1. + adjust);
+
+        for (index, frame) in data.chunks_exact_mut(number_of_channels).enumerate() {
+            while self.position >= 1. {
+                if consumer.slots() < number_of_channels {
+                    data[index * number_of_channels..].fill(T::EQUILIBRIUM);
+                    self.primed = false;
+                    return number_of_frames - index;
+                }
+
+                std::mem::swap(&mut self.previous, &mut self.next);
+
+                let chunk = consumer.read_chunk(number_of_channels).unwrap();
+                let (first, second) = chunk.as_slices();
+                self.next
+                    .iter_mut()
+                    .zip(first.iter().chain(second.iter()))
+                    .for_each(|(o, i)| *o = *i);
+                chunk.commit_all();
+
+                self.position -= 1.;
+            }
+
+            let position = self.position as f32;
+
+            frame
+                .iter_mut()
+                .zip(self.previous.iter().zip(self.next.iter()))
+                .for_each(|(o, (p, n))| *o = T::from_sample(p + (n - p) * position));
+
+            self.position += ratio;
+        }
+
+        0
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::atomic::Ordering;
+
+use napi::threadsafe_function::{ThreadSafeCallContext, ThreadsafeFunctionCallMode};
+use napi::{Env, JsFunction, JsObject, Result, Status};
+
+use crate::sinks::{SampleFormat, Tap, TapPump};
+
+/// Max number of chunks waiting to be handled by the JS callback, further
+/// chunks are dropped
+const MAX_PENDING_CHUNKS: usize = 32;
+
+/// Sink that pushes interleaved PCM chunks to a JS callback
+pub(crate) struct StreamSink {
+    pub format: SampleFormat,
+}
+
+impl StreamSink {
+    pub fn listen(
+        &self,
+        env: &Env,
+        js_context: &JsObject,
+        tap: &mut Tap,
+        block_size: usize,
+    ) -> Result<TapPump> {
+        let k_onsinkdata = crate::utils::get_symbol_for(env, "node-web-audio-api:onsinkdata");
+        let sinkdata_cb: JsFunction = js_context.get_property(k_onsinkdata)?;
+        let mut sinkdata_tsfn = env.create_threadsafe_function(
+            &sinkdata_cb,
+            MAX_PENDING_CHUNKS,
+            |ctx: ThreadSafeCallContext<Vec<u8>>| {
+                let buffer = ctx.env.create_buffer_with_data(ctx.value)?;
+                Ok(vec![buffer.into_raw()])
+            },
+        )?;
+
+        // unref tsfn so they do not prevent the process to exit
+        let _ = sinkdata_tsfn.unref(env);
+
+        let format = self.format;
+        let number_of_channels = tap.number_of_channels();
+        let dropped_frames = tap.dropped_frames();
+
+        let pump = tap.pump(block_size, move |samples| {
+            let status = sinkdata_tsfn.call(
+                Ok(format.encode(samples)),
+                ThreadsafeFunctionCallMode::NonBlocking,
+            );
+
+            // JS is not consuming the chunks fast enough
+            if status == Status::QueueFull {
+                let number_of_frames = samples.len() / number_of_channels;
+                dropped_frames.fetch_add(number_of_frames as u64, Ordering::Relaxed);
+            }
+        });
+
+        Ok(pump)
+    }
+}
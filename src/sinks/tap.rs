@@ -0,0 +1,268 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use web_audio_api::context::BaseAudioContext;
+use web_audio_api::node::{AudioNodeOptions, ChannelCountMode, ChannelInterpretation};
+use web_audio_api::worklet::{
+    AudioParamValues, AudioWorkletGlobalScope, AudioWorkletNode, AudioWorkletNodeOptions,
+    AudioWorkletProcessor,
+};
+
+const RENDER_QUANTUM_SIZE: usize = 128;
+
+/// Interval between two checks of the ring buffer when the render thread of
+/// an offline context waits for the consumer
+const BACKPRESSURE_INTERVAL: Duration = Duration::from_micros(100);
+
+/// Render thread side of a Tap, copies its input as interleaved samples into
+/// the ring buffer
+///
+/// A realtime render thread never blocks, frames are dropped if the ring
+/// buffer is full. An offline render thread waits for the consumer instead.
+pub(crate) struct TapProcessor {
+    producer: rtrb::Producer<f32>,
+    number_of_channels: usize,
+    dropped_frames: Arc<AtomicU64>,
+    blocking: bool,
+}
+
+impl AudioWorkletProcessor for TapProcessor {
+    type ProcessorOptions = TapProcessor;
+
+    fn constructor(opts: Self::ProcessorOptions) -> Self {
+        opts // the opts contain the full processor
+    }
+
+    fn process<'a, 'b>(
+        &mut self,
+        inputs: &'b [&'a [&'a [f32]]],
+        _outputs: &'b mut [&'a mut [&'a mut [f32]]],
+        _params: AudioParamValues<'b>,
+        _scope: &'b AudioWorkletGlobalScope,
+    ) -> bool {
+        let input = inputs[0];
+        let number_of_frames = input[0].len();
+        let number_of_channels = self.number_of_channels;
+        let number_of_samples = number_of_frames * number_of_channels;
+
+        if self.blocking {
+            while self.producer.slots() < number_of_samples && !self.producer.is_abandoned() {
+                thread::sleep(BACKPRESSURE_INTERVAL);
+            }
+        }
+
+        match self.producer.write_chunk_uninit(number_of_samples) {
+            Ok(chunk) => {
+                // Silent inputs may be down-mixed to a single channel by the
+                // render thread, in which case we just repeat the first channel
+                let samples = (0..number_of_frames).flat_map(|i| {
+                    (0..number_of_channels).map(move |c| match input.get(c) {
+                        Some(channel) => channel[i],
+                        None => input[0][i],
+                    })
+                });
+
+                chunk.fill_from_iter(samples);
+            }
+            Err(_) => {
+                self.dropped_frames
+                    .fetch_add(number_of_frames as u64, Ordering::Relaxed);
+            }
+        }
+
+        // keep the processor alive even if nothing is connected, until the
+        // consumer is dropped
+        !self.producer.is_abandoned()
+    }
+}
+
+/// Copy the output of the nodes connected to it into a lock-free ring buffer
+/// that can be consumed outside the render thread
+pub(crate) struct Tap {
+    node: Option<AudioWorkletNode>,
+    consumer: Option<rtrb::Consumer<f32>>,
+    number_of_channels: usize,
+    sample_rate: f32,
+    dropped_frames: Arc<AtomicU64>,
+}
+
+impl Tap {
+    /// Create a new Tap with room for `capacity` frames in its ring buffer
+    pub fn new<C: BaseAudioContext>(
+        context: &C,
+        number_of_channels: usize,
+        capacity: usize,
+    ) -> Self {
+        Self::with_backpressure(context, number_of_channels, capacity, false)
+    }
+
+    /// Create a new Tap whose render thread waits for room in the ring buffer
+    /// rather than dropping frames, only for offline contexts
+    ///
+    /// The ring buffer must have room for a render quantum on top of a block
+    /// of the pump.
+    pub fn new_blocking<C: BaseAudioContext>(
+        context: &C,
+        number_of_channels: usize,
+        capacity: usize,
+    ) -> Self {
+        Self::with_backpressure(context, number_of_channels, capacity, true)
+    }
+
+    fn with_backpressure<C: BaseAudioContext>(
+        context: &C,
+        number_of_channels: usize,
+        capacity: usize,
+        blocking: bool,
+    ) -> Self {
+        let (producer, consumer) = rtrb::RingBuffer::new(capacity * number_of_channels);
+        let dropped_frames = Arc::new(AtomicU64::new(0));
+
+        let processor_options = TapProcessor {
+            producer,
+            number_of_channels,
+            dropped_frames: Arc::clone(&dropped_frames),
+            blocking,
+        };
+
+        let options = AudioWorkletNodeOptions {
+            number_of_inputs: 1,
+            number_of_outputs: 0,
+            output_channel_count: vec![],
+            parameter_data: Default::default(),
+            audio_node_options: AudioNodeOptions {
+                channel_count: number_of_channels,
+                channel_count_mode: ChannelCountMode::Explicit,
+                channel_interpretation: ChannelInterpretation::Speakers,
+            },
+            processor_options,
+        };
+
+        let node = AudioWorkletNode::new::<TapProcessor>(context, options);
+
+        Self {
+            node: Some(node),
+            consumer: Some(consumer),
+            number_of_channels,
+            sample_rate: context.sample_rate(),
+            dropped_frames,
+        }
+    }
+
+    /// Panics if the node has been taken
+    pub fn node(&self) -> &AudioWorkletNode {
+        self.node.as_ref().expect("Tap node has been taken")
+    }
+
+    /// Take the node to hand it over to JS, which is then responsible for
+    /// the connections of the Tap
+    ///
+    /// Panics if called twice.
+    pub fn take_node(&mut self) -> AudioWorkletNode {
+        self.node.take().expect("Tap node has been taken")
+    }
+
+    pub fn number_of_channels(&self) -> usize {
+        self.number_of_channels
+    }
+
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Number of frames that could not be written into the ring buffer because
+    /// the consumer was too slow
+    pub fn dropped_frames(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.dropped_frames)
+    }
+
+    /// Take the consumer side of the ring buffer to drain it directly, e.g.
+    /// from an audio device callback
+    ///
+    /// Panics if the Tap is already pumped.
+    pub fn take_consumer(&mut self) -> rtrb::Consumer<f32> {
+        self.consumer.take().expect("Tap is already pumped")
+    }
+
+    /// Spawn a thread that drains the ring buffer by blocks of `block_size` frames
+    ///
+    /// The callback receives interleaved samples. When the pump is stopped the
+    /// remaining frames are flushed as a last, possibly shorter, block.
+    ///
+    /// Panics if called twice.
+    pub fn pump<F>(&mut self, block_size: usize, mut callback: F) -> TapPump
+    where
+        F: FnMut(&[f32]) + Send + 'static,
+    {
+        let mut consumer = self.take_consumer();
+        let number_of_channels = self.number_of_channels;
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = Arc::clone(&running);
+        // poll twice per render quantum
+        let interval =
+            Duration::from_secs_f64(RENDER_QUANTUM_SIZE as f64 / self.sample_rate as f64 / 2.);
+
+        let handle = thread::spawn(move || {
+            let mut block = vec![0.; block_size * number_of_channels];
+
+            while running_clone.load(Ordering::Acquire) {
+                if consumer.slots() >= block.len() {
+                    consumer.pop_entire_slice(&mut block).unwrap();
+                    callback(&block);
+                } else if consumer.is_abandoned() {
+                    break;
+                } else {
+                    thread::sleep(interval);
+                }
+            }
+
+            // flush everything that remains in the ring buffer
+            while !consumer.is_empty() {
+                let len = consumer.slots().min(block.len());
+                let len = len - len % number_of_channels;
+
+                if len == 0 {
+                    break;
+                }
+
+                consumer.pop_entire_slice(&mut block[..len]).unwrap();
+                callback(&block[..len]);
+            }
+        });
+
+        TapPump {
+            running,
+            handle: Some(handle),
+        }
+    }
+}
+
+/// Handle to the thread draining a Tap
+pub(crate) struct TapPump {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TapPump {
+    /// Wrap a thread that exits when `running` is set to false
+    pub fn new(running: Arc<AtomicBool>, handle: Option<JoinHandle<()>>) -> Self {
+        Self { running, handle }
+    }
+
+    /// Stop the thread, returns when all pending frames have been flushed
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::Release);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for TapPump {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
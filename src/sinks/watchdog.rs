@@ -0,0 +1,256 @@
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use web_audio_api::context::{AudioContext, AudioContextState, BaseAudioContext};
+
+use crate::sinks::{AudioSink, PlayoutStats};
+
+/// Interval between two checks of the output
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Interval between two attempts to open a lost audio device again
+const REOPEN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Duration without any progress of the render thread after which the audio
+/// device of the upstream backend is considered lost
+const STALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What to do when the audio device is lost
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum DeviceLossPolicy {
+    /// Switch the context to the `interrupted` state until the device comes back
+    Interrupt,
+    /// Same as `Interrupt` but also try to switch to the default output device
+    Fallback,
+}
+
+impl DeviceLossPolicy {
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "interrupt" => DeviceLossPolicy::Interrupt,
+            "fallback" => DeviceLossPolicy::Fallback,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Errors reported by the backend of an output device, shared with the watchdog
+///
+/// A reported error means the device is lost, other errors (e.g. a JACK port
+/// that can't be connected) are only dispatched as `error` events.
+#[derive(Clone, Default)]
+pub(crate) struct DeviceError {
+    lost: Arc<Mutex<Option<String>>>,
+    notified: Arc<Mutex<Vec<String>>>,
+}
+
+impl DeviceError {
+    pub fn report(&self, message: String) {
+        self.lost.lock().unwrap().replace(message);
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.lost.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.lost.lock().unwrap().take();
+    }
+
+    /// Report an error that does not interrupt the output
+    pub fn notify(&self, message: String) {
+        self.notified.lock().unwrap().push(message);
+    }
+
+    fn take_notified(&self) -> Vec<String> {
+        std::mem::take(&mut *self.notified.lock().unwrap())
+    }
+}
+
+/// Event sent to JS when the output is interrupted or comes back
+pub(crate) struct DeviceStatusEvent {
+    pub type_: &'static str,
+    pub message: String,
+}
+
+/// Spawn a thread monitoring the output of an AudioContext
+///
+/// Device loss is either reported by the backend (for sinks handled by this
+/// crate) or detected when the render thread of the upstream backend stops
+/// making progress while the context is running. The thread exits when the
+/// context is closed or dropped.
+///
+/// Audio devices handled by this crate are opened again (or replaced by the
+/// default device with the `Fallback` policy) until they play frames again.
+///
+/// When the output is not an audio device handled by this crate, the thread
+/// also counts the played frames from the clock of the context. Underruns and
+/// latencies are then either zero (the "none" sink never underruns) or unknown (audio devices
+/// of the upstream backend, which does not expose its audio callback).
+pub(crate) fn spawn_device_watchdog(
+    context: Weak<AudioContext>,
+    sink: Arc<Mutex<Option<AudioSink>>>,
+    policy: DeviceLossPolicy,
+    status_tsfn: ThreadsafeFunction<DeviceStatusEvent>,
+    playout_stats: Arc<PlayoutStats>,
+) {
+    thread::spawn(move || {
+        let mut interrupted = false;
+        let mut last_time = 0.;
+        let mut last_progress = Instant::now();
+        let mut last_total_frames = playout_stats.total_frames();
+        let mut last_reopen: Option<Instant> = None;
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let context = match context.upgrade() {
+                Some(context) => context,
+                None => break,
+            };
+
+            let state = context.state();
+
+            if state == AudioContextState::Closed {
+                break;
+            }
+
+            let (device_error, plays_on_device) = match sink.lock().unwrap().as_ref() {
+                Some(sink) => (sink.device_error(), sink.plays_on_device()),
+                None => (None, false),
+            };
+
+            let current_time = context.current_time();
+
+            // the sink id of the upstream backend may change with `setSinkId`
+            playout_stats.set_available(plays_on_device || context.sink_id() == "none");
+
+            if !plays_on_device && state == AudioContextState::Running {
+                let frames = ((current_time - last_time) * context.sample_rate() as f64).round();
+                playout_stats.record_frames(frames.max(0.) as u64, 0);
+            }
+
+            if state != AudioContextState::Running || current_time != last_time {
+                last_time = current_time;
+                last_progress = Instant::now();
+            }
+
+            // the devices handled by this crate play frames (or silence) as
+            // long as they are alive, whatever the state of the context
+            let total_frames = playout_stats.total_frames();
+            let playing = total_frames != last_total_frames;
+            last_total_frames = total_frames;
+
+            for message in device_error.iter().flat_map(DeviceError::take_notified) {
+                status_tsfn.call(
+                    Ok(DeviceStatusEvent {
+                        type_: "error",
+                        message,
+                    }),
+                    ThreadsafeFunctionCallMode::Blocking,
+                );
+            }
+
+            let lost = match &device_error {
+                Some(device_error) => match device_error.get() {
+                    // the device played a whole poll interval since the error
+                    // was reported, i.e. it recovered
+                    Some(_) if interrupted && playing => {
+                        device_error.clear();
+                        None
+                    }
+                    Some(message) => Some(message),
+                    // wait for the reopened device to actually play
+                    None if interrupted && plays_on_device && !playing => Some(String::new()),
+                    None => None,
+                },
+                // the "none" sink can't be lost
+                None if context.sink_id() == "none" => None,
+                None if last_progress.elapsed() >= STALL_TIMEOUT => Some(format!(
+                    "Audio device '{}' stopped responding",
+                    context.sink_id()
+                )),
+                None => None,
+            };
+
+            match lost {
+                Some(message) if !interrupted => {
+                    interrupted = true;
+                    last_reopen = None;
+
+                    status_tsfn.call(
+                        Ok(DeviceStatusEvent {
+                            type_: "interrupted",
+                            message,
+                        }),
+                        ThreadsafeFunctionCallMode::Blocking,
+                    );
+
+                    // upstream dispatches the `sinkchange` event
+                    if policy == DeviceLossPolicy::Fallback
+                        && device_error.is_none()
+                        && !context.sink_id().is_empty()
+                    {
+                        if let Err(err) = context.set_sink_id_sync(String::new()) {
+                            status_tsfn.call(
+                                Ok(DeviceStatusEvent {
+                                    type_: "error",
+                                    message: format!(
+                                        "Failed to fallback to default audio device: {err}"
+                                    ),
+                                }),
+                                ThreadsafeFunctionCallMode::Blocking,
+                            );
+                        }
+
+                        last_progress = Instant::now();
+                    }
+                }
+                None if interrupted => {
+                    interrupted = false;
+
+                    status_tsfn.call(
+                        Ok(DeviceStatusEvent {
+                            type_: "resumed",
+                            message: String::new(),
+                        }),
+                        ThreadsafeFunctionCallMode::Blocking,
+                    );
+                }
+                _ => (),
+            }
+
+            let reopen = interrupted
+                && plays_on_device
+                && last_reopen.is_none_or(|instant| instant.elapsed() >= REOPEN_INTERVAL);
+
+            if reopen {
+                last_reopen = Some(Instant::now());
+                let fallback = policy == DeviceLossPolicy::Fallback;
+
+                // the sink is released once the context is closed
+                let res = match sink.lock().unwrap().as_mut() {
+                    Some(sink) if context.state() != AudioContextState::Closed => {
+                        sink.reopen(&context, fallback)
+                    }
+                    _ => Ok(None),
+                };
+
+                // on error the device is not back yet, try again later
+                if let Ok(Some(sink_id)) = res {
+                    status_tsfn.call(
+                        Ok(DeviceStatusEvent {
+                            type_: "sinkchange",
+                            message: sink_id,
+                        }),
+                        ThreadsafeFunctionCallMode::Blocking,
+                    );
+                }
+
+                last_total_frames = playout_stats.total_frames();
+            }
+        }
+    });
+}
@@ -0,0 +1,314 @@
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+
+use napi::*;
+use web_audio_api::context::{AudioContext, BaseAudioContext};
+use web_audio_api::node::{AudioNode, AudioNodeOptions, ChannelCountMode, ChannelInterpretation};
+use web_audio_api::worklet::{
+    AudioParamValues, AudioWorkletGlobalScope, AudioWorkletNode, AudioWorkletNodeOptions,
+    AudioWorkletProcessor,
+};
+
+type WarningCallback = Box<dyn FnMut(String) + Send>;
+
+/// Scheduling requested for a thread of an AudioContext
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ThreadSchedulingOptions {
+    /// SCHED_FIFO priority, in the [1, 99] range on Linux
+    priority: Option<u8>,
+    /// Indices of the CPU cores the thread is allowed to run on
+    cpu_affinity: Option<Vec<usize>>,
+}
+
+impl ThreadSchedulingOptions {
+    pub fn from_js(js_options: &JsObject) -> Result<Self> {
+        let priority =
+            match js_options.get_named_property::<Either<JsNumber, JsNull>>("priority")? {
+                Either::A(js_number) => Some(js_number.get_uint32()? as u8),
+                Either::B(_) => None,
+            };
+
+        let cpu_affinity =
+            match js_options.get_named_property::<Either<JsObject, JsNull>>("cpuAffinity")? {
+                Either::A(js_cpus) => {
+                    let length = js_cpus.get_array_length()?;
+                    let mut cpus = Vec::with_capacity(length as usize);
+
+                    for i in 0..length {
+                        let cpu = js_cpus.get_element::<JsNumber>(i)?.get_uint32()?;
+                        cpus.push(cpu as usize);
+                    }
+
+                    Some(cpus)
+                }
+                Either::B(_) => None,
+            };
+
+        Ok(Self {
+            priority,
+            cpu_affinity,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.priority.is_none() && self.cpu_affinity.is_none()
+    }
+
+    /// Apply the requested scheduling to the calling thread, and return a
+    /// description of each request that could not be satisfied
+    fn apply_to_current_thread(&self, thread_name: &str, lock_memory: bool) -> Vec<String> {
+        let mut warnings = vec![];
+
+        if lock_memory {
+            if let Err(msg) = lock_stack() {
+                warnings.push(format!(
+                    "Failed to lock the stack of the {thread_name} thread in memory: {msg}"
+                ));
+            }
+        }
+
+        if let Some(priority) = self.priority {
+            if let Err(msg) = set_realtime_priority(priority) {
+                warnings.push(format!(
+                    "Failed to set realtime priority {priority} on the {thread_name} thread: {msg}"
+                ));
+            }
+        }
+
+        if let Some(cpus) = &self.cpu_affinity {
+            if let Err(msg) = set_cpu_affinity(cpus) {
+                warnings.push(format!(
+                    "Failed to set CPU affinity {cpus:?} on the {thread_name} thread: {msg}"
+                ));
+            }
+        }
+
+        warnings
+    }
+}
+
+#[cfg(unix)]
+fn set_realtime_priority(priority: u8) -> std::result::Result<(), String> {
+    use thread_priority::unix::{
+        set_thread_priority_and_policy, thread_native_id, RealtimeThreadSchedulePolicy,
+        ThreadSchedulePolicy,
+    };
+    use thread_priority::{ThreadPriority, ThreadPriorityValue};
+
+    let value = ThreadPriorityValue::try_from(priority).map_err(|msg| msg.to_string())?;
+    let policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+
+    set_thread_priority_and_policy(
+        thread_native_id(),
+        ThreadPriority::Crossplatform(value),
+        policy,
+    )
+    .map_err(|err| err.to_string())
+}
+
+#[cfg(not(unix))]
+fn set_realtime_priority(_priority: u8) -> std::result::Result<(), String> {
+    Err(String::from("SCHED_FIFO is not supported on this platform"))
+}
+
+#[cfg(target_os = "linux")]
+fn set_cpu_affinity(cpus: &[usize]) -> std::result::Result<(), String> {
+    // SAFETY: cpu_set_t is a plain bitmask, and pid 0 targets the calling thread
+    unsafe {
+        let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+        libc::CPU_ZERO(&mut set);
+
+        for &cpu in cpus {
+            if cpu >= libc::CPU_SETSIZE as usize {
+                return Err(format!("CPU index {cpu} is out of range"));
+            }
+
+            libc::CPU_SET(cpu, &mut set);
+        }
+
+        match libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) {
+            0 => Ok(()),
+            _ => Err(std::io::Error::last_os_error().to_string()),
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_cpu_affinity(_cpus: &[usize]) -> std::result::Result<(), String> {
+    Err(String::from(
+        "CPU affinity is not supported on this platform",
+    ))
+}
+
+/// Lock the stack of the calling thread in memory, so that it never waits
+/// for a page of its stack to be swapped in
+///
+/// The memory of the whole process cannot be locked with `mlockall`, as V8
+/// aborts when it fails to release the pages of its heap.
+#[cfg(target_os = "linux")]
+fn lock_stack() -> std::result::Result<(), String> {
+    // SAFETY: the attributes are initialized by pthread_getattr_np before
+    // being read, and destroyed once the stack bounds are retrieved
+    unsafe {
+        let mut attr = std::mem::zeroed::<libc::pthread_attr_t>();
+
+        if libc::pthread_getattr_np(libc::pthread_self(), &mut attr) != 0 {
+            return Err(String::from("failed to retrieve the stack of the thread"));
+        }
+
+        let mut addr = std::ptr::null_mut();
+        let mut size = 0;
+        let res = libc::pthread_attr_getstack(&attr, &mut addr, &mut size);
+        libc::pthread_attr_destroy(&mut attr);
+
+        if res != 0 {
+            return Err(String::from("failed to retrieve the stack of the thread"));
+        }
+
+        match libc::mlock(addr, size) {
+            0 => Ok(()),
+            _ => Err(std::io::Error::last_os_error().to_string()),
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn lock_stack() -> std::result::Result<(), String> {
+    Err(String::from(
+        "memory locking is not supported on this platform",
+    ))
+}
+
+/// Warnings raised while applying the scheduling of an AudioContext
+///
+/// The render thread may start before the JS handler is bound, so warnings
+/// are queued until then.
+#[derive(Default)]
+struct WarningQueue {
+    pending: Vec<String>,
+    onwarning: Option<WarningCallback>,
+}
+
+/// Scheduling requested for the threads of an AudioContext
+pub(crate) struct ThreadScheduling {
+    render_thread: ThreadSchedulingOptions,
+    worklet_thread: ThreadSchedulingOptions,
+    lock_memory: bool,
+    warnings: Mutex<WarningQueue>,
+}
+
+impl ThreadScheduling {
+    pub fn new(
+        render_thread: ThreadSchedulingOptions,
+        worklet_thread: ThreadSchedulingOptions,
+        lock_memory: bool,
+    ) -> Self {
+        Self {
+            render_thread,
+            worklet_thread,
+            lock_memory,
+            warnings: Mutex::new(WarningQueue::default()),
+        }
+    }
+
+    /// Apply the AudioWorklet thread scheduling from within the Worker thread
+    ///
+    /// Returns false if no scheduling has been requested for this thread.
+    pub fn apply_to_worklet_thread(&self) -> bool {
+        if self.worklet_thread.is_empty() && !self.lock_memory {
+            return false;
+        }
+
+        self.worklet_thread
+            .apply_to_current_thread("AudioWorklet", self.lock_memory)
+            .into_iter()
+            .for_each(|msg| self.warn(msg));
+
+        true
+    }
+
+    fn warn(&self, message: String) {
+        let mut warnings = self.warnings.lock().unwrap();
+
+        match warnings.onwarning.as_mut() {
+            Some(callback) => callback(message),
+            None => warnings.pending.push(message),
+        }
+    }
+
+    pub fn set_onwarning<F: FnMut(String) + Send + 'static>(&self, mut callback: F) {
+        let mut warnings = self.warnings.lock().unwrap();
+        warnings.pending.drain(..).for_each(&mut callback);
+        warnings.onwarning = Some(Box::new(callback));
+    }
+
+    pub fn clear_onwarning(&self) {
+        self.warnings.lock().unwrap().onwarning.take();
+    }
+
+    /// Apply the render thread scheduling from within the render thread
+    ///
+    /// Does nothing if no render thread scheduling has been requested.
+    pub fn bind_render_thread(self: &Arc<Self>, context: &AudioContext) {
+        if self.render_thread.is_empty() && !self.lock_memory {
+            return;
+        }
+
+        let options = AudioWorkletNodeOptions {
+            number_of_inputs: 1,
+            number_of_outputs: 0,
+            output_channel_count: vec![],
+            parameter_data: Default::default(),
+            audio_node_options: AudioNodeOptions {
+                channel_count: 1,
+                channel_count_mode: ChannelCountMode::Explicit,
+                channel_interpretation: ChannelInterpretation::Discrete,
+            },
+            processor_options: RenderThreadProbe {
+                scheduling: Arc::clone(self),
+                thread_id: None,
+            },
+        };
+
+        let probe = AudioWorkletNode::new::<RenderThreadProbe>(context, options);
+        context.destination().connect(&probe);
+    }
+}
+
+/// Processor applying the render thread scheduling
+///
+/// The upstream crate does not expose its render thread, so the scheduling
+/// is applied from the first call of this processor, and again whenever the
+/// render thread changes, e.g. when the output stream is rebuilt by `setSinkId`.
+struct RenderThreadProbe {
+    scheduling: Arc<ThreadScheduling>,
+    thread_id: Option<ThreadId>,
+}
+
+impl AudioWorkletProcessor for RenderThreadProbe {
+    type ProcessorOptions = RenderThreadProbe;
+
+    fn constructor(opts: Self::ProcessorOptions) -> Self {
+        opts // the opts contain the full processor
+    }
+
+    fn process<'a, 'b>(
+        &mut self,
+        _inputs: &'b [&'a [&'a [f32]]],
+        _outputs: &'b mut [&'a mut [&'a mut [f32]]],
+        _params: AudioParamValues<'b>,
+        _scope: &'b AudioWorkletGlobalScope,
+    ) -> bool {
+        let thread_id = thread::current().id();
+
+        if self.thread_id.replace(thread_id) != Some(thread_id) {
+            self.scheduling
+                .render_thread
+                .apply_to_current_thread("render", self.scheduling.lock_memory)
+                .into_iter()
+                .for_each(|msg| self.scheduling.warn(msg));
+        }
+
+        true
+    }
+}
@@ -91,6 +91,7 @@ impl From<ThreadsafeFunctionCallMode> for sys::napi_threadsafe_function_call_mod
 pub struct ThreadsafeFunction<T: 'static> {
     raw_tsfn: sys::napi_threadsafe_function,
     aborted: Arc<AtomicBool>,
+    referred: Arc<AtomicBool>,
     ref_count: Arc<AtomicUsize>,
     _phantom: PhantomData<T>,
 }
@@ -108,6 +109,7 @@ impl<T: 'static> Clone for ThreadsafeFunction<T> {
         Self {
             raw_tsfn: self.raw_tsfn,
             aborted: Arc::clone(&self.aborted),
+            referred: Arc::clone(&self.referred),
             ref_count: Arc::clone(&self.ref_count),
             _phantom: PhantomData,
         }
@@ -162,6 +164,7 @@ impl<T: 'static> ThreadsafeFunction<T> {
         Ok(ThreadsafeFunction {
             raw_tsfn,
             aborted,
+            referred: Arc::new(AtomicBool::new(true)),
             ref_count: Arc::new(AtomicUsize::new(initial_thread_count)),
             _phantom: PhantomData,
         })
@@ -171,18 +174,50 @@ impl<T: 'static> ThreadsafeFunction<T> {
 impl<T: 'static> ThreadsafeFunction<T> {
     /// See [napi_call_threadsafe_function](https://nodejs.org/api/n-api.html#n_api_napi_call_threadsafe_function)
     /// for more information.
+    ///
+    /// If the call fails, e.g. if the queue is full in non blocking mode, the
+    /// value is dropped.
     pub fn call(&self, value: T, mode: ThreadsafeFunctionCallMode) -> Status {
         if self.aborted.load(Ordering::Acquire) {
             return Status::Closing;
         }
-        unsafe {
-            sys::napi_call_threadsafe_function(
-                self.raw_tsfn,
-                Box::into_raw(Box::new(value)) as *mut _,
-                mode.into(),
-            )
+
+        let data = Box::into_raw(Box::new(value));
+        let status =
+            unsafe { sys::napi_call_threadsafe_function(self.raw_tsfn, data.cast(), mode.into()) };
+
+        // the value is only owned by the queue if the call succeeded
+        if status != sys::Status::napi_ok {
+            drop(unsafe { Box::from_raw(data) });
+        }
+
+        status.into()
+    }
+
+    /// See [napi_ref_threadsafe_function](https://nodejs.org/api/n-api.html#napi_ref_threadsafe_function)
+    /// for more information.
+    ///
+    /// "ref" is a keyword so that we use "refer" here.
+    pub fn refer(&mut self, env: &Env) -> Result<()> {
+        if !self.aborted.load(Ordering::Acquire) && !self.referred.load(Ordering::Acquire) {
+            check_status!(unsafe { sys::napi_ref_threadsafe_function(env.raw(), self.raw_tsfn) })?;
+            self.referred.store(true, Ordering::Release);
         }
-        .into()
+
+        Ok(())
+    }
+
+    /// See [napi_unref_threadsafe_function](https://nodejs.org/api/n-api.html#napi_unref_threadsafe_function)
+    /// for more information.
+    pub fn unref(&mut self, env: &Env) -> Result<()> {
+        if !self.aborted.load(Ordering::Acquire) && self.referred.load(Ordering::Acquire) {
+            check_status!(unsafe {
+                sys::napi_unref_threadsafe_function(env.raw(), self.raw_tsfn)
+            })?;
+            self.referred.store(false, Ordering::Release);
+        }
+
+        Ok(())
     }
 }
 
@@ -0,0 +1,39 @@
+import { assert } from 'chai';
+import { AudioContext } from '../index.mjs';
+
+describe('# AudioContext.getClockInfo()', () => {
+  it('should map the last rendered frame to process.hrtime', async () => {
+    const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+
+    await new Promise(resolve => setTimeout(resolve, 300));
+
+    const before = process.hrtime.bigint();
+    const info = audioContext.getClockInfo();
+    const after = process.hrtime.bigint();
+
+    assert.isAbove(info.currentFrame, 0);
+    assert.equal(info.currentFrame % 128, 0);
+    assert.closeTo(info.currentTime, info.currentFrame / audioContext.sampleRate, 1e-9);
+    assert.typeOf(info.hrtime, 'bigint');
+    assert.isTrue(info.hrtime <= after);
+    // last quantum has been rendered recently
+    assert.isTrue(before - info.hrtime < 100_000_000n);
+    assert.typeOf(info.drift, 'number');
+
+    await audioContext.close();
+  });
+
+  it('should report a small drift once warmed up', async function() {
+    this.timeout(5000);
+
+    const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+
+    await new Promise(resolve => setTimeout(resolve, 2500));
+
+    const { drift } = audioContext.getClockInfo();
+    // 1% is very loose, but keeps the test robust on loaded CI machines
+    assert.isBelow(Math.abs(drift), 10000);
+
+    await audioContext.close();
+  });
+});
@@ -0,0 +1,96 @@
+import { assert } from 'chai';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+import { AudioContext, AudioPlaybackStats } from '../index.mjs';
+
+describe('# AudioContext.playbackStats', () => {
+  it('should expose the same AudioPlaybackStats instance as playoutStats', async () => {
+    const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+
+    assert.instanceOf(audioContext.playbackStats, AudioPlaybackStats);
+    assert.strictEqual(audioContext.playbackStats, audioContext.playoutStats);
+
+    await audioContext.close();
+  });
+
+  it('should count played frames cumulatively', async () => {
+    const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+    const stats = audioContext.playbackStats;
+
+    await new Promise(resolve => setTimeout(resolve, 600));
+    const totalFrames = stats.totalFrames;
+
+    assert.isAbove(totalFrames, 0);
+    assert.closeTo(stats.totalDuration, totalFrames / audioContext.sampleRate, 1e-9);
+    assert.equal(stats.underrunFrames, 0);
+    assert.equal(stats.underrunEvents, 0);
+
+    await new Promise(resolve => setTimeout(resolve, 300));
+    assert.isAtLeast(stats.totalFrames, totalFrames);
+
+    await audioContext.close();
+  });
+
+  it('should reset latency measurements', async () => {
+    const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+    const stats = audioContext.playbackStats;
+
+    stats.resetLatency();
+
+    assert.equal(stats.minimumLatency, 0);
+    assert.equal(stats.maximumLatency, 0);
+    assert.equal(stats.averageLatency, 0);
+    assert.deepEqual(Object.keys(stats.toJSON()).sort(), [
+      'averageLatency',
+      'maximumLatency',
+      'minimumLatency',
+      'totalDuration',
+      'totalFrames',
+      'underrunDuration',
+      'underrunEvents',
+      'underrunFrames',
+    ]);
+
+    await audioContext.close();
+  });
+
+  it('should count the frames recorded by a file sink', async () => {
+    const filePath = path.join(os.tmpdir(), `playback-stats-${process.pid}.wav`);
+    const audioContext = new AudioContext({ sinkId: { type: 'file', path: filePath } });
+    const stats = audioContext.playbackStats;
+
+    await new Promise(resolve => setTimeout(resolve, 600));
+
+    assert.isAbove(stats.totalFrames, 0);
+    assert.equal(stats.underrunFrames, 0);
+    assert.equal(stats.underrunEvents, 0);
+
+    await audioContext.close();
+    fs.rmSync(filePath);
+  });
+
+  it('should report underruns and latency as null on a device of the default backend', async function() {
+    let audioContext;
+
+    try {
+      audioContext = new AudioContext();
+    } catch (err) {
+      // no audio device available
+      this.skip();
+    }
+
+    const stats = audioContext.playbackStats;
+    await new Promise(resolve => setTimeout(resolve, 600));
+
+    assert.isAbove(stats.totalFrames, 0);
+    assert.isNull(stats.underrunFrames);
+    assert.isNull(stats.underrunDuration);
+    assert.isNull(stats.underrunEvents);
+    assert.isNull(stats.averageLatency);
+    assert.isNull(stats.minimumLatency);
+    assert.isNull(stats.maximumLatency);
+
+    await audioContext.close();
+  });
+});
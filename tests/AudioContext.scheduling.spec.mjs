@@ -0,0 +1,40 @@
+import { assert } from 'chai';
+import { AudioContext } from '../index.mjs';
+
+describe('# AudioContext scheduling options', () => {
+  it('should throw on invalid priority', () => {
+    assert.throws(() => new AudioContext({ renderThread: { priority: 0 } }), TypeError);
+    assert.throws(() => new AudioContext({ renderThread: { priority: 100 } }), TypeError);
+    assert.throws(() => new AudioContext({ workletThread: { priority: -1 } }), TypeError);
+  });
+
+  it('should throw on invalid cpuAffinity', () => {
+    assert.throws(() => new AudioContext({ renderThread: { cpuAffinity: 1 } }), TypeError);
+    assert.throws(() => new AudioContext({ renderThread: { cpuAffinity: [] } }), TypeError);
+    assert.throws(() => new AudioContext({ workletThread: { cpuAffinity: [-1] } }), TypeError);
+  });
+
+  it('should report unsatisfiable requests as process warnings', async () => {
+    const warnings = [];
+    const onWarning = warning => {
+      if (warning.name === 'AudioSchedulingWarning') {
+        warnings.push(warning.message);
+      }
+    };
+
+    process.on('warning', onWarning);
+
+    // no machine has that many cores
+    const audioContext = new AudioContext({
+      sinkId: { type: 'none' },
+      renderThread: { cpuAffinity: [1023] },
+    });
+
+    await new Promise(resolve => setTimeout(resolve, 200));
+    await audioContext.close();
+    process.off('warning', onWarning);
+
+    assert.isAbove(warnings.length, 0);
+    assert.match(warnings[0], /render thread/);
+  });
+});
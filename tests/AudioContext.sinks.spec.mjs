@@ -0,0 +1,264 @@
+import { assert } from 'chai';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+import { Writable } from 'node:stream';
+import { AudioContext, OfflineAudioContext, mediaDevices } from '../index.mjs';
+
+describe('# AudioContext sinks', () => {
+  describe('## { type: \'stream\' }', () => {
+    it('should push interleaved PCM chunks into the stream', async () => {
+      const chunks = [];
+      const stream = new Writable({
+        write(chunk, _encoding, callback) {
+          chunks.push(chunk);
+          callback();
+        },
+      });
+
+      const audioContext = new AudioContext({
+        sinkId: { type: 'stream', stream, format: 's16', bufferSize: 256 },
+      });
+
+      const src = audioContext.createConstantSource();
+      src.connect(audioContext.destination);
+      src.start();
+
+      await new Promise(resolve => setTimeout(resolve, 200));
+      await audioContext.close();
+
+      assert.isAbove(chunks.length, 0);
+      // 256 frames * 2 channels * 2 bytes
+      assert.equal(chunks[0].length, 256 * 2 * 2);
+      // constant source with offset 1 (first chunk may start with silence)
+      const last = chunks[chunks.length - 2];
+      assert.equal(last.readInt16LE(0), 32767);
+      assert.equal(last.readInt16LE(2), 32767);
+    });
+
+    it('should throw if stream is not writable', () => {
+      assert.throws(() => new AudioContext({ sinkId: { type: 'stream', stream: {} } }), TypeError);
+    });
+
+    it('should not allow to change the sink', async () => {
+      const stream = new Writable({ write(_chunk, _encoding, callback) { callback(); } });
+      const audioContext = new AudioContext({ sinkId: { type: 'stream', stream } });
+
+      let error = null;
+
+      try {
+        await audioContext.setSinkId('');
+      } catch (err) {
+        error = err;
+      }
+
+      await audioContext.close();
+
+      assert.equal(error.name, 'InvalidStateError');
+    });
+  });
+
+  describe('## { type: \'file\' }', () => {
+    it('should record the output into a wav file finalized on close', async () => {
+      const filename = path.join(os.tmpdir(), `node-web-audio-api-file-sink-${process.pid}.wav`);
+      const audioContext = new AudioContext({
+        sinkId: { type: 'file', path: filename, sampleFormat: 's16', numberOfChannels: 1 },
+      });
+
+      const src = audioContext.createConstantSource();
+      src.connect(audioContext.destination);
+      src.start();
+
+      await new Promise(resolve => setTimeout(resolve, 200));
+      await audioContext.close();
+
+      const file = fs.readFileSync(filename);
+      fs.unlinkSync(filename);
+
+      assert.equal(file.toString('ascii', 0, 4), 'RIFF');
+      // RIFF chunk size is finalized
+      assert.equal(file.readUInt32LE(4), file.length - 8);
+      // mono, 16 bits
+      assert.equal(file.readUInt16LE(22), 1);
+      assert.equal(file.readUInt16LE(34), 16);
+      assert.isAbove(file.length, 44);
+    });
+
+    it('should record the output into a flac file', async () => {
+      const filename = path.join(os.tmpdir(), `node-web-audio-api-file-sink-${process.pid}.flac`);
+      const audioContext = new AudioContext({
+        sinkId: { type: 'file', path: filename, format: 'flac' },
+      });
+
+      const osc = audioContext.createOscillator();
+      osc.frequency.value = 440;
+      osc.connect(audioContext.destination);
+      osc.start();
+
+      await new Promise(resolve => setTimeout(resolve, 300));
+      await audioContext.close();
+
+      const file = fs.readFileSync(filename);
+      fs.unlinkSync(filename);
+
+      assert.equal(file.toString('ascii', 0, 4), 'fLaC');
+
+      const offline = new OfflineAudioContext(1, 1, audioContext.sampleRate);
+      const buffer = await offline.decodeAudioData(file.buffer.slice(file.byteOffset, file.byteOffset + file.length));
+
+      assert.equal(buffer.numberOfChannels, 2);
+      assert.isAbove(buffer.length, 4096);
+
+      // the recorded sine follows x[n + 1] + x[n - 1] = 2 cos(w) x[n]
+      const data = buffer.getChannelData(1);
+      const start = data.findIndex(value => value !== 0) + 1;
+      const k = 2 * Math.cos(2 * Math.PI * 440 / audioContext.sampleRate);
+      let maxError = 0;
+
+      for (let i = start; i < data.length - 1; i++) {
+        maxError = Math.max(maxError, Math.abs(data[i + 1] + data[i - 1] - k * data[i]));
+      }
+
+      assert.isBelow(maxError, 1e-5);
+      assert.deepEqual(buffer.getChannelData(0), data);
+    });
+
+    it('should throw NotSupportedError for flac files with more than 8 channels', () => {
+      assert.throws(() => new AudioContext({ sinkId: { type: 'file', path: 'test.flac', format: 'flac', numberOfChannels: 9 } }), DOMException);
+    });
+
+    it('should dispatch an error event when the file cannot be written', async function() {
+      if (process.platform !== 'linux') {
+        this.skip();
+      }
+
+      // writing into /dev/full fails with ENOSPC
+      const audioContext = new AudioContext({
+        sinkId: { type: 'file', path: '/dev/full', format: 'flac' },
+      });
+
+      const osc = audioContext.createOscillator();
+      osc.connect(audioContext.destination);
+      osc.start();
+
+      const event = await new Promise(resolve => audioContext.addEventListener('error', resolve));
+
+      assert.include(event.message, 'Failed to write into file sink');
+      assert.equal(audioContext.state, 'interrupted');
+
+      await audioContext.close();
+    });
+  });
+
+  describe('## { type: \'custom\' }', () => {
+    it('should call onData with planar blocks of bufferSize frames', async () => {
+      const blocks = [];
+      const audioContext = new AudioContext({
+        sinkId: {
+          type: 'custom',
+          numberOfChannels: 3,
+          bufferSize: 512,
+          onData(channels) {
+            blocks.push(channels);
+          },
+        },
+      });
+
+      await new Promise(resolve => setTimeout(resolve, 200));
+      await audioContext.close();
+
+      assert.isAbove(blocks.length, 0);
+      assert.equal(blocks[0].length, 3);
+      blocks[0].forEach(channel => {
+        assert.isTrue(channel instanceof Float32Array);
+        assert.equal(channel.length, 512);
+      });
+    });
+
+    it('should dispatch the errors thrown by onData as error events', async () => {
+      const audioContext = new AudioContext({
+        sinkId: {
+          type: 'custom',
+          onData() {
+            throw new Error('transport failure');
+          },
+        },
+      });
+
+      const event = await new Promise(resolve => audioContext.addEventListener('error', resolve, { once: true }));
+      await audioContext.close();
+
+      assert.include(event.message, 'transport failure');
+      assert.equal(event.error.message, 'transport failure');
+    });
+
+    it('should throw if onData is not a function', () => {
+      assert.throws(() => new AudioContext({ sinkId: { type: 'custom' } }), TypeError);
+      assert.throws(() => new AudioContext({ sinkId: { type: 'custom', pull() {} } }), TypeError);
+    });
+  });
+
+  describe('## backend option', () => {
+    it('mediaDevices.enumerateBackends() should list the default backend', async () => {
+      const backends = await mediaDevices.enumerateBackends();
+      assert.isTrue(Array.isArray(backends));
+      assert.equal(backends[0], 'default');
+    });
+
+    it('should throw NotSupportedError for unknown backend', () => {
+      let error = null;
+
+      try {
+        new AudioContext({ backend: 'unknown' });
+      } catch (err) {
+        error = err;
+      }
+
+      assert.equal(error.name, 'NotSupportedError');
+    });
+  });
+
+  describe('## addSink / removeSink', () => {
+    async function catchError(promise) {
+      try {
+        await promise;
+      } catch (err) {
+        return err;
+      }
+
+      return null;
+    }
+
+    it('should reject with NotFoundError for unknown device', async () => {
+      const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+      const error = await catchError(audioContext.addSink('unknown-device-id'));
+
+      assert.equal(error.name, 'NotFoundError');
+      await audioContext.close();
+    });
+
+    it('should reject with NotSupportedError for the jack backend', async () => {
+      const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+      const error = await catchError(audioContext.addSink('', { backend: 'jack' }));
+
+      assert.equal(error.name, 'NotSupportedError');
+      await audioContext.close();
+    });
+
+    it('should reject with RangeError for invalid latency', async () => {
+      const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+      const error = await catchError(audioContext.addSink('', { latency: 0 }));
+
+      assert.instanceOf(error, RangeError);
+      await audioContext.close();
+    });
+
+    it('should reject with NotFoundError when removing an unknown sink', async () => {
+      const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+      const error = await catchError(audioContext.removeSink('unknown-device-id'));
+
+      assert.equal(error.name, 'NotFoundError');
+      await audioContext.close();
+    });
+  });
+});
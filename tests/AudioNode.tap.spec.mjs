@@ -0,0 +1,74 @@
+import { assert } from 'chai';
+import {
+  AudioNodeTap,
+  OfflineAudioContext,
+} from '../index.mjs';
+
+describe('# AudioNode.tap(options, callback)', () => {
+  it('should deliver the output of the node by blocks of bufferSize frames', async () => {
+    const offline = new OfflineAudioContext(1, 10000, 48000);
+
+    const src = offline.createConstantSource();
+    src.offset.value = 0.5;
+    src.connect(offline.destination);
+    src.start();
+
+    const blocks = [];
+    const tap = src.tap({ channels: 2, bufferSize: 1024 }, channels => blocks.push(channels));
+
+    assert.instanceOf(tap, AudioNodeTap);
+    assert.equal(tap.node, src);
+
+    const buffer = await offline.startRendering();
+    await tap.close();
+
+    // the tap does not alter the rendering
+    assert.deepEqual(buffer.getChannelData(0).slice(0, 4), new Float32Array([0.5, 0.5, 0.5, 0.5]));
+
+    blocks.forEach(channels => assert.equal(channels.length, 2));
+    blocks.slice(0, -1).forEach(channels => assert.equal(channels[0].length, 1024));
+
+    const frames = blocks.reduce((acc, channels) => acc + channels[0].length, 0);
+    assert.isAtLeast(frames, 10000);
+    assert.equal(blocks[0][1][0], 0.5);
+    assert.equal(tap.droppedFrames, 0);
+  });
+
+  it('should make the offline render thread wait for a slow callback', async () => {
+    const length = 48000 * 2;
+    const offline = new OfflineAudioContext(1, length, 48000);
+
+    const src = offline.createConstantSource();
+    src.connect(offline.destination);
+    src.start();
+
+    let frames = 0;
+    let zeros = 0;
+    const tap = src.tap({ channels: 1, bufferSize: 128 }, channels => {
+      frames += channels[0].length;
+      zeros += channels[0].filter(v => v !== 1).length;
+      // pull the blocks slower than the render thread renders them
+      const start = performance.now();
+      while (performance.now() - start < 0.2) {}
+    });
+
+    await offline.startRendering();
+    await tap.close();
+
+    // the ring buffer only holds a few blocks, nothing has been dropped
+    assert.equal(tap.droppedFrames, 0);
+    assert.isAtLeast(frames, length);
+    assert.equal(zeros, 0);
+  });
+
+  it('should validate its arguments', () => {
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    const gain = offline.createGain();
+
+    assert.throws(() => gain.tap({}), TypeError);
+    assert.throws(() => gain.tap({}, 'callback'), TypeError);
+    assert.throws(() => gain.tap({ channels: 0 }, () => {}), DOMException);
+    assert.throws(() => gain.tap({ bufferSize: 0 }, () => {}), DOMException);
+    assert.throws(() => gain.tap({ output: 1 }, () => {}), DOMException);
+  });
+});
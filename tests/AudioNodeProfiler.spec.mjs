@@ -0,0 +1,102 @@
+import { assert } from 'chai';
+import {
+  AudioNodeProfiler,
+  AudioWorkletNode,
+  OfflineAudioContext,
+} from '../index.mjs';
+
+describe('# AudioNodeProfiler', () => {
+  it('should attribute render time to observed nodes', async () => {
+    const offline = new OfflineAudioContext(2, 48000, 48000);
+    const profiler = offline.createNodeProfiler();
+
+    assert.instanceOf(profiler, AudioNodeProfiler);
+
+    const osc = offline.createOscillator();
+    const gain = offline.createGain();
+    osc.connect(gain).connect(offline.destination);
+    osc.start();
+
+    profiler.observe(osc);
+    profiler.observe(gain, 'master');
+
+    await offline.startRendering();
+
+    const report = profiler.getReport();
+    assert.equal(report.length, 2);
+
+    const master = report.find(entry => entry.node === gain);
+    assert.equal(master.label, 'master');
+    assert.isAbove(master.quanta, 0);
+    assert.isAbove(master.totalTime, 0);
+    assert.isAtLeast(master.maxTime, master.averageTime);
+
+    profiler.reset();
+    profiler.getReport().forEach(entry => assert.equal(entry.quanta, 0));
+
+    profiler.disconnect();
+    assert.equal(profiler.getReport().length, 0);
+  });
+
+  it('should not attribute the nodes connected after observe() to the observed node', async () => {
+    const offline = new OfflineAudioContext(1, 128 * 20, 48000);
+    const profiler = offline.createNodeProfiler();
+
+    // at least 20ms per render quantum
+    const code = `
+      registerProcessor('busy', class extends AudioWorkletProcessor {
+        process() {
+          const start = Date.now();
+          while (Date.now() - start < 20) {}
+          return true;
+        }
+      });
+    `;
+    const blob = new Blob([code], { type: 'application/javascript' });
+    await offline.audioWorklet.addModule(URL.createObjectURL(blob));
+
+    const src = offline.createConstantSource();
+    const gain = offline.createGain();
+    src.connect(gain).connect(offline.destination);
+    src.start();
+
+    profiler.observe(src);
+    profiler.observe(gain);
+
+    // fan-out connected after observe(), not observed
+    const busy = new AudioWorkletNode(offline, 'busy');
+    gain.connect(busy).connect(offline.destination);
+
+    await offline.startRendering();
+
+    const gainStats = profiler.getReport().find(entry => entry.node === gain);
+
+    assert.isAbove(gainStats.quanta, 0);
+    assert.isBelow(gainStats.averageTime, 0.01);
+  });
+
+  it('should throw when observing a node of another context', () => {
+    const a = new OfflineAudioContext(1, 128, 48000);
+    const b = new OfflineAudioContext(1, 128, 48000);
+    const profiler = a.createNodeProfiler();
+
+    assert.throws(() => profiler.observe(b.createGain()), DOMException);
+    assert.throws(() => profiler.observe({}), TypeError);
+  });
+
+  it('should reject unknown or released probe ids', () => {
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    const profiler = offline.createNodeProfiler();
+    const kNapiObj = Object.getOwnPropertySymbols(profiler)
+      .find(symbol => symbol.description === 'node-web-audio-api:napi-obj');
+    const napiObj = profiler[kNapiObj];
+
+    const gain = offline.createGain();
+    profiler.observe(gain);
+    profiler.unobserve(gain);
+
+    assert.throws(() => napiObj.getStats(0), /RangeError/);
+    assert.throws(() => napiObj.releaseProbe(0), /RangeError/);
+    assert.throws(() => napiObj.getStats(42), /RangeError/);
+  });
+});
@@ -0,0 +1,136 @@
+import { Blob } from 'node:buffer';
+import { assert } from 'chai';
+import { AudioRingBuffer, AudioWorkletNode, OfflineAudioContext } from '../index.mjs';
+
+describe('# AudioRingBuffer', () => {
+  it('should push and pull frames across the end of the buffer', () => {
+    const ring = new AudioRingBuffer({ numberOfChannels: 2, length: 4 });
+
+    assert.equal(ring.numberOfChannels, 2);
+    assert.equal(ring.length, 4);
+    assert.equal(ring.availableRead, 0);
+    assert.equal(ring.availableWrite, 4);
+
+    assert.equal(ring.push([new Float32Array([1, 2, 3]), new Float32Array([-1, -2, -3])]), 3);
+    assert.equal(ring.availableRead, 3);
+    assert.equal(ring.availableWrite, 1);
+
+    const left = new Float32Array(2);
+    const right = new Float32Array(2);
+    assert.equal(ring.pull([left, right]), 2);
+    assert.deepEqual(Array.from(left), [1, 2]);
+    assert.deepEqual(Array.from(right), [-1, -2]);
+
+    // wraps around, the missing channel is filled with zeros
+    assert.equal(ring.push([new Float32Array([4, 5, 6])]), 3);
+
+    const out = new Float32Array(4);
+    assert.equal(ring.pull([out]), 4);
+    assert.deepEqual(Array.from(out), [3, 4, 5, 6]);
+
+    ring.push([new Float32Array([7]), new Float32Array([-7])]);
+    ring.pull([left, right]);
+    assert.deepEqual(Array.from(right), [-7, 0]);
+  });
+
+  it('should count overrun and underrun frames', () => {
+    const ring = new AudioRingBuffer({ length: 4 });
+
+    assert.equal(ring.push([new Float32Array([1, 2, 3, 4, 5, 6])]), 4);
+    assert.equal(ring.overrunFrames, 2);
+
+    const out = new Float32Array(6).fill(-1);
+    assert.equal(ring.pull([out]), 4);
+    assert.deepEqual(Array.from(out), [1, 2, 3, 4, 0, 0]);
+    assert.equal(ring.underrunFrames, 2);
+  });
+
+  it('should share its memory with an instance created from its buffer', () => {
+    const producer = new AudioRingBuffer({ numberOfChannels: 1, length: 8 });
+    const consumer = new AudioRingBuffer(producer.buffer);
+
+    assert.instanceOf(producer.buffer, SharedArrayBuffer);
+    assert.equal(consumer.numberOfChannels, 1);
+    assert.equal(consumer.length, 8);
+
+    producer.push([new Float32Array([0.5, 0.25])]);
+    assert.equal(consumer.availableRead, 2);
+
+    const out = new Float32Array(2);
+    consumer.pull([out]);
+    assert.deepEqual(Array.from(out), [0.5, 0.25]);
+    assert.equal(producer.availableWrite, 8);
+  });
+
+  it('should throw on invalid arguments', () => {
+    assert.throws(() => new AudioRingBuffer(new SharedArrayBuffer(1024)), TypeError);
+    assert.throws(() => new AudioRingBuffer({ length: 0 }), DOMException);
+
+    const ring = new AudioRingBuffer({ length: 4 });
+    assert.throws(() => ring.push([new Float64Array(4)]), TypeError);
+    assert.throws(() => ring.push([new Float32Array(1), new Float32Array(1)]), DOMException);
+  });
+
+  it('should stay in bounds when its indices are corrupted', () => {
+    const ring = new AudioRingBuffer({ length: 4 });
+    // write and read indices, cf. `RingBufferHeader`
+    const header = new BigUint64Array(ring.buffer, 0, 24);
+    const kWriteIndex = 8;
+    const kReadIndex = 16;
+
+    // read index ahead of the write index, the buffer looks full
+    header[kWriteIndex] = 2n;
+    header[kReadIndex] = 1000n;
+    assert.equal(ring.availableRead, 4);
+    assert.equal(ring.availableWrite, 0);
+    assert.equal(ring.push([new Float32Array(8)]), 0);
+    assert.equal(ring.pull([new Float32Array(8)]), 4);
+
+    // write index too far ahead of the read index
+    header[kWriteIndex] = 2n ** 64n - 1n;
+    header[kReadIndex] = 0n;
+    assert.equal(ring.availableRead, 4);
+    assert.equal(ring.pull([new Float32Array(8)]), 4);
+    // the indices wrap around instead of overflowing
+    assert.equal(ring.push([new Float32Array(8)]), 0);
+    assert.equal(ring.pull([new Float32Array(8)]), 4);
+  });
+
+  it('should stream frames to an AudioWorkletProcessor', async () => {
+    const code = `
+      registerProcessor('ring-source', class extends AudioWorkletProcessor {
+        constructor(options) {
+          super();
+          this.ring = new AudioRingBuffer(options.processorOptions.buffer);
+        }
+
+        process(inputs, outputs) {
+          this.ring.pull(outputs[0]);
+          return true;
+        }
+      });
+    `;
+    const blob = new Blob([code], { type: 'application/javascript' });
+
+    const offline = new OfflineAudioContext(1, 128 * 2, 48000);
+    await offline.audioWorklet.addModule(URL.createObjectURL(blob));
+
+    const ring = new AudioRingBuffer({ length: 256 });
+    const frames = new Float32Array(128 + 64).map((_, i) => i + 1);
+    ring.push([frames]);
+
+    const node = new AudioWorkletNode(offline, 'ring-source', {
+      numberOfInputs: 0,
+      outputChannelCount: [1],
+      processorOptions: { buffer: ring.buffer },
+    });
+    node.connect(offline.destination);
+
+    const buffer = await offline.startRendering();
+    const data = buffer.getChannelData(0);
+
+    assert.deepEqual(Array.from(data.subarray(0, 128 + 64)), Array.from(frames));
+    assert.equal(data[128 + 64], 0);
+    assert.equal(ring.underrunFrames, 64);
+  });
+});
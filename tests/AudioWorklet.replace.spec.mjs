@@ -0,0 +1,69 @@
+import { Blob } from 'node:buffer';
+import { assert } from 'chai';
+import { AudioWorkletNode, OfflineAudioContext } from '../index.mjs';
+
+function createModule(value) {
+  const code = `
+    registerProcessor('counter-processor', class extends AudioWorkletProcessor {
+      constructor() {
+        super();
+        this.count = 0;
+      }
+
+      migrate(previous) {
+        this.count = previous.count;
+      }
+
+      process(inputs, outputs) {
+        this.count += 1;
+        // the state of the previous instance is kept
+        outputs[0][0].fill(${value} * this.count);
+        return true;
+      }
+    });
+  `;
+
+  const blob = new Blob([code], { type: 'application/javascript' });
+  return URL.createObjectURL(blob);
+}
+
+describe('# AudioWorklet.addModule(moduleUrl, { replace })', () => {
+  it('should swap the processors of a reloaded module', async () => {
+    const offline = new OfflineAudioContext(1, 128 * 4, 48000);
+    await offline.audioWorklet.addModule(createModule(1));
+
+    const node = new AudioWorkletNode(offline, 'counter-processor');
+    node.connect(offline.destination);
+
+    offline.suspend(128 * 2 / 48000).then(async () => {
+      await offline.audioWorklet.addModule(createModule(-1), { replace: true });
+      await offline.resume();
+    });
+
+    const buffer = await offline.startRendering();
+    const data = buffer.getChannelData(0);
+
+    assert.equal(data[0], 1);
+    assert.equal(data[128], 2);
+    assert.equal(data[128 * 2], -3);
+    assert.equal(data[128 * 3], -4);
+  });
+
+  it('should throw if a processor name is registered again without `replace`', async () => {
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    await offline.audioWorklet.addModule(createModule(1));
+
+    let error = null;
+
+    try {
+      await offline.audioWorklet.addModule(createModule(2));
+    } catch (err) {
+      error = err;
+    }
+
+    assert.instanceOf(error, DOMException);
+    assert.equal(error.name, 'NotSupportedError');
+
+    await offline.startRendering();
+  });
+});
@@ -1,6 +1,10 @@
 import { Blob } from 'node:buffer';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+import { pathToFileURL } from 'node:url';
 import { assert } from 'chai';
-import { AudioContext, OscillatorNode, AudioWorkletNode } from '../index.mjs';
+import { AudioContext, OscillatorNode, AudioWorkletNode, OfflineAudioContext } from '../index.mjs';
 
 const scriptTexts = `
 class FirstProcessor extends AudioWorkletProcessor {
@@ -60,6 +64,71 @@ describe('AudioWorklet', () => {
       assert.isFalse(errored);
     });
 
+    it(`should evaluate modules as ES modules`, async () => {
+      const dirname = fs.mkdtempSync(path.join(os.tmpdir(), 'worklet-'));
+      fs.mkdirSync(path.join(dirname, 'dsp'));
+      fs.writeFileSync(path.join(dirname, 'dsp', 'fill.js'), `
+        export const fill = (channel, value) => channel.fill(value);
+      `);
+      // static import relative to the module, dynamic import relative to the
+      // importing module
+      fs.writeFileSync(path.join(dirname, 'dsp', 'index.js'), `
+        export { fill } from './fill.js';
+        export const { fill: lazyFill } = await import('./fill.js');
+      `);
+      fs.writeFileSync(path.join(dirname, 'processor.js'), `
+        import { fill, lazyFill } from './dsp/index.js';
+
+        registerProcessor('fill-processor', class extends AudioWorkletProcessor {
+          process(inputs, outputs) {
+            (fill === lazyFill ? fill : () => {})(outputs[0][0], 0.5);
+            return true;
+          }
+        });
+      `);
+
+      const offline = new OfflineAudioContext(1, 128, 48000);
+      await offline.audioWorklet.addModule(path.join(dirname, 'processor.js'));
+
+      // absolute imports from a blob module
+      const code = `
+        import { fill } from '${pathToFileURL(path.join(dirname, 'dsp', 'fill.js')).href}';
+
+        registerProcessor('blob-fill-processor', class extends AudioWorkletProcessor {
+          process(inputs, outputs) {
+            fill(outputs[0][0], 0.25);
+            return true;
+          }
+        });
+      `;
+      const blob = new Blob([code], { type: 'application/javascript' });
+      await offline.audioWorklet.addModule(URL.createObjectURL(blob));
+
+      new AudioWorkletNode(offline, 'fill-processor').connect(offline.destination);
+      new AudioWorkletNode(offline, 'blob-fill-processor').connect(offline.destination);
+
+      const buffer = await offline.startRendering();
+      assert.equal(buffer.getChannelData(0)[0], 0.75);
+
+      fs.rmSync(dirname, { recursive: true });
+    });
+
+    it(`should reject on invalid modules`, async () => {
+      const offline = new OfflineAudioContext(1, 128, 48000);
+      const blob = new Blob([`import { fill } from './relative-to-blob.js';`], { type: 'application/javascript' });
+
+      let errored = false;
+
+      try {
+        await offline.audioWorklet.addModule(URL.createObjectURL(blob));
+      } catch (err) {
+        errored = true;
+      }
+
+      assert.isTrue(errored);
+      await offline.startRendering();
+    });
+
     it.skip(`should support loading from cwd relative path`, async () => {});
     it.skip(`should support loading from caller relative path`, async () => {});
     it.skip(`should support loading from url`, async () => {});
@@ -0,0 +1,155 @@
+import { Blob } from 'node:buffer';
+import { assert } from 'chai';
+import { AudioContext, AudioWorkletNode } from '../index.mjs';
+
+const scriptTexts = `
+// identifies the AudioWorkletGlobalScope, i.e. the thread
+const scope = Math.random();
+
+class ScopeProcessor extends AudioWorkletProcessor {
+  constructor() {
+    super();
+    this.port.postMessage(scope);
+  }
+
+  process(inputs, outputs) {
+    return true;
+  }
+}
+
+registerProcessor('scope-processor', ScopeProcessor);
+
+// outputs the frame at which the render quantum starts
+class FrameProcessor extends AudioWorkletProcessor {
+  process(inputs, outputs) {
+    outputs[0][0].fill(Number(currentFrame));
+    return true;
+  }
+}
+
+registerProcessor('frame-processor', FrameProcessor);
+
+// reports the latency of the frames received from a FrameProcessor
+class LatencyProcessor extends AudioWorkletProcessor {
+  process(inputs) {
+    const frame = inputs[0][0] ? inputs[0][0][0] : 0;
+
+    if (frame > 0) {
+      this.port.postMessage(Number(currentFrame) - frame);
+      return false;
+    }
+
+    return true;
+  }
+}
+
+registerProcessor('latency-processor', LatencyProcessor);
+`;
+
+function getScope(node) {
+  return new Promise(resolve => node.port.once('message', resolve));
+}
+
+describe('# AudioWorklet threads', () => {
+  it('should validate the `workletThreads` option', () => {
+    assert.throws(() => new AudioContext({ workletThreads: 0 }), DOMException);
+    assert.throws(() => new AudioContext({ workletThreads: -1 }), TypeError);
+  });
+
+  it('should distribute the processors over the threads', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const audioContext = new AudioContext({ sinkId: { type: 'none' }, workletThreads: 2 });
+    await audioContext.audioWorklet.addModule(objectUrl);
+
+    // round robin in order of creation
+    const a = new AudioWorkletNode(audioContext, 'scope-processor');
+    const b = new AudioWorkletNode(audioContext, 'scope-processor');
+    const c = new AudioWorkletNode(audioContext, 'scope-processor');
+    // explicitly pinned
+    const d = new AudioWorkletNode(audioContext, 'scope-processor', { workletThreadIndex: 1 });
+
+    const [scopeA, scopeB, scopeC, scopeD] = await Promise.all([a, b, c, d].map(getScope));
+
+    assert.notEqual(scopeA, scopeB);
+    assert.equal(scopeA, scopeC);
+    assert.equal(scopeB, scopeD);
+
+    assert.throws(() => {
+      new AudioWorkletNode(audioContext, 'scope-processor', { workletThreadIndex: 2 });
+    }, DOMException);
+
+    await audioContext.close();
+  });
+
+  it('should warn that the processors of nodes that are not pipelined run one after the other', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const warnings = [];
+    const onWarning = warning => {
+      if (warning.name === 'AudioWorkletWarning') {
+        warnings.push(warning);
+      }
+    };
+    process.on('warning', onWarning);
+
+    const single = new AudioContext({ sinkId: { type: 'none' } });
+    await single.audioWorklet.addModule(objectUrl);
+    new AudioWorkletNode(single, 'scope-processor');
+
+    const audioContext = new AudioContext({ sinkId: { type: 'none' }, workletThreads: 2 });
+    await audioContext.audioWorklet.addModule(objectUrl);
+    new AudioWorkletNode(audioContext, 'scope-processor', { pipelined: true });
+
+    // warnings are emitted on next tick
+    await new Promise(resolve => setImmediate(resolve));
+    assert.equal(warnings.length, 0);
+
+    new AudioWorkletNode(audioContext, 'scope-processor');
+    new AudioWorkletNode(audioContext, 'scope-processor');
+
+    await new Promise(resolve => setImmediate(resolve));
+    process.off('warning', onWarning);
+    // once per context
+    assert.equal(warnings.length, 1);
+
+    await single.close();
+    await audioContext.close();
+  });
+
+  it('should only delay the output of pipelined nodes', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const audioContext = new AudioContext({ sinkId: { type: 'none' }, workletThreads: 2 });
+    await audioContext.audioWorklet.addModule(objectUrl);
+
+    async function measureLatency(pipelined) {
+      // a missed render quantum would be silent
+      const source = new AudioWorkletNode(audioContext, 'frame-processor', {
+        numberOfInputs: 0,
+        workletThreadIndex: 0,
+        processDeadline: 1,
+        pipelined,
+      });
+      const sink = new AudioWorkletNode(audioContext, 'latency-processor', {
+        workletThreadIndex: 1,
+        processDeadline: 1,
+      });
+      source.connect(sink).connect(audioContext.destination);
+
+      const latency = await getScope(sink);
+      source.disconnect();
+      sink.disconnect();
+
+      return latency;
+    }
+
+    assert.equal(await measureLatency(false), 0);
+    assert.equal(await measureLatency(true), 128);
+
+    await audioContext.close();
+  });
+});
@@ -0,0 +1,277 @@
+import { Blob } from 'node:buffer';
+import { assert } from 'chai';
+import {
+  AudioWorkletNode,
+  OfflineAudioContext,
+} from '../index.mjs';
+
+// Minimal WebAssembly encoder, just enough to assemble the test module
+function uleb(n) {
+  const out = [];
+
+  do {
+    let byte = n & 0x7f;
+    n >>>= 7;
+    if (n !== 0) {
+      byte |= 0x80;
+    }
+    out.push(byte);
+  } while (n !== 0);
+
+  return out;
+}
+
+function sleb(n) {
+  const out = [];
+
+  while (true) {
+    const byte = n & 0x7f;
+    n >>= 7;
+
+    if ((n === 0 && (byte & 0x40) === 0) || (n === -1 && (byte & 0x40) !== 0)) {
+      out.push(byte);
+      return out;
+    }
+
+    out.push(byte | 0x80);
+  }
+}
+
+const str = s => [...uleb(Buffer.byteLength(s)), ...Buffer.from(s)];
+const vec = items => [...uleb(items.length), ...items.flat()];
+const section = (id, content) => [id, ...uleb(content.length), ...content];
+const func = (params, results) => [0x60, ...vec(params), ...vec(results)];
+const body = (locals, code) => {
+  const bytes = [...vec(locals), ...code, 0x0b];
+  return [...uleb(bytes.length), ...bytes];
+};
+const [i32, i64, f32] = [0x7f, 0x7e, 0x7d];
+
+// - 'wasm-gain' outputs the values of its 'gain' param and echoes the messages
+// - 'wasm-trap' traps on each render quantum
+// - 'wasm-loop' never returns from its first render quantum
+function assemble(descriptors) {
+  const json = [...Buffer.from(JSON.stringify(descriptors)), 0];
+  return new Uint8Array([
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+    ...section(1, vec([
+      func([i32, i32], []),
+      func([], [i32]),
+      func([i32], [i32]),
+      func([i32, f32], [i32]),
+      func([i32, i32, i32, i32, i64], [i32]),
+      func([i32, i32, i32], []),
+    ])),
+    ...section(2, vec([[...str('env'), ...str('awp_post_message'), 0x00, 0]])),
+    ...section(3, vec([[1], [2], [3], [4], [5]])),
+    ...section(5, vec([[0x00, 4]])),
+    ...section(6, vec([[i32, 0x01, 0x41, ...sleb(1024), 0x0b]])),
+    ...section(7, vec([
+      [...str('memory'), 0x02, 0],
+      [...str('awp_descriptors'), 0x00, 1],
+      [...str('awp_alloc'), 0x00, 2],
+      [...str('awp_create'), 0x00, 3],
+      [...str('awp_process'), 0x00, 4],
+      [...str('awp_message'), 0x00, 5],
+    ])),
+    ...section(10, vec([
+      body([], [0x41, 16]),
+      body([], [
+        0x23, 0, 0x23, 0, 0x20, 0, 0x41, 15, 0x6a, 0x41, ...sleb(-16), 0x71, 0x6a, 0x24, 0,
+      ]),
+      body([], [0x20, 0]),
+      body([[4, i32]], [
+        0x20, 0, 0x41, 1, 0x46, 0x04, 0x40, 0x00, 0x0b,
+        0x20, 0, 0x41, 2, 0x46, 0x04, 0x40, 0x03, 0x40, 0x0c, 0, 0x0b, 0x0b,
+        0x20, 2, 0x28, 0x02, 0x04, 0x21, 5,
+        0x20, 3, 0x28, 0x02, 0x04, 0x21, 6,
+        0x20, 3, 0x28, 0x02, 0x00, 0x21, 7,
+        0x41, 0, 0x21, 8,
+        0x03, 0x40,
+        0x20, 5, 0x20, 8, 0x6a,
+        0x20, 6, 0x20, 8, 0x41, 0, 0x20, 7, 0x41, 1, 0x47, 0x1b, 0x6a, 0x2a, 0x02, 0x00,
+        0x38, 0x02, 0x00,
+        0x20, 8, 0x41, 4, 0x6a, 0x22, 8, 0x41, ...sleb(512), 0x49, 0x0d, 0,
+        0x0b,
+        0x41, 1,
+      ]),
+      body([], [0x20, 1, 0x20, 2, 0x10, 0]),
+    ])),
+    ...section(11, vec([[0x00, 0x41, 16, 0x0b, ...vec(json)]])),
+  ]);
+}
+
+const descriptors = [
+  {
+    name: 'wasm-gain',
+    parameterDescriptors: [{ name: 'gain', defaultValue: 0.5, minValue: 0, maxValue: 1 }],
+  },
+  { name: 'wasm-trap' },
+  { name: 'wasm-loop' },
+];
+
+function moduleUrl(descriptors) {
+  const blob = new Blob([assemble(descriptors)], { type: 'application/wasm' });
+  return URL.createObjectURL(blob);
+}
+
+describe('# AudioWorklet.addWasmModule(moduleUrl)', () => {
+  it('should run the processor on the render thread', async () => {
+    const offline = new OfflineAudioContext(1, 1000, 48000);
+    await offline.audioWorklet.addWasmModule(moduleUrl(descriptors));
+
+    const node = new AudioWorkletNode(offline, 'wasm-gain', {
+      numberOfInputs: 0,
+      outputChannelCount: [1],
+    });
+    node.connect(offline.destination);
+
+    const gain = node.parameters.get('gain');
+    assert.equal(gain.value, 0.5);
+    gain.setValueAtTime(0.25, 500 / 48000);
+
+    const buffer = await offline.startRendering();
+    const data = buffer.getChannelData(0);
+
+    assert.equal(data[0], 0.5);
+    assert.equal(data[499], 0.5);
+    assert.equal(data[500], 0.25);
+    assert.equal(data[999], 0.25);
+  });
+
+  it('should exchange messages through the port', async () => {
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    await offline.audioWorklet.addWasmModule(moduleUrl(descriptors));
+
+    const node = new AudioWorkletNode(offline, 'wasm-gain', {
+      numberOfInputs: 0,
+      outputChannelCount: [1],
+    });
+    node.connect(offline.destination);
+
+    const echo = new Promise(resolve => {
+      node.port.on('message', data => {
+        node.port.close();
+        resolve(Buffer.from(data).toString());
+      });
+    });
+
+    node.port.postMessage('hello');
+    // the message is handed over to the render thread asynchronously
+    await new Promise(resolve => setTimeout(resolve, 10));
+    await offline.startRendering();
+
+    assert.equal(await echo, 'hello');
+  });
+
+  it('should dispatch processorerror if the processor traps', async () => {
+    const offline = new OfflineAudioContext(1, 256, 48000);
+    await offline.audioWorklet.addWasmModule(moduleUrl(descriptors));
+
+    const node = new AudioWorkletNode(offline, 'wasm-trap', {
+      numberOfInputs: 0,
+      outputChannelCount: [1],
+    });
+    node.connect(offline.destination);
+
+    const error = new Promise(resolve => node.onprocessorerror = resolve);
+    const buffer = await offline.startRendering();
+    const event = await error;
+
+    assert.match(event.message, /^Failed to execute 'process' on 'wasm-trap' AudioWorkletProcessor/);
+    assert.deepEqual(buffer.getChannelData(0).slice(0, 4), new Float32Array(4));
+  });
+
+  it('should stop processors exceeding the instruction limit', async () => {
+    const offline = new OfflineAudioContext(1, 256, 48000);
+    await offline.audioWorklet.addWasmModule(moduleUrl(descriptors));
+
+    const node = new AudioWorkletNode(offline, 'wasm-loop', {
+      numberOfInputs: 0,
+      outputChannelCount: [1],
+    });
+    node.connect(offline.destination);
+
+    const error = new Promise(resolve => node.onprocessorerror = resolve);
+    const buffer = await offline.startRendering();
+    const event = await error;
+
+    assert.include(event.message, 'exceeded the limit');
+    assert.deepEqual(buffer.getChannelData(0), new Float32Array(256));
+  });
+
+  it('should render faster than the equivalent JavaScript processor', async function() {
+    this.timeout(60000);
+
+    const code = `
+      registerProcessor('js-gain', class extends AudioWorkletProcessor {
+        static get parameterDescriptors() {
+          return [{ name: 'gain', defaultValue: 0.5, minValue: 0, maxValue: 1 }];
+        }
+
+        process(inputs, outputs, parameters) {
+          const gain = parameters.gain;
+          outputs[0][0].forEach((_, i, channel) => channel[i] = gain[gain.length > 1 ? i : 0]);
+          return true;
+        }
+      });
+    `;
+    const blob = new Blob([code], { type: 'application/javascript' });
+    const jsUrl = URL.createObjectURL(blob);
+
+    async function renderTime(name) {
+      const offline = new OfflineAudioContext(1, 128 * 200, 48000);
+      await offline.audioWorklet.addWasmModule(moduleUrl(descriptors));
+      await offline.audioWorklet.addModule(jsUrl);
+
+      const node = new AudioWorkletNode(offline, name, {
+        numberOfInputs: 0,
+        outputChannelCount: [1],
+      });
+      node.connect(offline.destination);
+
+      const start = performance.now();
+      const buffer = await offline.startRendering();
+      const duration = performance.now() - start;
+
+      assert.equal(buffer.getChannelData(0)[128 * 200 - 1], 0.5);
+      return duration;
+    }
+
+    const jsTime = await renderTime('js-gain');
+    const wasmTime = await renderTime('wasm-gain');
+
+    assert.isBelow(wasmTime, jsTime);
+  });
+
+  it('should reject invalid modules', async () => {
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    const blob = new Blob([new Uint8Array([0, 1, 2, 3])]);
+
+    let error = null;
+
+    try {
+      await offline.audioWorklet.addWasmModule(URL.createObjectURL(blob));
+    } catch (err) {
+      error = err;
+    }
+
+    assert.instanceOf(error, TypeError);
+  });
+
+  it('should not register the same processor twice', async () => {
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    await offline.audioWorklet.addWasmModule(moduleUrl(descriptors));
+
+    let error = null;
+
+    try {
+      await offline.audioWorklet.addWasmModule(moduleUrl(descriptors));
+    } catch (err) {
+      error = err;
+    }
+
+    assert.instanceOf(error, DOMException);
+    assert.equal(error.name, 'NotSupportedError');
+  });
+});
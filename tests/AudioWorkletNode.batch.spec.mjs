@@ -0,0 +1,163 @@
+import { Blob } from 'node:buffer';
+import { assert } from 'chai';
+import {
+  AudioContext,
+  AudioWorkletNode,
+  DelayNode,
+  OfflineAudioContext,
+  OscillatorNode,
+} from '../index.mjs';
+
+const scriptTexts = `
+class IndexProcessor extends AudioWorkletProcessor {
+  process(inputs, outputs) {
+    // the index of the render quantum and the time at which it starts
+    outputs[0][0].fill(Number(currentFrame) / renderQuantumSize);
+    outputs[0][1].fill(currentTime * sampleRate / renderQuantumSize);
+    return true;
+  }
+}
+
+registerProcessor('index-processor', IndexProcessor);
+
+class GainProcessor extends AudioWorkletProcessor {
+  static get parameterDescriptors() {
+    return [{ name: 'gain' }];
+  }
+
+  process(inputs, outputs, parameters) {
+    const gain = parameters.gain;
+
+    for (let channel = 0; channel < inputs[0].length; channel++) {
+      for (let i = 0; i < renderQuantumSize; i++) {
+        outputs[0][channel][i] = inputs[0][channel][i] * gain[gain.length > 1 ? i : 0];
+      }
+    }
+
+    return true;
+  }
+}
+
+registerProcessor('gain-processor', GainProcessor);
+`;
+
+describe('# AudioWorkletNode batchQuanta', () => {
+  it('should process batches of render quanta without latency', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const offline = new OfflineAudioContext(2, 128 * 10, 48000);
+    await offline.audioWorklet.addModule(objectUrl);
+
+    const node = new AudioWorkletNode(offline, 'index-processor', {
+      numberOfInputs: 0,
+      outputChannelCount: [2],
+      batchQuanta: 4,
+    });
+    node.connect(offline.destination);
+
+    const buffer = await offline.startRendering();
+    const expected = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+
+    for (let channel = 0; channel < 2; channel++) {
+      const data = buffer.getChannelData(channel);
+      const values = expected.map((_, i) => data[i * 128]);
+      assert.deepEqual(values, expected);
+    }
+
+    // the last batch stops at the end of the rendering
+    assert.equal(node.getStats().quanta, 10);
+  });
+
+  // an oscillator through a gain processor whose gain is automated, so that
+  // each render quantum has its own inputs and params
+  async function renderEffect(objectUrl, batchQuanta, feedback = false) {
+    const offline = new OfflineAudioContext(1, 128 * 10, 48000);
+    await offline.audioWorklet.addModule(objectUrl);
+
+    const src = new OscillatorNode(offline, { frequency: 440 });
+    const node = new AudioWorkletNode(offline, 'gain-processor', { batchQuanta });
+    node.parameters.get('gain')
+      .setValueAtTime(0.5, 0)
+      .linearRampToValueAtTime(1, 128 * 10 / offline.sampleRate);
+
+    src.connect(node).connect(offline.destination);
+    src.start();
+
+    if (feedback) {
+      const delay = new DelayNode(offline, { delayTime: 128 / offline.sampleRate });
+      node.connect(delay).connect(node);
+    }
+
+    const buffer = await offline.startRendering();
+
+    return { node, data: Array.from(buffer.getChannelData(0)) };
+  }
+
+  it('should process batches of render quanta of an effect with a fixed latency', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const unbatched = await renderEffect(objectUrl, 1);
+    const batched = await renderEffect(objectUrl, 4);
+
+    assert.equal(unbatched.node.batchLatency, 0);
+    assert.equal(batched.node.batchLatency, 3 * 128 / 48000);
+
+    // the output is delayed by `batchQuanta - 1` render quanta
+    const latency = Math.round(batched.node.batchLatency * 48000);
+    const expected = new Array(latency).fill(0).concat(unbatched.data.slice(0, -latency));
+    assert.deepEqual(batched.data, expected);
+
+    // the last batch is processed at the end of the rendering
+    assert.equal(batched.node.getStats().quanta, 10);
+  });
+
+  it('should not batch the render quanta of a node connected in a cycle', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const unbatched = await renderEffect(objectUrl, 1, true);
+    const batched = await renderEffect(objectUrl, 4, true);
+
+    assert.equal(batched.node.batchLatency, 0);
+    assert.deepEqual(batched.data, unbatched.data);
+
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    await offline.audioWorklet.addModule(objectUrl);
+
+    // cycle through a param
+    const node = new AudioWorkletNode(offline, 'gain-processor', { batchQuanta: 4 });
+    const delay = new DelayNode(offline);
+    node.connect(delay).connect(node.parameters.get('gain'));
+    assert.equal(node.batchLatency, 0);
+
+    // removed connections do not make cycles
+    const other = new AudioWorkletNode(offline, 'gain-processor', { batchQuanta: 4 });
+    const otherDelay = new DelayNode(offline);
+    otherDelay.connect(other);
+    otherDelay.disconnect(other);
+    other.connect(otherDelay);
+    assert.equal(other.batchLatency, 3 * 128 / 48000);
+  });
+
+  it('should validate its options', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    await offline.audioWorklet.addModule(objectUrl);
+
+    assert.throws(() => new AudioWorkletNode(offline, 'index-processor', { batchQuanta: 0 }), RangeError);
+    assert.throws(() => new AudioWorkletNode(offline, 'index-processor', { numberOfInputs: 0, batchQuanta: 4, processDeadline: 0.01 }), DOMException);
+
+    const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+    await audioContext.audioWorklet.addModule(objectUrl);
+
+    assert.throws(() => new AudioWorkletNode(audioContext, 'index-processor', { numberOfInputs: 0, batchQuanta: 4 }), DOMException);
+    // a single render quantum per round-trip is the default
+    new AudioWorkletNode(audioContext, 'index-processor', { batchQuanta: 1 });
+
+    await audioContext.close();
+  });
+});
@@ -0,0 +1,76 @@
+import { Blob } from 'node:buffer';
+import { assert } from 'chai';
+import { AudioWorkletNode, ConstantSourceNode, OfflineAudioContext } from '../index.mjs';
+
+const scriptTexts = `
+class GainProcessor extends AudioWorkletProcessor {
+  static get parameterDescriptors() {
+    return [{ name: 'gain', defaultValue: 0.5 }];
+  }
+
+  constructor() {
+    super();
+    this.last = { inputs: null, outputs: null, parameters: null };
+    this.rebuilds = { inputs: 0, outputs: 0, parameters: 0 };
+  }
+
+  process(inputs, outputs, parameters) {
+    for (const [name, arrays] of Object.entries({ inputs, outputs, parameters })) {
+      if (arrays !== this.last[name]) {
+        this.last[name] = arrays;
+        this.rebuilds[name] += 1;
+      }
+    }
+
+    const input = inputs[0][0];
+    const output = outputs[0][0];
+    const gain = parameters.gain;
+
+    for (let i = 0; i < output.length; i++) {
+      output[i] = input[i] * (gain.length === 1 ? gain[0] : gain[i]);
+    }
+
+    if (currentFrame === BigInt(128 * 5)) {
+      this.port.postMessage(this.rebuilds);
+    }
+
+    return true;
+  }
+}
+
+registerProcessor('gain-processor', GainProcessor);
+`;
+
+describe('# AudioWorkletNode inputs and outputs', () => {
+  it('should process inputs and params in place', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const offline = new OfflineAudioContext(1, 128 * 6, 48000);
+    await offline.audioWorklet.addModule(objectUrl);
+
+    const src = new ConstantSourceNode(offline, { offset: 2 });
+    const node = new AudioWorkletNode(offline, 'gain-processor', {
+      outputChannelCount: [1],
+    });
+    src.connect(node).connect(offline.destination);
+    src.start();
+
+    // a-rate values during the second render quantum only
+    const gain = node.parameters.get('gain');
+    gain.setValueAtTime(0.5, 128 / 48000);
+    gain.linearRampToValueAtTime(1, 256 / 48000);
+    gain.setValueAtTime(0.25, 256 / 48000);
+
+    const rebuilds = new Promise(resolve => node.port.once('message', resolve));
+    const buffer = await offline.startRendering();
+    const data = buffer.getChannelData(0);
+
+    assert.equal(data[0], 1);
+    assert.isAbove(data[255], data[129]);
+    assert.equal(data[128 * 3], 0.5);
+    // the arrays given to `process` are reused while their layout does not
+    // change, the params switching between 1 and 128 values
+    assert.deepEqual(await rebuilds, { inputs: 1, outputs: 1, parameters: 3 });
+  });
+});
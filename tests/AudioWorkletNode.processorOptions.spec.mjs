@@ -0,0 +1,88 @@
+import { Blob } from 'node:buffer';
+import { assert } from 'chai';
+import { AudioBuffer, AudioWorkletNode, OfflineAudioContext } from '../index.mjs';
+
+const scriptTexts = `
+class SamplerProcessor extends AudioWorkletProcessor {
+  constructor(options) {
+    super();
+
+    const { sample, samples } = options.processorOptions;
+    this.sample = sample;
+    this.index = 0;
+
+    // the arrays returned by getChannelData are copies
+    const copy = sample.getChannelData(1);
+    const rightSample = copy[1];
+    copy.fill(42);
+    const other = new Float32Array(2);
+    sample.copyFromChannel(other, 1);
+
+    this.port.postMessage({
+      sameBuffer: samples[0] === sample && samples[1] === sample,
+      sampleRate: sample.sampleRate,
+      length: sample.length,
+      numberOfChannels: sample.numberOfChannels,
+      isFloat32Array: copy instanceof Float32Array,
+      sameArray: sample.getChannelData(1) === copy,
+      readOnly: other[1] === rightSample,
+    });
+  }
+
+  process(inputs, outputs) {
+    const output = outputs[0];
+
+    for (let channel = 0; channel < output.length; channel++) {
+      this.sample.copyFromChannel(output[channel], channel, this.index);
+    }
+
+    this.index += renderQuantumSize;
+    return true;
+  }
+}
+
+registerProcessor('sampler-processor', SamplerProcessor);
+`;
+
+describe('# AudioWorkletNode processorOptions', () => {
+  it('should give the channel data of AudioBuffers to the processor', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const offline = new OfflineAudioContext(2, 128 * 2, 48000);
+    await offline.audioWorklet.addModule(objectUrl);
+
+    const sample = new AudioBuffer({ numberOfChannels: 2, length: 256, sampleRate: 48000 });
+    const left = new Float32Array(256).map((_, i) => i / 256);
+    const right = new Float32Array(256).map((_, i) => -i / 256);
+    sample.copyToChannel(left, 0);
+    sample.copyToChannel(right, 1);
+
+    const node = new AudioWorkletNode(offline, 'sampler-processor', {
+      numberOfInputs: 0,
+      outputChannelCount: [2],
+      processorOptions: { sample, samples: [sample, sample] },
+    });
+    node.connect(offline.destination);
+    // the channels of the main thread buffer are copied before being written
+    sample.copyToChannel(new Float32Array(256), 0);
+
+    const info = await new Promise(resolve => node.port.once('message', resolve));
+
+    assert.deepEqual(info, {
+      sameBuffer: true,
+      sampleRate: 48000,
+      length: 256,
+      numberOfChannels: 2,
+      isFloat32Array: true,
+      sameArray: true,
+      readOnly: true,
+    });
+
+    const buffer = await offline.startRendering();
+
+    assert.deepEqual(buffer.getChannelData(0), left);
+    assert.deepEqual(buffer.getChannelData(1), right);
+    assert.deepEqual(sample.getChannelData(0), new Float32Array(256));
+  });
+});
@@ -0,0 +1,63 @@
+import { Blob } from 'node:buffer';
+import { assert } from 'chai';
+import { AudioWorkletNode, OfflineAudioContext } from '../index.mjs';
+
+// duration of a render quantum at 48kHz
+const BUDGET = 128 / 48000;
+// the chosen quanta busy-wait 3 times the duration of a render quantum
+const SLOW_TIME = 3 * BUDGET;
+
+const scriptTexts = `
+class SlowProcessor extends AudioWorkletProcessor {
+  process(inputs, outputs) {
+    // quanta 3 and 7
+    if ((Number(currentFrame) / 128) % 4 === 3) {
+      const end = performance.now() + ${SLOW_TIME * 1000};
+      while (performance.now() < end) {}
+    }
+
+    return true;
+  }
+}
+
+registerProcessor('slow-processor', SlowProcessor);
+`;
+
+describe('# AudioWorkletNode.getStats()', () => {
+  it('should report the durations of the process calls', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const offline = new OfflineAudioContext(1, 128 * 8, 48000);
+    await offline.audioWorklet.addModule(objectUrl);
+
+    const node = new AudioWorkletNode(offline, 'slow-processor', { numberOfInputs: 0 });
+    node.connect(offline.destination);
+
+    assert.equal(node.getStats().quanta, 0);
+
+    await offline.startRendering();
+
+    const stats = node.getStats();
+
+    assert.equal(stats.quanta, 8);
+    // only lower bounds, any other quantum may be late on a loaded machine
+    assert.isAtLeast(stats.lateQuanta, 2);
+    assert.isAtLeast(stats.maxTime, SLOW_TIME);
+    // the 99th percentile of 8 calls is the slowest one, within 12.5%
+    assert.isAtLeast(stats.p99Time, SLOW_TIME * 0.875);
+    assert.isAtMost(stats.p99Time, stats.maxTime);
+    assert.isAtLeast(stats.averageTime, 2 * SLOW_TIME / 8);
+    assert.isAtMost(stats.averageTime, stats.maxTime);
+
+    node.resetStats();
+
+    assert.deepEqual(node.getStats(), {
+      quanta: 0,
+      averageTime: 0,
+      maxTime: 0,
+      p99Time: 0,
+      lateQuanta: 0,
+    });
+  });
+});
@@ -0,0 +1,68 @@
+import { Blob } from 'node:buffer';
+import { assert } from 'chai';
+import { AudioWorkletNode, OfflineAudioContext } from '../index.mjs';
+
+const scriptTexts = `
+class BlockingProcessor extends AudioWorkletProcessor {
+  constructor() {
+    super();
+    this.blocked = new Int32Array(new SharedArrayBuffer(4));
+  }
+
+  process(inputs, outputs) {
+    // block the worklet thread, e.g. as a long garbage collection would do
+    Atomics.wait(this.blocked, 0, 0, 50);
+    outputs[0][0].fill(1);
+    return true;
+  }
+}
+
+registerProcessor('blocking-processor', BlockingProcessor);
+`;
+
+describe('# AudioWorkletNode watchdog', () => {
+  it('should give up on a processor missing its deadline', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const offline = new OfflineAudioContext(1, 128 * 20, 48000);
+    await offline.audioWorklet.addModule(objectUrl);
+
+    const node = new AudioWorkletNode(offline, 'blocking-processor', {
+      numberOfInputs: 0,
+      processDeadline: 0.005,
+      maxMissedQuanta: 4,
+    });
+    node.connect(offline.destination);
+
+    const errors = [];
+    node.onprocessorerror = e => errors.push(e);
+
+    const buffer = await offline.startRendering();
+    // let the event be dispatched
+    await new Promise(resolve => setTimeout(resolve, 100));
+
+    assert.equal(errors.length, 1);
+    assert.equal(node.missedQuanta, 4);
+    // the missed render quanta and the following ones are silent
+    assert.deepEqual(buffer.getChannelData(0).slice(-128), new Float32Array(128));
+  });
+
+  it('should validate its options', async () => {
+    const blob = new Blob([scriptTexts], { type: 'application/javascript' });
+    const objectUrl = URL.createObjectURL(blob);
+
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    await offline.audioWorklet.addModule(objectUrl);
+
+    assert.throws(() => new AudioWorkletNode(offline, 'blocking-processor', { processDeadline: 0 }), RangeError);
+    assert.throws(() => new AudioWorkletNode(offline, 'blocking-processor', { processDeadline: NaN }), RangeError);
+    assert.throws(() => new AudioWorkletNode(offline, 'blocking-processor', { maxMissedQuanta: 0 }), RangeError);
+    assert.throws(() => new AudioWorkletNode(offline, 'blocking-processor', { maxMissedQuanta: -1 }), TypeError);
+
+    const node = new AudioWorkletNode(offline, 'blocking-processor');
+    assert.equal(node.missedQuanta, 0);
+
+    await offline.startRendering();
+  });
+});
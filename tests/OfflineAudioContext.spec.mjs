@@ -1,7 +1,9 @@
+import { Blob } from 'node:buffer';
 import { assert } from 'chai';
 import {
   AudioContext,
   AudioBuffer,
+  AudioWorkletNode,
   OfflineAudioContext,
 } from '../index.mjs';
 
@@ -36,6 +38,87 @@ describe('# OfflineAudioContext', () => {
       assert.deepEqual(aResult, bResult);
     });
   });
-});
 
+  describe('## renderCapacity', () => {
+    it('should dispatch update events measured against audio time', async () => {
+      const offline = new OfflineAudioContext(1, 48000, 48000);
+      const events = [];
+
+      offline.renderCapacity.addEventListener('update', e => events.push(e));
+      offline.renderCapacity.start({ updateInterval: 0.25 });
+
+      const osc = offline.createOscillator();
+      osc.connect(offline.destination);
+      osc.start();
+
+      await offline.startRendering();
+      // events are dispatched asynchronously from the render thread
+      await new Promise(resolve => setTimeout(resolve, 50));
+
+      assert.isAtLeast(events.length, 3);
+      assert.closeTo(events[1].timestamp - events[0].timestamp, 0.25, 128 / 48000);
+      events.forEach(e => assert.isAtLeast(e.peakLoad, e.averageLoad));
+    });
+  });
+
+  describe('## AudioWorklet channels', () => {
+    it('should recycle the channels of closed contexts', async function() {
+      this.timeout(60000);
+
+      // index of the slot of the channel in the slab, cf. `pack_worklet_id`
+      const slotIndex = context => {
+        const kNapiObj = Object.getOwnPropertySymbols(context)
+          .find(symbol => symbol.description === 'node-web-audio-api:napi-obj');
+        return Number(BigInt(context[kNapiObj].workletIds[0]) & 0xffffffffn);
+      };
 
+      // release the slots of the contexts of the previous tests, cf. `--expose-gc`
+      if (global.gc) {
+        global.gc();
+        await new Promise(resolve => setImmediate(resolve));
+      }
+
+      // the contexts are kept alive so that only the explicit releases on
+      // `startRendering` and `close` can free their slot
+      const contexts = [];
+      const slotIndices = new Set();
+
+      for (let i = 0; i < 300; i++) {
+        const offline = new OfflineAudioContext(1, 128, 48000);
+        contexts.push(offline);
+        slotIndices.add(slotIndex(offline));
+        await offline.startRendering();
+      }
+
+      for (let i = 0; i < 100; i++) {
+        const context = new AudioContext({ sinkId: { type: 'none' } });
+        contexts.push(context);
+        slotIndices.add(slotIndex(context));
+        await context.close();
+      }
+
+      // without reuse, each live context would hold its own slot, the few
+      // other indices are slots released by the garbage collector meanwhile
+      assert.isBelow(slotIndices.size, 10);
+
+      // worklets still work in a reused slot
+      const blob = new Blob([`
+        registerProcessor('constant', class extends AudioWorkletProcessor {
+          process(inputs, outputs) {
+            outputs[0][0].fill(0.5);
+            return true;
+          }
+        });
+      `], { type: 'application/javascript' });
+
+      const offline = new OfflineAudioContext(1, 128, 48000);
+      await offline.audioWorklet.addModule(URL.createObjectURL(blob));
+
+      const node = new AudioWorkletNode(offline, 'constant', { numberOfInputs: 0 });
+      node.connect(offline.destination);
+
+      const buffer = await offline.startRendering();
+      assert.equal(buffer.getChannelData(0)[127], 0.5);
+    });
+  });
+});
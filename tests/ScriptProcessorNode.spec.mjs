@@ -0,0 +1,55 @@
+import { assert } from 'chai';
+import {
+  AudioContext,
+  OfflineAudioContext,
+  ScriptProcessorNode,
+} from '../index.mjs';
+
+function sleep(ms) {
+  return new Promise(resolve => setTimeout(resolve, ms));
+}
+
+describe('# ScriptProcessorNode', () => {
+  it('should validate the overflow option', () => {
+    const offline = new OfflineAudioContext(1, 128, 48000);
+
+    assert.throws(() => new ScriptProcessorNode(offline, { overflow: 'nope' }), TypeError);
+
+    const node = new ScriptProcessorNode(offline, { overflow: 'drop' });
+    assert.equal(node.droppedEvents, 0);
+  });
+
+  it('should expose ref() and unref()', () => {
+    const offline = new OfflineAudioContext(1, 128, 48000);
+    const node = offline.createScriptProcessor(256, 1, 1);
+
+    assert.equal(node.ref(), node);
+    assert.equal(node.unref(), node);
+  });
+
+  it('should drop the events when the handler lags behind', async () => {
+    const audioContext = new AudioContext({ sinkId: { type: 'none' } });
+    const node = new ScriptProcessorNode(audioContext, {
+      bufferSize: 256,
+      numberOfInputChannels: 1,
+      numberOfOutputChannels: 1,
+      overflow: 'drop',
+    });
+
+    let events = 0;
+    node.onaudioprocess = () => {
+      events += 1;
+      // handler 3 times slower than realtime
+      const start = Date.now();
+      while (Date.now() - start < 16) {}
+    };
+
+    node.connect(audioContext.destination);
+
+    await sleep(500);
+    await audioContext.close();
+
+    assert.isAbove(events, 0);
+    assert.isAbove(node.droppedEvents, 0);
+  });
+});